@@ -4,9 +4,8 @@ import './App.css';
 import TextField from '@material-ui/core/TextField'
 import Container from '@material-ui/core/Box'
 import Divider from '@material-ui/core/Divider'
-import * as Layout from './Layout'
 
-import { LAMPORTS_PER_SOL,Account, PublicKey, Connection, SystemProgram ,Transaction,sendAndConfirmTransaction} from '@solana/web3.js';
+import { LAMPORTS_PER_SOL,Account, PublicKey, Connection ,Transaction,sendAndConfirmTransaction} from '@solana/web3.js';
 import { Button,Grid } from '@material-ui/core';
 import { HelloWorld } from './HelloWorld';
 
@@ -26,7 +25,7 @@ class Content extends React.Component {
     let url =  'https://api.devnet.solana.com';
     this.connection = new Connection(url);
     this.programID = new PublicKey("3tpz9jRHR79GM4xMBGpJjqEpYn9NNNEeN38UCG124i5z");
-    this.messageAccount = new Account();
+    this.messageSeed = "hello";
     this.playerPrivKey = [82,225,254,52,201,68,118,133,116,22,32,184,203,37,211,93,152,39,183,144,80,221,96,180,32,218,118,10,16,137,239,56,223,133,86,36,186,139,88,155,30,10,104,80,106,84,55,105,47,30,248,38,224,88,195,126,248,29,120,173,150,207,110,168];
     this.playerAccount = new Account(this.playerPrivKey);
   }
@@ -57,16 +56,26 @@ class Content extends React.Component {
   }
 
   async onErase() {
+    let messageAccountKey = await HelloWorld.findMessageAddress(
+      this.playerAccount.publicKey,
+      this.programID,
+      this.messageSeed,
+    );
+
+    let configAccountKey = await HelloWorld.findConfigAddress(this.programID);
+
     let trxi = HelloWorld.createEraseInstruction(
       this.playerAccount.publicKey,
-      this.messageAccount.publicKey,
+      messageAccountKey,
+      this.playerAccount.publicKey,
+      configAccountKey,
       this.programID,
     );
 
     const transaction = new Transaction();
     transaction.add(trxi);
 
-    let signers= [this.playerAccount, this.messageAccount];
+    let signers= [this.playerAccount];
     sendAndConfirmTransaction(this.connection, transaction, signers, {
         skipPreflight: false,
         commitment: 'recent',
@@ -80,31 +89,40 @@ class Content extends React.Component {
 
   async onHello() {
 
-    let messageNeeded = await this.connection.getMinimumBalanceForRentExemption(Layout.messagSpace);
-
-    const trxi0 =  SystemProgram.createAccount({
-      fromPubkey: this.playerAccount.publicKey,
-      newAccountPubkey: this.messageAccount.publicKey,
-      lamports: messageNeeded,
-      space: Layout.messagSpace,
-      programId: this.programID,
-    });
-
-    console.log("message:", this.messageAccount.publicKey.toBase58());
+    // 消息账号由作者公钥和seed推导(PDA)，不用再生成并保存随机的Account
+    let messageAccountKey = await HelloWorld.findMessageAddress(
+      this.playerAccount.publicKey,
+      this.programID,
+      this.messageSeed,
+    );
+    console.log("message:", messageAccountKey.toBase58());
 
+    const transaction = new Transaction();
+    let configAccountKey = await HelloWorld.findConfigAddress(this.programID);
+
+    let messageAccountInfo = await this.connection.getAccountInfo(messageAccountKey);
+    if (messageAccountInfo === null) {
+      let counterAccountKey = await HelloWorld.findMessageCounterAddress(this.programID);
+      transaction.add(HelloWorld.createInitializeInstruction(
+        this.playerAccount.publicKey,
+        messageAccountKey,
+        configAccountKey,
+        counterAccountKey,
+        this.programID,
+        this.messageSeed,
+      ));
+    }
 
     let trxi = HelloWorld.createHelloInstruction(
       this.playerAccount.publicKey,
-      this.messageAccount.publicKey,
+      messageAccountKey,
+      configAccountKey,
       this.programID,
       "hello world!",
     );
-
-    const transaction = new Transaction();
-    transaction.add(trxi0);
     transaction.add(trxi);
 
-    let signers= [this.playerAccount, this.messageAccount];
+    let signers= [this.playerAccount];
     sendAndConfirmTransaction(this.connection, transaction, signers, {
         skipPreflight: false,
         commitment: 'recent',
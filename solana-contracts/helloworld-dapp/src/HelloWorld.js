@@ -7,38 +7,222 @@ import {
     SystemProgram,
     TransactionInstruction,
     SYSVAR_CLOCK_PUBKEY } from "@solana/web3.js"
-import {u64} from '@solana/spl-token'
+import {u64, TOKEN_PROGRAM_ID} from '@solana/spl-token'
 import * as BufferLayout from 'buffer-layout'
 
+/**
+ * First byte of the Borsh encoded instructions, followed by the version of the encoding
+ */
+const VERSIONED_INSTRUCTION_TAG = 255;
+const INSTRUCTION_VERSION = 3;
+
+/**
+ * Errors returned by the hello-world program, indexed by their custom error code
+ */
+export const HelloWorldErrors = [
+    'InvalidInstruction',
+    'IncorrectAccountOwner',
+    'Unauthorized',
+    'AccountClosed',
+    'Overflow',
+    'MessageTooLong',
+    'InvalidUtf8',
+    'AccountNotInitialized',
+    'NotRentExempt',
+    'UnsupportedInstructionVersion',
+    'UnsupportedStateVersion',
+    'AccountNotMigrated',
+    'MessageFinalized',
+    'InvalidChunkAccount',
+    'VersionMismatch',
+    'DelegateExpired',
+    'InvalidNumberOfProvidedSigners',
+    'InvalidNumberOfRequiredSigners',
+    'InvalidConfigAccount',
+    'InvalidFeeAccount',
+    'InsufficientFeeFunds',
+    'ProgramPaused',
+    'InvalidParent',
+    'InvalidChannelAccount',
+    'AlreadyReacted',
+    'MessageNotExpired',
+    'InvalidSignature',
+    'NonceAlreadyUsed',
+    'RevealTooEarly',
+    'RevealTooLate',
+    'CommitmentMismatch',
+    'PollNotOpen',
+    'PollClosed',
+    'InvalidPollOption',
+    'AlreadyVoted',
+    'InvalidWeightAccount',
+    'BountyNotExpired',
+    'InvalidReply',
+    'CommitmentNotExpired',
+    'PollNotClosed',
+];
+
 /**
  * HelloWorld
  */
 export class HelloWorld {
+    /**
+     * Turn a `Custom(n)` program error back into the name of the hello-world error,
+     * accepts either the error code or the `InstructionError` of a failed transaction
+     */
+    static decodeError(error) {
+        let code = error;
+        if (error && error.InstructionError) {
+            code = error.InstructionError[1].Custom;
+        }
+        if (typeof code !== 'number') {
+            return null;
+        }
+        return HelloWorldErrors[code] || null;
+    }
+
+    /**
+     * Derive the message account of an author for the given seed
+     */
+    static async findMessageAddress(
+        authorAccountKey,
+        programID,
+        seed,
+    ) {
+        const [messageAccountKey] = await PublicKey.findProgramAddress(
+            [
+                Buffer.from('message', 'utf8'),
+                authorAccountKey.toBuffer(),
+                Buffer.from(seed, 'utf8'),
+            ],
+            programID,
+        );
+        return messageAccountKey;
+    }
+
+    /**
+     * Derive the config account of the program
+     */
+    static async findConfigAddress(
+        programID,
+    ) {
+        const [configAccountKey] = await PublicKey.findProgramAddress(
+            [Buffer.from('config', 'utf8')],
+            programID,
+        );
+        return configAccountKey;
+    }
+
+    /**
+     * Derive the account counting the messages created by the program
+     */
+    static async findMessageCounterAddress(
+        programID,
+    ) {
+        const [counterAccountKey] = await PublicKey.findProgramAddress(
+            [Buffer.from('message_counter', 'utf8')],
+            programID,
+        );
+        return counterAccountKey;
+    }
+
+    static createInitializeInstruction(
+        playerAccountKey,
+        messageAccountKey,
+        configAccountKey,
+        counterAccountKey,
+        programID,
+        seed,
+    ) {
+
+        const seedLength = Buffer.byteLength(seed, 'utf8');
+        const dataLayout = BufferLayout.struct([
+            BufferLayout.u8("tag"),
+            BufferLayout.u8("version"),
+            BufferLayout.u8("i"),
+            BufferLayout.u32("seedLength"),
+            BufferLayout.blob(seedLength,"seed"),
+        ]);
+
+        const data = Buffer.alloc(dataLayout.span);
+        dataLayout.encode(
+            {
+              tag: VERSIONED_INSTRUCTION_TAG,
+              version: INSTRUCTION_VERSION,
+              i:2, // initialize
+              seedLength,
+              seed: Buffer.from(seed, 'utf8'),
+            },
+            data,
+        );
+
+        let keys = [
+            {pubkey: playerAccountKey, isSigner: true, isWritable: true},
+            {pubkey: messageAccountKey, isSigner: false, isWritable: true},
+            {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+            {pubkey: configAccountKey, isSigner: false, isWritable: false},
+            {pubkey: counterAccountKey, isSigner: false, isWritable: true},
+        ];
+
+        const  trxi = new TransactionInstruction({
+            keys,
+            programId: programID,
+            data,
+        });
+        return trxi;
+    }
+
+    /**
+     * `feeAccountKeys` ({source, treasury, owner}) pays the posting fee when the program
+     * config charges one
+     */
     static createHelloInstruction(
         playerAccountKey,
         messageAccountKey,
+        configAccountKey,
         programID,
         message,
+        feeAccountKeys,
     ) {
 
+        const messageLength = Buffer.byteLength(message, 'utf8');
         const dataLayout = BufferLayout.struct([
+            BufferLayout.u8("tag"),
+            BufferLayout.u8("version"),
             BufferLayout.u8("i"),
-            BufferLayout.blob(message.length,"message"),
+            BufferLayout.u32("messageLength"),
+            BufferLayout.blob(messageLength,"message"),
+            BufferLayout.u8("expectedVersion"),
+            BufferLayout.u8("parent"),
         ]);
       
         const data = Buffer.alloc(dataLayout.span);
         dataLayout.encode(
             {
+              tag: VERSIONED_INSTRUCTION_TAG,
+              version: INSTRUCTION_VERSION,
               i:0, // hello
+              messageLength,
               message: Buffer.from(message, 'utf8'),
+              expectedVersion: 0, // None
+              parent: 0, // None
             },
             data,
         );
       
         let keys = [
             {pubkey: playerAccountKey, isSigner: true, isWritable: true},
-            {pubkey: messageAccountKey, isSigner: true, isWritable: true},
+            {pubkey: messageAccountKey, isSigner: false, isWritable: true},
+            {pubkey: configAccountKey, isSigner: false, isWritable: false},
         ];
+        if (feeAccountKeys) {
+            keys.push(
+                {pubkey: feeAccountKeys.source, isSigner: false, isWritable: true},
+                {pubkey: feeAccountKeys.treasury, isSigner: false, isWritable: true},
+                {pubkey: feeAccountKeys.owner, isSigner: true, isWritable: false},
+                {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+            );
+        }
 
         const  trxi = new TransactionInstruction({
             keys,
@@ -52,25 +236,32 @@ export class HelloWorld {
     static createEraseInstruction(
         playerAccountKey,
         messageAccountKey,
+        destinationAccountKey,
+        configAccountKey,
         programID,
-        message,
     ) {
 
         const dataLayout = BufferLayout.struct([
+            BufferLayout.u8("tag"),
+            BufferLayout.u8("version"),
             BufferLayout.u8("i"),
         ]);
       
         const data = Buffer.alloc(dataLayout.span);
         dataLayout.encode(
             {
+              tag: VERSIONED_INSTRUCTION_TAG,
+              version: INSTRUCTION_VERSION,
               i:1, // erase
             },
             data,
         );
       
         let keys = [
-            {pubkey: playerAccountKey, isSigner: true, isWritable: true},
-            {pubkey: messageAccountKey, isSigner: true, isWritable: true},
+            {pubkey: playerAccountKey, isSigner: true, isWritable: false},
+            {pubkey: messageAccountKey, isSigner: false, isWritable: true},
+            {pubkey: destinationAccountKey, isSigner: false, isWritable: true},
+            {pubkey: configAccountKey, isSigner: false, isWritable: false},
         ];
 
         const  trxi = new TransactionInstruction({
@@ -9,4 +9,7 @@ export function publicKey(property) {
     return BufferLayout.blob(32, property);
 }
 
-export const messagSpace = 32+1+256;
+// version, author, created_at, updated_at, edit_count, chunk_count, finalized,
+// created_slot, updated_slot, history_index, history, delegate, delegate_expiry_slot,
+// authority, parent, reply_count, reactions, ttl, reserved, message
+export const messagSpace = 1+32+8+8+8+4+1+8+8+1+8*32+36+8+36+36+8+6*8+8+54+1+256;
@@ -1,5 +1,9 @@
 //! Program entrypoint
 
+// entrypoint!宏会检查solana-program自己的cfg，新的rustc会把它们当成未知的cfg报警；
+// 老的rustc不认识unexpected_cfgs这个lint，所以要一起允许unknown_lints
+#![allow(unknown_lints, unexpected_cfgs)]
+
 use crate::{error::HelloWorldError, processor::Processor};
 use solana_program::{
     account_info::AccountInfo, entrypoint, entrypoint::ProgramResult,
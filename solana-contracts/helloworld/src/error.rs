@@ -14,6 +14,123 @@ pub enum HelloWorldError {
     /// Invalid instruction
     #[error("Invalid instruction")]
     InvalidInstruction,
+    /// The message account is not owned by the hello-world program
+    #[error("Message account is not owned by the program")]
+    IncorrectAccountOwner,
+    /// The signer is not the recorded author of the message
+    #[error("Signer is not allowed to modify the message")]
+    Unauthorized,
+    /// The message account has been erased
+    #[error("Message account is closed")]
+    AccountClosed,
+    /// Operation overflowed
+    #[error("Operation overflowed")]
+    Overflow,
+    /// The message does not fit into the message account
+    #[error("Message is too long")]
+    MessageTooLong,
+    /// The message or seed is not valid UTF-8
+    #[error("Invalid UTF-8 string")]
+    InvalidUtf8,
+    /// The message account has no author recorded yet
+    #[error("Message account is not initialized")]
+    AccountNotInitialized,
+    /// The message account does not hold enough lamports to be rent exempt
+    #[error("Message account is not rent exempt")]
+    NotRentExempt,
+    /// The instruction was encoded with a version this program does not know
+    #[error("Unsupported instruction version")]
+    UnsupportedInstructionVersion,
+    /// The message account was written with a layout version this program does not know
+    #[error("Unsupported message account version")]
+    UnsupportedStateVersion,
+    /// The message account still uses the legacy layout
+    #[error("Message account must be migrated first")]
+    AccountNotMigrated,
+    /// The message was finalized and can not be changed anymore
+    #[error("Message is finalized")]
+    MessageFinalized,
+    /// A chunk account does not belong to the message at the expected position
+    #[error("Invalid message chunk account")]
+    InvalidChunkAccount,
+    /// The message was updated since the version the update is based on
+    #[error("Message version does not match")]
+    VersionMismatch,
+    /// The grant of the delegate signing the update has expired
+    #[error("Delegate grant has expired")]
+    DelegateExpired,
+    /// Invalid number of provided signers
+    #[error("Invalid number of provided signers")]
+    InvalidNumberOfProvidedSigners,
+    /// Invalid number of required signers
+    #[error("Invalid number of required signers")]
+    InvalidNumberOfRequiredSigners,
+    /// The config account is not the one derived by `find_config_address`
+    #[error("Invalid config account")]
+    InvalidConfigAccount,
+    /// A fee token account does not match the program config
+    #[error("Invalid fee account")]
+    InvalidFeeAccount,
+    /// The fee source account holds less than the posting fee
+    #[error("Insufficient funds for the posting fee")]
+    InsufficientFeeFunds,
+    /// The program is paused by the admin of the config
+    #[error("Program is paused")]
+    ProgramPaused,
+    /// The parent message can not take this message as a reply
+    #[error("Invalid parent message")]
+    InvalidParent,
+    /// The channel or channel page account does not match the channel
+    #[error("Invalid channel account")]
+    InvalidChannelAccount,
+    /// The wallet already reacted to the message
+    #[error("Already reacted to the message")]
+    AlreadyReacted,
+    /// The message has no time to live, or it has not passed yet
+    #[error("Message has not expired")]
+    MessageNotExpired,
+    /// No ed25519 verification of the signed message in the transaction
+    #[error("Missing or mismatched ed25519 signature")]
+    InvalidSignature,
+    /// The nonce of the signed message was already used
+    #[error("Nonce already used")]
+    NonceAlreadyUsed,
+    /// The reveal window of the commitment has not started yet
+    #[error("Reveal too early")]
+    RevealTooEarly,
+    /// The deadline of the commitment has passed
+    #[error("Reveal too late")]
+    RevealTooLate,
+    /// The revealed message and salt do not hash to the commitment
+    #[error("Revealed message does not match the commitment")]
+    CommitmentMismatch,
+    /// The poll does not accept votes yet
+    #[error("Poll is not open")]
+    PollNotOpen,
+    /// The poll does not accept votes anymore
+    #[error("Poll is closed")]
+    PollClosed,
+    /// The poll option does not exist, or the options of a new poll are invalid
+    #[error("Invalid poll option")]
+    InvalidPollOption,
+    /// The wallet already voted in the poll
+    #[error("Already voted in the poll")]
+    AlreadyVoted,
+    /// The token account weighting the vote is not a funded account of the voter in the vote mint
+    #[error("Invalid vote weight account")]
+    InvalidWeightAccount,
+    /// The bounty can not be cancelled before it expires
+    #[error("Bounty has not expired")]
+    BountyNotExpired,
+    /// The awarded message is not a reply to the question of another author
+    #[error("Message is not a reply eligible for the bounty")]
+    InvalidReply,
+    /// The commitment can not be closed while it can still be revealed
+    #[error("Commitment has not expired")]
+    CommitmentNotExpired,
+    /// The poll still accepts votes
+    #[error("Poll is not closed")]
+    PollNotClosed,
 }
 
 impl From<HelloWorldError> for ProgramError {
@@ -35,6 +152,63 @@ impl PrintProgramError for HelloWorldError {
     {
         match self {
             HelloWorldError::InvalidInstruction => msg!("Invalid instruction"),
+            HelloWorldError::IncorrectAccountOwner => {
+                msg!("Error: Message account is not owned by the program")
+            }
+            HelloWorldError::Unauthorized => msg!("Error: Signer is not allowed to modify the message"),
+            HelloWorldError::AccountClosed => msg!("Error: Message account is closed"),
+            HelloWorldError::Overflow => msg!("Error: Operation overflowed"),
+            HelloWorldError::MessageTooLong => msg!("Error: Message is too long"),
+            HelloWorldError::InvalidUtf8 => msg!("Error: Invalid UTF-8 string"),
+            HelloWorldError::AccountNotInitialized => {
+                msg!("Error: Message account is not initialized")
+            }
+            HelloWorldError::NotRentExempt => msg!("Error: Message account is not rent exempt"),
+            HelloWorldError::UnsupportedInstructionVersion => {
+                msg!("Error: Unsupported instruction version")
+            }
+            HelloWorldError::UnsupportedStateVersion => {
+                msg!("Error: Unsupported message account version")
+            }
+            HelloWorldError::AccountNotMigrated => msg!("Error: Message account must be migrated first"),
+            HelloWorldError::MessageFinalized => msg!("Error: Message is finalized"),
+            HelloWorldError::InvalidChunkAccount => msg!("Error: Invalid message chunk account"),
+            HelloWorldError::VersionMismatch => msg!("Error: Message version does not match"),
+            HelloWorldError::DelegateExpired => msg!("Error: Delegate grant has expired"),
+            HelloWorldError::InvalidNumberOfProvidedSigners => {
+                msg!("Error: Invalid number of provided signers")
+            }
+            HelloWorldError::InvalidNumberOfRequiredSigners => {
+                msg!("Error: Invalid number of required signers")
+            }
+            HelloWorldError::InvalidConfigAccount => msg!("Error: Invalid config account"),
+            HelloWorldError::InvalidFeeAccount => msg!("Error: Invalid fee account"),
+            HelloWorldError::InsufficientFeeFunds => {
+                msg!("Error: Insufficient funds for the posting fee")
+            }
+            HelloWorldError::ProgramPaused => msg!("Error: Program is paused"),
+            HelloWorldError::InvalidParent => msg!("Error: Invalid parent message"),
+            HelloWorldError::InvalidChannelAccount => msg!("Error: Invalid channel account"),
+            HelloWorldError::AlreadyReacted => msg!("Error: Already reacted to the message"),
+            HelloWorldError::MessageNotExpired => msg!("Error: Message has not expired"),
+            HelloWorldError::InvalidSignature => msg!("Error: Missing or mismatched ed25519 signature"),
+            HelloWorldError::NonceAlreadyUsed => msg!("Error: Nonce already used"),
+            HelloWorldError::RevealTooEarly => msg!("Error: Reveal too early"),
+            HelloWorldError::RevealTooLate => msg!("Error: Reveal too late"),
+            HelloWorldError::CommitmentMismatch => {
+                msg!("Error: Revealed message does not match the commitment")
+            }
+            HelloWorldError::PollNotOpen => msg!("Error: Poll is not open"),
+            HelloWorldError::PollClosed => msg!("Error: Poll is closed"),
+            HelloWorldError::InvalidPollOption => msg!("Error: Invalid poll option"),
+            HelloWorldError::AlreadyVoted => msg!("Error: Already voted in the poll"),
+            HelloWorldError::InvalidWeightAccount => msg!("Error: Invalid vote weight account"),
+            HelloWorldError::BountyNotExpired => msg!("Error: Bounty has not expired"),
+            HelloWorldError::InvalidReply => {
+                msg!("Error: Message is not a reply eligible for the bounty")
+            }
+            HelloWorldError::CommitmentNotExpired => msg!("Error: Commitment has not expired"),
+            HelloWorldError::PollNotClosed => msg!("Error: Poll is not closed"),
         }
     }
 }
\ No newline at end of file
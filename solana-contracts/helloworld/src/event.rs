@@ -0,0 +1,374 @@
+//! Program events
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::{BorshDeserialize, BorshSerialize};
+use crate::state::{Reaction, Timepoint};
+use solana_program::{clock::Slot, hash::HASH_BYTES, msg, pubkey::Pubkey};
+
+/// Prefix of the log lines carrying an event, followed by the base64 encoded Borsh event.
+/// The runtime prepends `Program log: ` to the whole line.
+pub const EVENT_LOG_PREFIX: &str = "hello-world-event: ";
+
+/// State changes logged by the hello-world program. A successful instruction logs one event
+/// per change, e.g. a reply `Hello` logs the posted message and the new reply.
+///
+/// New events are only ever appended, so the Borsh tag of an existing one never changes.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub enum HelloWorldEvent {
+    /// A message account was created
+    MessageInitialized {
+        /// message account
+        message: Pubkey,
+        /// author of the message
+        author: Pubkey,
+    },
+    /// A message was posted
+    MessagePosted {
+        /// message account
+        message: Pubkey,
+        /// authority or delegate who posted it
+        poster: Pubkey,
+        /// `edit_count` of the message after the post
+        edit_count: u64,
+        /// text of the message
+        text: String,
+    },
+    /// A message account and its chunks were closed
+    MessageErased {
+        /// message account
+        message: Pubkey,
+        /// account receiving the lamports
+        destination: Pubkey,
+    },
+    /// A legacy message account was copied into a new message account and closed
+    MessageMigrated {
+        /// closed legacy message account
+        legacy_message: Pubkey,
+        /// new message account
+        message: Pubkey,
+        /// author of the message
+        author: Pubkey,
+    },
+    /// A chunk was appended to a message
+    ChunkAppended {
+        /// message account
+        message: Pubkey,
+        /// chunk account
+        chunk: Pubkey,
+        /// position of the chunk in the message
+        index: u32,
+    },
+    /// A message was finalized
+    MessageFinalized {
+        /// message account
+        message: Pubkey,
+    },
+    /// A delegate was approved to post a message
+    DelegateApproved {
+        /// message account
+        message: Pubkey,
+        /// approved delegate
+        delegate: Pubkey,
+        /// first slot at which the delegate can not post anymore
+        expiry_slot: Slot,
+    },
+    /// The delegate of a message was revoked
+    DelegateRevoked {
+        /// message account
+        message: Pubkey,
+    },
+    /// A multisignature account was initialized
+    MultisigInitialized {
+        /// multisignature account
+        multisig: Pubkey,
+        /// number of signers required
+        m: u8,
+        /// number of valid signers
+        n: u8,
+    },
+    /// The authority of a message changed
+    AuthorityChanged {
+        /// message account
+        message: Pubkey,
+        /// previous authority
+        old_authority: Pubkey,
+        /// new authority, the author when it returns to the single author mode
+        new_authority: Pubkey,
+    },
+    /// The program config was created
+    ConfigInitialized {
+        /// admin of the config
+        admin: Pubkey,
+        /// mint of the posting fee
+        fee_mint: Pubkey,
+        /// posting fee
+        fee_amount: u64,
+        /// token account receiving the fees
+        treasury: Pubkey,
+    },
+    /// The posting fee of the program config changed
+    ConfigUpdated {
+        /// mint of the posting fee
+        fee_mint: Pubkey,
+        /// posting fee
+        fee_amount: u64,
+        /// token account receiving the fees
+        treasury: Pubkey,
+    },
+    /// The program was paused or unpaused
+    PauseChanged {
+        /// `true` once paused
+        paused: bool,
+    },
+    /// The admin of the program config changed
+    AdminTransferred {
+        /// previous admin
+        old_admin: Pubkey,
+        /// new admin
+        new_admin: Pubkey,
+    },
+    /// A message replied to another one for the first time
+    ReplyPosted {
+        /// message account of the reply
+        message: Pubkey,
+        /// message account replied to
+        parent: Pubkey,
+        /// `reply_count` of the parent after the reply
+        reply_count: u64,
+    },
+    /// A channel was created
+    ChannelCreated {
+        /// channel account
+        channel: Pubkey,
+        /// topic of the channel
+        topic: String,
+        /// account which created the channel
+        creator: Pubkey,
+    },
+    /// A message was posted to a channel
+    ChannelPosted {
+        /// channel account
+        channel: Pubkey,
+        /// message account
+        message: Pubkey,
+        /// position of the message in the channel, starting at 0
+        position: u64,
+    },
+    /// A wallet reacted to a message
+    Reacted {
+        /// message account
+        message: Pubkey,
+        /// wallet which reacted
+        reactor: Pubkey,
+        /// kind of reaction
+        reaction: Reaction,
+        /// number of reactions of this kind after this one
+        count: u64,
+    },
+    /// A wallet took its reaction back
+    Unreacted {
+        /// message account
+        message: Pubkey,
+        /// wallet which reacted
+        reactor: Pubkey,
+        /// kind of reaction
+        reaction: Reaction,
+        /// number of reactions of this kind left, zero if the message was erased since
+        count: u64,
+    },
+    /// The time to live of a message changed
+    TtlSet {
+        /// message account
+        message: Pubkey,
+        /// seconds without update after which the message can be reaped, zero for never
+        ttl: u64,
+    },
+    /// An expired message account and its chunks were closed by `Reap`
+    MessageReaped {
+        /// message account
+        message: Pubkey,
+        /// caller of `Reap`, receiving the bounty
+        reaper: Pubkey,
+        /// author of the message, receiving the rest of the lamports
+        author: Pubkey,
+        /// lamports paid to the reaper
+        bounty: u64,
+        /// lamports returned to the author
+        refund: u64,
+    },
+    /// The bounty share of `Reap` changed
+    ReapBountyUpdated {
+        /// share of the lamports paid to the reaper, in basis points
+        bounty_bps: u16,
+    },
+    /// A direct message was added to the inbox of its recipient
+    DirectMessageSent {
+        /// direct message account
+        message: Pubkey,
+        /// wallet which sent the message
+        sender: Pubkey,
+        /// wallet the message is sealed for
+        recipient: Pubkey,
+        /// position of the message in the inbox of the recipient
+        index: u64,
+    },
+    /// The hash of a message to reveal later was committed
+    MessageCommitted {
+        /// message account
+        message: Pubkey,
+        /// commitment account
+        commitment: Pubkey,
+        /// `commitment_hash` of the message and its salt
+        hash: [u8; HASH_BYTES],
+        /// the message can be revealed from this point on
+        reveal_after: Timepoint,
+        /// the message can not be revealed anymore from this point on
+        reveal_deadline: Timepoint,
+    },
+    /// A committed message was revealed, after `MessagePosted`
+    MessageRevealed {
+        /// message account
+        message: Pubkey,
+        /// commitment account, now closed
+        commitment: Pubkey,
+    },
+    /// A message signed off-chain by its author was posted by a relayer, after `MessagePosted`
+    SignedMessageRelayed {
+        /// message account
+        message: Pubkey,
+        /// author who signed the message
+        author: Pubkey,
+        /// signer paying for the transaction
+        relayer: Pubkey,
+        /// nonce of the signature, now used
+        nonce: u64,
+    },
+    /// A poll was created
+    PollCreated {
+        /// poll account
+        poll: Pubkey,
+        /// account which created the poll
+        creator: Pubkey,
+        /// question of the poll
+        question: String,
+        /// labels of the options
+        options: Vec<String>,
+        /// votes are accepted from this point on
+        opens_at: Timepoint,
+        /// votes are not accepted anymore from this point on
+        closes_at: Timepoint,
+        /// mint weighting the votes, if any
+        vote_mint: Option<Pubkey>,
+    },
+    /// A wallet voted in a poll
+    Voted {
+        /// poll account
+        poll: Pubkey,
+        /// wallet which voted
+        voter: Pubkey,
+        /// index of the option voted for
+        option: u8,
+        /// weight of the vote
+        weight: u64,
+        /// tally of the option after the vote
+        votes: u64,
+    },
+    /// A bounty was posted on a question
+    BountyPosted {
+        /// message account asking the question
+        question: Pubkey,
+        /// bounty vault
+        bounty: Pubkey,
+        /// author who posted the bounty
+        author: Pubkey,
+        /// lamports held by the vault for the awarded reply
+        amount: u64,
+        /// the bounty can be cancelled from this point on
+        expires_at: Timepoint,
+    },
+    /// A bounty was paid to the author of a reply, the vault is closed
+    BountyAwarded {
+        /// message account asking the question
+        question: Pubkey,
+        /// awarded reply
+        reply: Pubkey,
+        /// author of the reply, receiving the bounty
+        recipient: Pubkey,
+        /// lamports paid
+        amount: u64,
+    },
+    /// An expired bounty was cancelled, the vault is closed
+    BountyCancelled {
+        /// message account asking the question
+        question: Pubkey,
+        /// author who posted the bounty, receiving it back
+        author: Pubkey,
+        /// lamports refunded on top of the rent of the vault
+        amount: u64,
+    },
+    /// A commitment was closed after its deadline without being revealed
+    CommitmentClosed {
+        /// message account
+        message: Pubkey,
+        /// commitment account, now closed
+        commitment: Pubkey,
+        /// committer receiving the rent back
+        committer: Pubkey,
+    },
+    /// A vote receipt was closed after the poll closed, the escrowed tokens went back
+    VoteWithdrawn {
+        /// poll account
+        poll: Pubkey,
+        /// wallet which voted
+        voter: Pubkey,
+        /// weight of the vote, the tokens returned when the poll has a vote mint
+        weight: u64,
+    },
+}
+
+impl HelloWorldEvent {
+    /// Logs the event under `EVENT_LOG_PREFIX`.
+    pub fn emit(&self) {
+        msg!("{}", self.to_log());
+    }
+
+    /// Formats the event as logged by `emit`, without the `Program log: ` prefix of the runtime.
+    pub fn to_log(&self) -> String {
+        // writing into a Vec can not fail
+        let data = self.try_to_vec().unwrap();
+        format!("{}{}", EVENT_LOG_PREFIX, STANDARD.encode(data))
+    }
+
+    /// Decodes the event of a log line written by `emit`, with or without the `Program log: `
+    /// prefix of the runtime. Returns `None` for any other line.
+    pub fn from_log(log: &str) -> Option<Self> {
+        let log = log.strip_prefix("Program log: ").unwrap_or(log);
+        let data = STANDARD.decode(log.strip_prefix(EVENT_LOG_PREFIX)?).ok()?;
+        Self::try_from_slice(&data).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_event_from_log() {
+        let event = HelloWorldEvent::MessagePosted {
+            message: Pubkey::new_from_array([1; 32]),
+            poster: Pubkey::new_from_array([2; 32]),
+            edit_count: 3,
+            text: "hello".to_string(),
+        };
+        let log = event.to_log();
+        assert!(log.starts_with(EVENT_LOG_PREFIX));
+        assert_eq!(HelloWorldEvent::from_log(&log), Some(event.clone()));
+        assert_eq!(HelloWorldEvent::from_log(&format!("Program log: {}", log)), Some(event));
+
+        assert_eq!(HelloWorldEvent::from_log("Program log: hello-world: Hello"), None);
+        assert_eq!(
+            HelloWorldEvent::from_log(&format!("Program log: {}not base64", EVENT_LOG_PREFIX)),
+            None
+        );
+    }
+}
@@ -1,63 +1,2030 @@
 //! Instruction types
 
-use crate::error::HelloWorldError;
+use crate::{
+    error::HelloWorldError,
+    find_bounty_address, find_commitment_address, find_config_address, find_direct_message_address, find_inbox_address,
+    find_message_address, find_message_counter_address, find_nonce_address, find_poll_address, find_poll_vault_address,
+    find_program_data_address, find_vote_address,
+    state::{
+        Channel, DirectMessage, HelloWorldState, MessageChunk, Poll, Reaction, Timepoint, MAX_BPS,
+        MAX_SIGNERS, MIN_SIGNERS, SALT_LEN,
+    },
+};
+use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
+    clock::Slot,
+    hash::HASH_BYTES,
+    instruction::{AccountMeta, Instruction},
     program_error::ProgramError,
+    ed25519_program,
+    pubkey::Pubkey,
+    system_program,
+    sysvar,
 };
-use std::mem::size_of;
-use std::str::from_utf8;
+
+/// First byte of a Borsh encoded instruction, the next byte is the version of the encoding.
+pub const VERSIONED_INSTRUCTION_TAG: u8 = u8::MAX;
+
+/// Version of the Borsh encoded instructions produced by [pack](enum.HelloWorldInstruction.html#method.pack).
+pub const INSTRUCTION_VERSION: u8 = 3;
 
 /// Instructions supported by the hello-world program.
 #[repr(C)]
-#[derive(Clone, Debug, PartialEq)]
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
 pub enum HelloWorldInstruction {
     /// Hello print hello to an Account file
+    ///
+    /// When the program config charges a posting fee, the fee accounts follow the config
+    /// account and `fee_amount` tokens are transferred to the treasury before the message is
+    /// written. Without a fee they are omitted.
+    ///
+    /// A message naming a `parent` becomes a reply to it: the parent account follows the
+    /// config account and counts the reply the first time. Without a parent the accounts
+    /// after it move up by one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[signer]` The authority of the message, or its delegate
+    ///   1. `[writable]` The message account
+    ///   2. `[]` The program config, derived by `find_config_address`
+    ///   3. `[writable]` Optional: the parent message account, if `parent` is set
+    ///   4. `[writable]` Optional: the token account of `fee_mint` paying the fee
+    ///   5. `[writable]` Optional: the treasury token account of the config
+    ///   6. `[signer]` Optional: the owner of the paying token account
+    ///   7. `[]` Optional: SPL Token program
+    ///
+    ///   * Multisignature authority
+    ///   0. `[]` The multisignature authority of the message, or its delegate
+    ///   1. `[writable]` The message account
+    ///   2. `[]` The program config, derived by `find_config_address`
+    ///   3. ..8 Optional: the parent and fee accounts, as above
+    ///   8. ..8+M `[signer]` M signer accounts, moved up by the parent and fee accounts omitted
     Hello{
         /// message for hello
         message: String,
+        /// `edit_count` of the message the update is based on, the update is rejected if the
+        /// message was changed since then
+        expected_version: Option<u64>,
+        /// message account this message replies to
+        parent: Option<Pubkey>,
     },
-    /// Erase free the hello account
+    /// Erase free the hello account, its lamports are sent to the destination
+    /// and its data is wiped so it can not be read or revived afterwards.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[signer]` The authority of the message
+    ///   1. `[writable]` The message account to close
+    ///   2. `[writable]` The destination account of the lamports
+    ///   3. `[]` The program config, derived by `find_config_address`
+    ///   4. ..4+N `[writable]` The N chunk accounts of the message, in order
+    ///
+    ///   * Multisignature authority
+    ///   0. `[]` The multisignature authority of the message
+    ///   1. `[writable]` The message account to close
+    ///   2. `[writable]` The destination account of the lamports
+    ///   3. `[]` The program config, derived by `find_config_address`
+    ///   4. ..4+N `[writable]` The N chunk accounts of the message, in order, followed by
+    ///      M `[signer]` signer accounts
     Erase ,
+    /// Initialize create the message account of an author at a program derived address,
+    /// so clients can find it from the author's pubkey and the seed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The author, funding the new account
+    ///   1. `[writable]` The message account, derived by `find_message_address`
+    ///   2. `[]` System program
+    ///   3. `[]` The program config, derived by `find_config_address`
+    ///   4. `[writable]` The message counter, derived by `find_message_counter_address`
+    Initialize {
+        /// seed of the message account, at most `MAX_SEED_LEN` bytes
+        seed: String,
+    },
+    /// Migrate copy a message account using the legacy layout into a new message account
+    /// of the author with the current layout, then close the legacy account. The legacy
+    /// account can not be grown in place, so the author picks the seed of the new one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The author, funding the new account and receiving the legacy rent
+    ///   1. `[writable]` The legacy message account
+    ///   2. `[writable]` The new message account, derived by `find_message_address`
+    ///   3. `[]` System program
+    ///   4. `[]` The program config, derived by `find_config_address`
+    ///   5. `[writable]` The message counter, derived by `find_message_counter_address`
+    Migrate {
+        /// seed of the new message account, at most `MAX_SEED_LEN` bytes
+        seed: String,
+    },
+    /// AppendChunk store the next part of a message longer than `MAX_MESSAGE_LEN` in a new
+    /// chunk account, derived from the message account and the number of chunks so far.
+    /// Only messages governed by their author can be extended.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The author of the message, funding the chunk account
+    ///   1. `[writable]` The message account
+    ///   2. `[writable]` The chunk account, derived by `find_chunk_address`
+    ///   3. `[]` System program
+    ///   4. `[]` The program config, derived by `find_config_address`
+    AppendChunk {
+        /// text of the chunk, at most `MAX_CHUNK_LEN` bytes
+        data: String,
+    },
+    /// Finalize mark the message as complete, it can not be updated or extended afterwards.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[signer]` The authority of the message
+    ///   1. `[writable]` The message account
+    ///   2. `[]` The program config, derived by `find_config_address`
+    ///
+    ///   * Multisignature authority
+    ///   0. `[]` The multisignature authority of the message
+    ///   1. `[writable]` The message account
+    ///   2. `[]` The program config, derived by `find_config_address`
+    ///   3. ..3+M `[signer]` M signer accounts
+    Finalize,
+    /// Approve let a delegate post the message on behalf of the author until the expiry
+    /// slot, replacing the previous delegate if any.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[signer]` The authority of the message
+    ///   1. `[writable]` The message account
+    ///   2. `[]` The delegate
+    ///   3. `[]` The program config, derived by `find_config_address`
+    ///
+    ///   * Multisignature authority
+    ///   0. `[]` The multisignature authority of the message
+    ///   1. `[writable]` The message account
+    ///   2. `[]` The delegate
+    ///   3. `[]` The program config, derived by `find_config_address`
+    ///   4. ..4+M `[signer]` M signer accounts
+    Approve {
+        /// first slot at which the delegate can not post anymore
+        expiry_slot: Slot,
+    },
+    /// Revoke remove the delegate of the message.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[signer]` The authority of the message
+    ///   1. `[writable]` The message account
+    ///   2. `[]` The program config, derived by `find_config_address`
+    ///
+    ///   * Multisignature authority
+    ///   0. `[]` The multisignature authority of the message
+    ///   1. `[writable]` The message account
+    ///   2. `[]` The program config, derived by `find_config_address`
+    ///   3. ..3+M `[signer]` M signer accounts
+    Revoke,
+    /// InitializeMultisig initialize a multisignature account with N provided signers, it
+    /// can then be set as the authority of message accounts.
+    ///
+    /// The `InitializeMultisig` instruction requires no signers and MUST be included within
+    /// the same Transaction as the system program's `CreateAccount` instruction that creates
+    /// the account being initialized, owned by this program. Otherwise another party can
+    /// acquire ownership of the uninitialized account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The multisignature account to initialize
+    ///   1. `[]` The program config, derived by `find_config_address`
+    ///   2. ..2+N `[]` The signer accounts, must equal to N where 1 <= N <= 11
+    InitializeMultisig {
+        /// The number of signers (M) required to validate this multisignature account
+        m: u8,
+    },
+    /// SetAuthority hand the message over to a new authority, which can be a multisignature
+    /// account. Setting the author back as authority returns to the single author mode.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[signer]` The current authority of the message
+    ///   1. `[writable]` The message account
+    ///   2. `[]` The new authority
+    ///   3. `[]` The program config, derived by `find_config_address`
+    ///
+    ///   * Multisignature authority
+    ///   0. `[]` The current multisignature authority of the message
+    ///   1. `[writable]` The message account
+    ///   2. `[]` The new authority
+    ///   3. `[]` The program config, derived by `find_config_address`
+    ///   4. ..4+M `[signer]` M signer accounts
+    SetAuthority,
+    /// InitConfig create the program config, the signer becomes its admin. Until then
+    /// posting is free, so it should be sent right after the program is deployed. Only the
+    /// upgrade authority of the program can send it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The admin, the upgrade authority funding the config account
+    ///   1. `[writable]` The config account, derived by `find_config_address`
+    ///   2. `[]` The treasury token account receiving the fees, its mint is the fee mint
+    ///   3. `[]` System program
+    ///   4. `[]` The ProgramData account of the program, derived by `find_program_data_address`
+    InitConfig {
+        /// amount of tokens charged by every `Hello`, zero for free posting
+        fee_amount: u64,
+    },
+    /// UpdateConfig change the posting fee and the treasury receiving it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin of the config
+    ///   1. `[writable]` The config account
+    ///   2. `[]` The treasury token account receiving the fees, its mint is the fee mint
+    UpdateConfig {
+        /// amount of tokens charged by every `Hello`, zero for free posting
+        fee_amount: u64,
+    },
+    /// Pause reject every instruction but the admin ones until `Unpause`, e.g. during an
+    /// incident.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin of the config
+    ///   1. `[writable]` The config account
+    Pause,
+    /// Unpause accept the instructions again after a `Pause`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin of the config
+    ///   1. `[writable]` The config account
+    Unpause,
+    /// TransferAdmin hand the config over to a new admin.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin of the config
+    ///   1. `[writable]` The config account
+    ///   2. `[]` The new admin
+    TransferAdmin,
+    /// CreateChannel create the channel of a topic at a program derived address, so
+    /// clients can find it from the topic alone.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The creator, funding the channel account
+    ///   1. `[writable]` The channel account, derived by `find_channel_address`
+    ///   2. `[]` System program
+    ///   3. `[]` The program config, derived by `find_config_address`
+    CreateChannel {
+        /// topic of the channel, at most `MAX_SEED_LEN` bytes
+        topic: String,
+    },
+    /// PostToChannel append a message to the index of a channel, in the current page
+    /// account. The first message of a page creates it. Like `AppendChunk`, only messages
+    /// governed by their author can be posted.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The author of the message, funding a new page account
+    ///   1. `[]` The message account
+    ///   2. `[writable]` The channel account
+    ///   3. `[writable]` The current page account, derived by `find_channel_page_address`
+    ///      from the `next_position` of the channel
+    ///   4. `[]` System program
+    ///   5. `[]` The program config, derived by `find_config_address`
+    PostToChannel,
+    /// React count a reaction of a wallet to a message. A receipt account derived from the
+    /// message and the wallet records it, so each wallet reacts at most once to a message;
+    /// it must `Unreact` first to change its reaction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The reacting wallet, funding the receipt account
+    ///   1. `[writable]` The message account
+    ///   2. `[writable]` The receipt account, derived by `find_reaction_address`
+    ///   3. `[]` System program
+    ///   4. `[]` The program config, derived by `find_config_address`
+    React {
+        /// kind of reaction
+        reaction: Reaction,
+    },
+    /// Unreact take back the reaction of a wallet and close its receipt account, the rent
+    /// goes back to the wallet. It also works once the message was erased, or replaced by a
+    /// new message at the same address whose counters are then left untouched.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The reacting wallet, receiving the rent of the receipt
+    ///   1. `[writable]` The message account
+    ///   2. `[writable]` The receipt account, derived by `find_reaction_address`
+    ///   3. `[]` The program config, derived by `find_config_address`
+    Unreact,
+    /// SetTtl let anyone reap the message once it has not been updated for `ttl` seconds,
+    /// or never when `ttl` is zero. Setting it counts as an update.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[signer]` The authority of the message
+    ///   1. `[writable]` The message account
+    ///   2. `[]` The program config, derived by `find_config_address`
+    ///
+    ///   * Multisignature authority
+    ///   0. `[]` The multisignature authority of the message
+    ///   1. `[writable]` The message account
+    ///   2. `[]` The program config, derived by `find_config_address`
+    ///   3. ..3+M `[signer]` M signer accounts
+    SetTtl {
+        /// seconds without update after which the message can be reaped, zero for never
+        ttl: u64,
+    },
+    /// Reap close an expired message account and its chunks. Anyone may call it: the caller
+    /// gets `reap_bounty_bps` of the lamports of every closed account, the author the rest.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The caller, receiving the bounty
+    ///   1. `[writable]` The expired message account
+    ///   2. `[writable]` The author of the message, receiving the rest of the lamports
+    ///   3. `[]` The program config, derived by `find_config_address`
+    ///   4. ..4+N `[writable]` The N chunk accounts of the message, in order
+    Reap,
+    /// SetReapBounty change the share of the lamports of a reaped message paid to the
+    /// caller of `Reap`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin of the config
+    ///   1. `[writable]` The config account
+    SetReapBounty {
+        /// share of the lamports paid to the caller, in basis points, at most `MAX_BPS`
+        bounty_bps: u16,
+    },
+    /// PostSigned post a message signed off-chain by its author, the transaction being paid
+    /// by a relayer. The transaction must contain an ed25519 program instruction verifying
+    /// the signature of `author` over `signed_post_message`, see `verify_signature`. The
+    /// message account of `author` for `seed` is created when it does not exist, and the
+    /// nonce account marks `nonce` as used so the signature can not be replayed.
+    ///
+    /// When the program config charges a posting fee, the fee accounts follow and the fee is
+    /// paid like for `Hello`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The relayer, paying for the new accounts
+    ///   1. `[writable]` The message account, derived by `find_message_address`
+    ///   2. `[writable]` The nonce account, derived by `find_nonce_address`
+    ///   3. `[]` The instructions sysvar
+    ///   4. `[]` The system program
+    ///   5. `[]` The program config, derived by `find_config_address`
+    ///   6. `[writable]` The message counter, derived by `find_message_counter_address`
+    ///   7. `[writable]` Optional: the token account of `fee_mint` paying the fee
+    ///   8. `[writable]` Optional: the treasury of the config
+    ///   9. `[signer]` Optional: the owner of the paying token account
+    ///   10. `[]` Optional: the token program
+    PostSigned {
+        /// author who signed the message
+        author: Pubkey,
+        /// seed of the message account of the author
+        seed: String,
+        /// message
+        message: String,
+        /// nonce included in the signature, usable once per author
+        nonce: u64,
+    },
+    /// SendDirectMessage add a direct message to the inbox of `recipient`. The payload is
+    /// stored as is, clients encrypt it for the recipient beforehand. The inbox is created by
+    /// the first message it receives, the message account is the next one of the inbox.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The sender, paying for the new accounts
+    ///   1. `[writable]` The inbox of the recipient, derived by `find_inbox_address`
+    ///   2. `[writable]` The direct message account at the `message_count` of the inbox,
+    ///      derived by `find_direct_message_address`
+    ///   3. `[]` The system program
+    ///   4. `[]` The program config, derived by `find_config_address`
+    SendDirectMessage {
+        /// wallet the payload is sealed for
+        recipient: Pubkey,
+        /// encrypted payload, at most `MAX_DIRECT_MESSAGE_LEN` bytes
+        payload: Vec<u8>,
+    },
+    /// Commit store the hash of a message to reveal later, e.g. a sealed bid. Only the
+    /// `commitment_hash` of the message and a salt is public until `Reveal`. A message has a
+    /// single pending commitment, a new one replaces it once its deadline has passed: the
+    /// rent of the expired one goes back to its committer and the author pays it again.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The author of the message, paying for the commitment account
+    ///   1. `[]` The message account
+    ///   2. `[writable]` The commitment account, derived by `find_commitment_address`
+    ///   3. `[]` The system program
+    ///   4. `[]` The program config, derived by `find_config_address`
+    ///   5. `[writable]` Optional: the committer of the expired commitment replaced, if it is
+    ///      not the author
+    Commit {
+        /// `commitment_hash` of the message and its salt
+        hash: [u8; HASH_BYTES],
+        /// the message can be revealed from this point on
+        reveal_after: Timepoint,
+        /// the message can not be revealed anymore from this point on, after `reveal_after`
+        reveal_deadline: Timepoint,
+    },
+    /// Reveal publish a committed message within its reveal window, the message and salt
+    /// must hash to the commitment. The commitment account is closed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The author who committed, receiving the rent back
+    ///   1. `[writable]` The message account
+    ///   2. `[writable]` The commitment account, derived by `find_commitment_address`
+    ///   3. `[]` The program config, derived by `find_config_address`
+    Reveal {
+        /// committed message
+        message: String,
+        /// salt hashed with the message
+        salt: [u8; SALT_LEN],
+    },
+    /// CreatePoll create a poll at the address derived from its creator and a seed. Votes
+    /// are accepted within `[opens_at, closes_at)`. With a vote mint, a token vault derived
+    /// from the poll is created as well to escrow the weighted votes.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The creator, funding the poll account and the vault
+    ///   1. `[writable]` The poll account, derived by `find_poll_address`
+    ///   2. `[]` The system program
+    ///   3. `[]` The program config, derived by `find_config_address`
+    ///
+    ///   Optional, if the poll has a vote mint:
+    ///   4. `[writable]` The vault token account, derived by `find_poll_vault_address`
+    ///   5. `[]` The vote mint
+    ///   6. `[]` The token program
+    ///   7. `[]` The rent sysvar
+    CreatePoll {
+        /// seed of the poll account, at most `MAX_SEED_LEN` bytes
+        seed: String,
+        /// question, at most `MAX_POLL_QUESTION_LEN` bytes
+        question: String,
+        /// labels of the options, between `MIN_POLL_OPTIONS` and `MAX_POLL_OPTIONS`
+        options: Vec<String>,
+        /// votes are accepted from this point on
+        opens_at: Timepoint,
+        /// votes are not accepted anymore from this point on, after `opens_at`
+        closes_at: Timepoint,
+        /// mint weighting the votes by the tokens escrowed, one vote per wallet if `None`
+        vote_mint: Option<Pubkey>,
+    },
+    /// Vote count the vote of a wallet for an option of an open poll. A receipt account
+    /// derived from the poll and the wallet records it, so each wallet votes once.
+    ///
+    /// When the poll has a vote mint, the whole balance of the token account of the voter
+    /// is moved to the vault of the poll and weighs the vote. The tokens stay escrowed until
+    /// the poll closes, so they can not be voted with twice, see `WithdrawVote`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The voter, funding the receipt account
+    ///   1. `[writable]` The poll account
+    ///   2. `[writable]` The receipt account, derived by `find_vote_address`
+    ///   3. `[]` The system program
+    ///   4. `[]` The program config, derived by `find_config_address`
+    ///
+    ///   Optional, if the poll has a vote mint:
+    ///   5. `[writable]` The token account of the voter in the vote mint
+    ///   6. `[writable]` The vault of the poll, derived by `find_poll_vault_address`
+    ///   7. `[]` The token program
+    Vote {
+        /// index of the option voted for
+        option: u8,
+    },
+    /// PostBounty put `amount` lamports on a question of its author. The lamports are held
+    /// by the bounty vault of the question until `Award` pays them to the author of a reply,
+    /// or `Cancel` gives them back once the bounty expires. A question has a single bounty.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The author of the question, funding the bounty and the vault
+    ///   1. `[]` The message account asking the question
+    ///   2. `[writable]` The bounty vault, derived by `find_bounty_address`
+    ///   3. `[]` The system program
+    ///   4. `[]` The program config, derived by `find_config_address`
+    PostBounty {
+        /// lamports paid to the author of the awarded reply
+        amount: u64,
+        /// the bounty can be cancelled from this point on
+        expires_at: Timepoint,
+    },
+    /// Award pay the bounty of a question to the author of a reply to it, the rent of the
+    /// vault goes back to the author of the bounty. It works until the bounty is cancelled,
+    /// even after it expires.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The author of the bounty, receiving the rent of the vault
+    ///   1. `[writable]` The bounty vault, derived by `find_bounty_address`
+    ///   2. `[]` The reply message account, whose parent is the question
+    ///   3. `[writable]` The author of the reply, receiving the bounty
+    ///   4. `[]` The program config, derived by `find_config_address`
+    Award,
+    /// Cancel close an expired bounty vault, the bounty and the rent go back to its author.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The author of the bounty
+    ///   1. `[writable]` The bounty vault, derived by `find_bounty_address`
+    ///   2. `[]` The program config, derived by `find_config_address`
+    Cancel,
+    /// CloseCommitment close a commitment which was not revealed before its deadline, the
+    /// rent goes back to its committer.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The committer
+    ///   1. `[]` The message account
+    ///   2. `[writable]` The commitment account, derived by `find_commitment_address`
+    ///   3. `[]` The program config, derived by `find_config_address`
+    CloseCommitment,
+    /// WithdrawVote close the receipt of a vote once the poll is closed, the rent goes back
+    /// to the voter along with the tokens escrowed by the vote, if the poll has a vote mint.
+    /// The tally of the poll is kept.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The voter
+    ///   1. `[]` The poll account
+    ///   2. `[writable]` The receipt account, derived by `find_vote_address`
+    ///   3. `[]` The program config, derived by `find_config_address`
+    ///
+    ///   Optional, if the poll has a vote mint:
+    ///   4. `[writable]` The token account receiving the escrowed tokens
+    ///   5. `[writable]` The vault of the poll, derived by `find_poll_vault_address`
+    ///   6. `[]` The token program
+    WithdrawVote,
 }
 
+/// Offset of the signature offsets in the data of an ed25519 program instruction, after the
+/// signature count and a padding byte
+pub const ED25519_SIGNATURE_OFFSETS_START: usize = 2;
+
+/// Size of the offsets of one signature in the data of an ed25519 program instruction
+pub const ED25519_SIGNATURE_OFFSETS_SERIALIZED_SIZE: usize = 14;
+
+/// Instruction index of the ed25519 signature offsets pointing into their own instruction
+pub const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
 //对于instruction真正工作的其实只有反序列化函数，比如这里叫unpack，
 //而序列化是在客户端请求做的，因此pack函数不是必须的，但是如果使用单元测试的时候，可能需要通过pack来构建hook内容。
+//
+//指令数据格式：[255][版本号][Borsh编码的指令]
 impl HelloWorldInstruction {
     /// Unpacks a byte buffer into a [HelloWorldInstruction](enum.HelloWorldInstruction.html).
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
         use HelloWorldError::InvalidInstruction;
 
         let (&tag, rest) = input.split_first().ok_or(InvalidInstruction)?;
-        Ok(match tag { //HelloWorld
-            0 => {
-                let message= String::from(from_utf8(rest).unwrap());
-                Self::Hello{
-                    message,
-                }
-            },
-            1 => Self::Erase,
+        if tag != VERSIONED_INSTRUCTION_TAG {
+            return Err(InvalidInstruction.into());
+        }
+        let (&version, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+        if version != INSTRUCTION_VERSION {
+            return Err(HelloWorldError::UnsupportedInstructionVersion.into());
+        }
+        let instruction = Self::try_from_slice(rest).map_err(|_| InvalidInstruction)?;
 
-            _ => return Err(HelloWorldError::InvalidInstruction.into()),
-        })
+        match &instruction {
+            Self::Hello { message, .. } => HelloWorldState::check_message(message)?,
+            Self::CreateChannel { topic } => Channel::check_topic(topic)?,
+            Self::CreatePoll { question, options, .. } => Poll::check_content(question, options)?,
+            Self::AppendChunk { data } => MessageChunk::check_data(data)?,
+            _ => {}
+        }
+        Ok(instruction)
     }
 
     /// Packs a [HelloWorldInstruction](enum.HelloWorldInstruction.html) into a byte buffer.
     pub fn pack(&self) -> Vec<u8> {
-        let mut buf : Vec<u8>;
-        let self_len= size_of::<Self>();
-        match self {
-            &Self::Hello{
-                ref message,
-            } => {
-                buf = Vec::with_capacity(self_len+1);
-                buf.push(0); // tag
-                buf.extend_from_slice(message.as_bytes());
-            }
-            Self::Erase => {
-                buf = Vec::with_capacity(self_len);
-                buf.push(1); //tag
-            }
-        };
+        let mut buf = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION];
+        // writing into a Vec can not fail
+        self.serialize(&mut buf).unwrap();
         buf
     }
-}
\ No newline at end of file
+}
+
+/// Token accounts paying the posting fee of a `Hello`, see [Config](../state/struct.Config.html)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeeAccounts {
+    /// token account of the fee mint paying the fee
+    pub source: Pubkey,
+    /// owner of `source`, signing the transfer
+    pub owner: Pubkey,
+    /// treasury token account of the config
+    pub treasury: Pubkey,
+}
+
+/// Creates a `Hello` instruction, signed by the authority of the message or its delegate.
+pub fn hello(
+    program_id: &Pubkey,
+    authority_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    message: &str,
+) -> Result<Instruction, ProgramError> {
+    hello_instruction(
+        program_id,
+        authority_pubkey,
+        message_pubkey,
+        None,
+        signer_pubkeys,
+        None,
+        message,
+        None,
+    )
+}
+
+/// Creates a `Hello` instruction which only applies if the message is still at `expected_version`.
+pub fn hello_checked(
+    program_id: &Pubkey,
+    authority_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    message: &str,
+    expected_version: u64,
+) -> Result<Instruction, ProgramError> {
+    hello_instruction(
+        program_id,
+        authority_pubkey,
+        message_pubkey,
+        None,
+        signer_pubkeys,
+        None,
+        message,
+        Some(expected_version),
+    )
+}
+
+/// Creates a `Hello` instruction paying the posting fee of the program config from `fee`.
+pub fn hello_with_fee(
+    program_id: &Pubkey,
+    authority_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    fee: &FeeAccounts,
+    message: &str,
+    expected_version: Option<u64>,
+) -> Result<Instruction, ProgramError> {
+    hello_instruction(
+        program_id,
+        authority_pubkey,
+        message_pubkey,
+        None,
+        signer_pubkeys,
+        Some(fee),
+        message,
+        expected_version,
+    )
+}
+
+/// Creates a `Hello` instruction posting the message as a reply to `parent_pubkey`,
+/// paying the posting fee from `fee` if the program config charges one.
+pub fn reply(
+    program_id: &Pubkey,
+    authority_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    parent_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    fee: Option<&FeeAccounts>,
+    message: &str,
+) -> Result<Instruction, ProgramError> {
+    hello_instruction(
+        program_id,
+        authority_pubkey,
+        message_pubkey,
+        Some(parent_pubkey),
+        signer_pubkeys,
+        fee,
+        message,
+        None,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn hello_instruction(
+    program_id: &Pubkey,
+    authority_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    parent_pubkey: Option<&Pubkey>,
+    signer_pubkeys: &[&Pubkey],
+    fee: Option<&FeeAccounts>,
+    message: &str,
+    expected_version: Option<u64>,
+) -> Result<Instruction, ProgramError> {
+    HelloWorldState::check_message(message)?;
+    let data = HelloWorldInstruction::Hello {
+        message: message.to_string(),
+        expected_version,
+        parent: parent_pubkey.copied(),
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(8 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    accounts.push(AccountMeta::new(*message_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(find_config_address(program_id).0, false));
+    if let Some(parent_pubkey) = parent_pubkey {
+        accounts.push(AccountMeta::new(*parent_pubkey, false));
+    }
+    if let Some(fee) = fee {
+        accounts.push(AccountMeta::new(fee.source, false));
+        accounts.push(AccountMeta::new(fee.treasury, false));
+        accounts.push(AccountMeta::new_readonly(fee.owner, true));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
+    push_signers(&mut accounts, signer_pubkeys);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `Erase` instruction, `chunk_pubkeys` are the chunk accounts of the message in order.
+pub fn erase(
+    program_id: &Pubkey,
+    authority_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    chunk_pubkeys: &[&Pubkey],
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::Erase.pack();
+
+    let mut accounts = Vec::with_capacity(4 + chunk_pubkeys.len() + signer_pubkeys.len());
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    accounts.push(AccountMeta::new(*message_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(find_config_address(program_id).0, false));
+    for chunk_pubkey in chunk_pubkeys.iter() {
+        accounts.push(AccountMeta::new(**chunk_pubkey, false));
+    }
+    push_signers(&mut accounts, signer_pubkeys);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `Initialize` instruction, the message account is derived by `find_message_address`.
+pub fn initialize(
+    program_id: &Pubkey,
+    author_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    seed: &str,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::Initialize {
+        seed: seed.to_string(),
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*author_pubkey, true),
+        AccountMeta::new(*message_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new(find_message_counter_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Migrate` instruction, the new message account is derived by `find_message_address`.
+pub fn migrate(
+    program_id: &Pubkey,
+    author_pubkey: &Pubkey,
+    legacy_message_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    seed: &str,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::Migrate {
+        seed: seed.to_string(),
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*author_pubkey, true),
+        AccountMeta::new(*legacy_message_pubkey, false),
+        AccountMeta::new(*message_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new(find_message_counter_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AppendChunk` instruction, the chunk account is derived by `find_chunk_address`.
+pub fn append_chunk(
+    program_id: &Pubkey,
+    author_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    chunk_pubkey: &Pubkey,
+    data: &str,
+) -> Result<Instruction, ProgramError> {
+    MessageChunk::check_data(data)?;
+    let data = HelloWorldInstruction::AppendChunk {
+        data: data.to_string(),
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*author_pubkey, true),
+        AccountMeta::new(*message_pubkey, false),
+        AccountMeta::new(*chunk_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CreateChannel` instruction, the channel account is derived by `find_channel_address`.
+pub fn create_channel(
+    program_id: &Pubkey,
+    creator_pubkey: &Pubkey,
+    channel_pubkey: &Pubkey,
+    topic: &str,
+) -> Result<Instruction, ProgramError> {
+    Channel::check_topic(topic)?;
+    let data = HelloWorldInstruction::CreateChannel {
+        topic: topic.to_string(),
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*creator_pubkey, true),
+        AccountMeta::new(*channel_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `PostToChannel` instruction, `page_pubkey` is the current page of the channel.
+pub fn post_to_channel(
+    program_id: &Pubkey,
+    author_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    channel_pubkey: &Pubkey,
+    page_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::PostToChannel.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*author_pubkey, true),
+        AccountMeta::new_readonly(*message_pubkey, false),
+        AccountMeta::new(*channel_pubkey, false),
+        AccountMeta::new(*page_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `React` instruction, the receipt account is derived by `find_reaction_address`.
+pub fn react(
+    program_id: &Pubkey,
+    reactor_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    receipt_pubkey: &Pubkey,
+    reaction: Reaction,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::React { reaction }.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*reactor_pubkey, true),
+        AccountMeta::new(*message_pubkey, false),
+        AccountMeta::new(*receipt_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `Unreact` instruction.
+pub fn unreact(
+    program_id: &Pubkey,
+    reactor_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    receipt_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::Unreact.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*reactor_pubkey, true),
+        AccountMeta::new(*message_pubkey, false),
+        AccountMeta::new(*receipt_pubkey, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Finalize` instruction.
+pub fn finalize(
+    program_id: &Pubkey,
+    authority_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::Finalize.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    accounts.push(AccountMeta::new(*message_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(find_config_address(program_id).0, false));
+    push_signers(&mut accounts, signer_pubkeys);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetTtl` instruction, zero `ttl` keeps the message forever.
+pub fn set_ttl(
+    program_id: &Pubkey,
+    authority_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    ttl: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::SetTtl { ttl }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    accounts.push(AccountMeta::new(*message_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(find_config_address(program_id).0, false));
+    push_signers(&mut accounts, signer_pubkeys);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Reap` instruction, `chunk_pubkeys` are the chunk accounts of the message in order.
+pub fn reap(
+    program_id: &Pubkey,
+    reaper_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    author_pubkey: &Pubkey,
+    chunk_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::Reap.pack();
+
+    let mut accounts = Vec::with_capacity(4 + chunk_pubkeys.len());
+    accounts.push(AccountMeta::new(*reaper_pubkey, true));
+    accounts.push(AccountMeta::new(*message_pubkey, false));
+    accounts.push(AccountMeta::new(*author_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(find_config_address(program_id).0, false));
+    for chunk_pubkey in chunk_pubkeys.iter() {
+        accounts.push(AccountMeta::new(**chunk_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SendDirectMessage` instruction, `index` is the `message_count` of the inbox of
+/// the recipient, zero before its first message.
+pub fn send_direct_message(
+    program_id: &Pubkey,
+    sender_pubkey: &Pubkey,
+    recipient_pubkey: &Pubkey,
+    index: u64,
+    payload: &[u8],
+) -> Result<Instruction, ProgramError> {
+    DirectMessage::check_payload(payload)?;
+    let data = HelloWorldInstruction::SendDirectMessage {
+        recipient: *recipient_pubkey,
+        payload: payload.to_vec(),
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*sender_pubkey, true),
+        AccountMeta::new(find_inbox_address(program_id, recipient_pubkey).0, false),
+        AccountMeta::new(find_direct_message_address(program_id, recipient_pubkey, index).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Commit` instruction, `hash` is the `commitment_hash` of the message to reveal.
+/// `previous_committer` is refunded the rent of the expired commitment it replaces.
+pub fn commit(
+    program_id: &Pubkey,
+    author_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    hash: &[u8; HASH_BYTES],
+    reveal_after: Timepoint,
+    reveal_deadline: Timepoint,
+    previous_committer: Option<&Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    if reveal_after.is_before(&reveal_deadline) == Some(false) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let data = HelloWorldInstruction::Commit {
+        hash: *hash,
+        reveal_after,
+        reveal_deadline,
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*author_pubkey, true),
+        AccountMeta::new_readonly(*message_pubkey, false),
+        AccountMeta::new(find_commitment_address(program_id, message_pubkey).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+    if let Some(previous_committer) = previous_committer {
+        accounts.push(AccountMeta::new(*previous_committer, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Reveal` instruction.
+pub fn reveal(
+    program_id: &Pubkey,
+    author_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    message: &str,
+    salt: &[u8; SALT_LEN],
+) -> Result<Instruction, ProgramError> {
+    HelloWorldState::check_message(message)?;
+    let data = HelloWorldInstruction::Reveal {
+        message: message.to_string(),
+        salt: *salt,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*author_pubkey, true),
+        AccountMeta::new(*message_pubkey, false),
+        AccountMeta::new(find_commitment_address(program_id, message_pubkey).0, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CloseCommitment` instruction for the expired commitment of `message_pubkey`.
+pub fn close_commitment(
+    program_id: &Pubkey,
+    committer_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::CloseCommitment.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*committer_pubkey, true),
+        AccountMeta::new_readonly(*message_pubkey, false),
+        AccountMeta::new(find_commitment_address(program_id, message_pubkey).0, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CreatePoll` instruction, the poll account is derived by `find_poll_address`.
+#[allow(clippy::too_many_arguments)]
+pub fn create_poll(
+    program_id: &Pubkey,
+    creator_pubkey: &Pubkey,
+    seed: &str,
+    question: &str,
+    options: &[&str],
+    opens_at: Timepoint,
+    closes_at: Timepoint,
+    vote_mint: Option<&Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let options: Vec<String> = options.iter().map(|label| label.to_string()).collect();
+    Poll::check_content(question, &options)?;
+    if opens_at.is_before(&closes_at) == Some(false) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let data = HelloWorldInstruction::CreatePoll {
+        seed: seed.to_string(),
+        question: question.to_string(),
+        options,
+        opens_at,
+        closes_at,
+        vote_mint: vote_mint.copied(),
+    }
+    .pack();
+
+    let poll_pubkey = find_poll_address(program_id, creator_pubkey, seed).0;
+    let mut accounts = vec![
+        AccountMeta::new(*creator_pubkey, true),
+        AccountMeta::new(poll_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+    if let Some(vote_mint) = vote_mint {
+        accounts.extend_from_slice(&[
+            AccountMeta::new(find_poll_vault_address(program_id, &poll_pubkey).0, false),
+            AccountMeta::new_readonly(*vote_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ]);
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Vote` instruction, `weight_pubkey` is the token account of the voter in the
+/// vote mint of the poll, if it has one, whose whole balance is escrowed.
+pub fn vote(
+    program_id: &Pubkey,
+    voter_pubkey: &Pubkey,
+    poll_pubkey: &Pubkey,
+    option: u8,
+    weight_pubkey: Option<&Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::Vote { option }.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*voter_pubkey, true),
+        AccountMeta::new(*poll_pubkey, false),
+        AccountMeta::new(find_vote_address(program_id, poll_pubkey, voter_pubkey).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+    if let Some(weight_pubkey) = weight_pubkey {
+        accounts.extend_from_slice(&[
+            AccountMeta::new(*weight_pubkey, false),
+            AccountMeta::new(find_poll_vault_address(program_id, poll_pubkey).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ]);
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `WithdrawVote` instruction, `destination_pubkey` receives the escrowed tokens
+/// if the poll has a vote mint.
+pub fn withdraw_vote(
+    program_id: &Pubkey,
+    voter_pubkey: &Pubkey,
+    poll_pubkey: &Pubkey,
+    destination_pubkey: Option<&Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::WithdrawVote.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*voter_pubkey, true),
+        AccountMeta::new_readonly(*poll_pubkey, false),
+        AccountMeta::new(find_vote_address(program_id, poll_pubkey, voter_pubkey).0, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+    if let Some(destination_pubkey) = destination_pubkey {
+        accounts.extend_from_slice(&[
+            AccountMeta::new(*destination_pubkey, false),
+            AccountMeta::new(find_poll_vault_address(program_id, poll_pubkey).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ]);
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `PostBounty` instruction for the question asked by `question_pubkey`.
+pub fn post_bounty(
+    program_id: &Pubkey,
+    author_pubkey: &Pubkey,
+    question_pubkey: &Pubkey,
+    amount: u64,
+    expires_at: Timepoint,
+) -> Result<Instruction, ProgramError> {
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let data = HelloWorldInstruction::PostBounty { amount, expires_at }.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*author_pubkey, true),
+        AccountMeta::new_readonly(*question_pubkey, false),
+        AccountMeta::new(find_bounty_address(program_id, question_pubkey).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `Award` instruction paying the bounty of `question_pubkey` to `recipient_pubkey`,
+/// the author of the reply `reply_pubkey`.
+pub fn award(
+    program_id: &Pubkey,
+    author_pubkey: &Pubkey,
+    question_pubkey: &Pubkey,
+    reply_pubkey: &Pubkey,
+    recipient_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::Award.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*author_pubkey, true),
+        AccountMeta::new(find_bounty_address(program_id, question_pubkey).0, false),
+        AccountMeta::new_readonly(*reply_pubkey, false),
+        AccountMeta::new(*recipient_pubkey, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Cancel` instruction for the bounty of `question_pubkey`.
+pub fn cancel(
+    program_id: &Pubkey,
+    author_pubkey: &Pubkey,
+    question_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::Cancel.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*author_pubkey, true),
+        AccountMeta::new(find_bounty_address(program_id, question_pubkey).0, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `Approve` instruction.
+pub fn approve(
+    program_id: &Pubkey,
+    authority_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    delegate_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    expiry_slot: Slot,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::Approve { expiry_slot }.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    accounts.push(AccountMeta::new(*message_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*delegate_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(find_config_address(program_id).0, false));
+    push_signers(&mut accounts, signer_pubkeys);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Revoke` instruction.
+pub fn revoke(
+    program_id: &Pubkey,
+    authority_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::Revoke.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    accounts.push(AccountMeta::new(*message_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(find_config_address(program_id).0, false));
+    push_signers(&mut accounts, signer_pubkeys);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `InitializeMultisig` instruction.
+pub fn initialize_multisig(
+    program_id: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    m: u8,
+) -> Result<Instruction, ProgramError> {
+    if !is_valid_signer_index(m as usize)
+        || !is_valid_signer_index(signer_pubkeys.len())
+        || m as usize > signer_pubkeys.len()
+    {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let data = HelloWorldInstruction::InitializeMultisig { m }.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*multisig_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(find_config_address(program_id).0, false));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetAuthority` instruction.
+pub fn set_authority(
+    program_id: &Pubkey,
+    authority_pubkey: &Pubkey,
+    message_pubkey: &Pubkey,
+    new_authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::SetAuthority.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    accounts.push(AccountMeta::new(*message_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*new_authority_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(find_config_address(program_id).0, false));
+    push_signers(&mut accounts, signer_pubkeys);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `InitConfig` instruction, the fee mint is the mint of the treasury token account.
+pub fn init_config(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    treasury_pubkey: &Pubkey,
+    fee_amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::InitConfig { fee_amount }.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*admin_pubkey, true),
+        AccountMeta::new(find_config_address(program_id).0, false),
+        AccountMeta::new_readonly(*treasury_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_program_data_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `UpdateConfig` instruction, the fee mint is the mint of the treasury token account.
+pub fn update_config(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    treasury_pubkey: &Pubkey,
+    fee_amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = HelloWorldInstruction::UpdateConfig { fee_amount }.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_pubkey, true),
+        AccountMeta::new(find_config_address(program_id).0, false),
+        AccountMeta::new_readonly(*treasury_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetReapBounty` instruction.
+pub fn set_reap_bounty(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    bounty_bps: u16,
+) -> Result<Instruction, ProgramError> {
+    if bounty_bps > MAX_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+    admin_instruction(program_id, admin_pubkey, HelloWorldInstruction::SetReapBounty { bounty_bps })
+}
+
+/// Creates a `Pause` instruction.
+pub fn pause(program_id: &Pubkey, admin_pubkey: &Pubkey) -> Result<Instruction, ProgramError> {
+    admin_instruction(program_id, admin_pubkey, HelloWorldInstruction::Pause)
+}
+
+/// Creates an `Unpause` instruction.
+pub fn unpause(program_id: &Pubkey, admin_pubkey: &Pubkey) -> Result<Instruction, ProgramError> {
+    admin_instruction(program_id, admin_pubkey, HelloWorldInstruction::Unpause)
+}
+
+/// Creates a `TransferAdmin` instruction.
+pub fn transfer_admin(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    new_admin_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let mut instruction = admin_instruction(program_id, admin_pubkey, HelloWorldInstruction::TransferAdmin)?;
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*new_admin_pubkey, false));
+    Ok(instruction)
+}
+
+fn admin_instruction(
+    program_id: &Pubkey,
+    admin_pubkey: &Pubkey,
+    instruction: HelloWorldInstruction,
+) -> Result<Instruction, ProgramError> {
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_pubkey, true),
+        AccountMeta::new(find_config_address(program_id).0, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.pack(),
+    })
+}
+
+/// Bytes an author signs off-chain for `PostSigned`: the message account, which binds the
+/// program, the author and the seed, the nonce and the message.
+pub fn signed_post_message(message_pubkey: &Pubkey, nonce: u64, message: &str) -> Vec<u8> {
+    let mut data = Vec::with_capacity(32 + 8 + message.len());
+    data.extend_from_slice(message_pubkey.as_ref());
+    data.extend_from_slice(&nonce.to_le_bytes());
+    data.extend_from_slice(message.as_bytes());
+    data
+}
+
+/// Creates an ed25519 program instruction verifying the `signature` of `author` over `message`,
+/// with the key, the signature and the message in its own data.
+pub fn verify_signature(author: &Pubkey, signature: &[u8; 64], message: &[u8]) -> Instruction {
+    let public_key_offset = ED25519_SIGNATURE_OFFSETS_START + ED25519_SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+    let signature_offset = public_key_offset + 32;
+    let message_data_offset = signature_offset + signature.len();
+
+    let mut data = Vec::with_capacity(message_data_offset + message.len());
+    data.extend_from_slice(&[1, 0]);
+    for value in [
+        signature_offset as u16,
+        ED25519_CURRENT_INSTRUCTION,
+        public_key_offset as u16,
+        ED25519_CURRENT_INSTRUCTION,
+        message_data_offset as u16,
+        message.len() as u16,
+        ED25519_CURRENT_INSTRUCTION,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(author.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Creates a `PostSigned` instruction, it must follow the `verify_signature` instruction of the
+/// signature of `author` over `signed_post_message`.
+pub fn post_signed(
+    program_id: &Pubkey,
+    relayer_pubkey: &Pubkey,
+    author_pubkey: &Pubkey,
+    seed: &str,
+    nonce: u64,
+    fee: Option<&FeeAccounts>,
+    message: &str,
+) -> Result<Instruction, ProgramError> {
+    HelloWorldState::check_message(message)?;
+    let data = HelloWorldInstruction::PostSigned {
+        author: *author_pubkey,
+        seed: seed.to_string(),
+        message: message.to_string(),
+        nonce,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(11);
+    accounts.push(AccountMeta::new(*relayer_pubkey, true));
+    accounts.push(AccountMeta::new(find_message_address(program_id, author_pubkey, seed).0, false));
+    accounts.push(AccountMeta::new(find_nonce_address(program_id, author_pubkey, nonce).0, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(find_config_address(program_id).0, false));
+    accounts.push(AccountMeta::new(find_message_counter_address(program_id).0, false));
+    if let Some(fee) = fee {
+        accounts.push(AccountMeta::new(fee.source, false));
+        accounts.push(AccountMeta::new(fee.treasury, false));
+        accounts.push(AccountMeta::new_readonly(fee.owner, true));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+fn push_signers(accounts: &mut Vec<AccountMeta>, signer_pubkeys: &[&Pubkey]) {
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+}
+
+/// Utility function that checks index is between MIN_SIGNERS and MAX_SIGNERS
+pub fn is_valid_signer_index(index: usize) -> bool {
+    (MIN_SIGNERS..=MAX_SIGNERS).contains(&index)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_instruction_packing() {
+        let check = HelloWorldInstruction::Hello {
+            message: "hello world!".to_string(),
+            expected_version: None,
+            parent: None,
+        };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 0];
+        expect.extend_from_slice(&12u32.to_le_bytes());
+        expect.extend_from_slice(b"hello world!");
+        expect.push(0);
+        expect.push(0);
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::Hello {
+            message: "hello world!".to_string(),
+            expected_version: Some(7),
+            parent: Some(Pubkey::new_from_array([1; 32])),
+        };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 0];
+        expect.extend_from_slice(&12u32.to_le_bytes());
+        expect.extend_from_slice(b"hello world!");
+        expect.push(1);
+        expect.extend_from_slice(&7u64.to_le_bytes());
+        expect.push(1);
+        expect.extend_from_slice(&[1; 32]);
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::Erase;
+        let packed = check.pack();
+        let expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 1];
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::Initialize {
+            seed: "hello".to_string(),
+        };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 2];
+        expect.extend_from_slice(&5u32.to_le_bytes());
+        expect.extend_from_slice(b"hello");
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::Migrate {
+            seed: "hello".to_string(),
+        };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 3];
+        expect.extend_from_slice(&5u32.to_le_bytes());
+        expect.extend_from_slice(b"hello");
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::AppendChunk {
+            data: "hello".to_string(),
+        };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 4];
+        expect.extend_from_slice(&5u32.to_le_bytes());
+        expect.extend_from_slice(b"hello");
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::Finalize;
+        let packed = check.pack();
+        let expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 5];
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::Approve { expiry_slot: 42 };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 6];
+        expect.extend_from_slice(&42u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::Revoke;
+        let packed = check.pack();
+        let expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 7];
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::InitializeMultisig { m: 2 };
+        let packed = check.pack();
+        let expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 8, 2];
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::SetAuthority;
+        let packed = check.pack();
+        let expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 9];
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::InitConfig { fee_amount: 42 };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 10];
+        expect.extend_from_slice(&42u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::UpdateConfig { fee_amount: 42 };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 11];
+        expect.extend_from_slice(&42u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::CreateChannel {
+            topic: "rust".to_string(),
+        };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 15];
+        expect.extend_from_slice(&4u32.to_le_bytes());
+        expect.extend_from_slice(b"rust");
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::React {
+            reaction: Reaction::Heart,
+        };
+        let packed = check.pack();
+        let expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 17, 3];
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        // unknown reactions are rejected
+        assert_eq!(
+            HelloWorldInstruction::unpack(&[VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 17, 6])
+                .unwrap_err(),
+            HelloWorldError::InvalidInstruction.into()
+        );
+
+        let check = HelloWorldInstruction::SetTtl { ttl: 3_600 };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 19];
+        expect.extend_from_slice(&3_600u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::SetReapBounty { bounty_bps: 250 };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 21];
+        expect.extend_from_slice(&250u16.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::PostSigned {
+            author: Pubkey::new_from_array([1; 32]),
+            seed: "a".to_string(),
+            message: "hi".to_string(),
+            nonce: 7,
+        };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 22];
+        expect.extend_from_slice(&[1; 32]);
+        expect.extend_from_slice(&[1, 0, 0, 0, b'a', 2, 0, 0, 0, b'h', b'i']);
+        expect.extend_from_slice(&7u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::SendDirectMessage {
+            recipient: Pubkey::new_from_array([1; 32]),
+            payload: vec![2, 3],
+        };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 23];
+        expect.extend_from_slice(&[1; 32]);
+        expect.extend_from_slice(&[2, 0, 0, 0, 2, 3]);
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::Commit {
+            hash: [1; HASH_BYTES],
+            reveal_after: Timepoint::Slot(2),
+            reveal_deadline: Timepoint::UnixTimestamp(3),
+        };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 24];
+        expect.extend_from_slice(&[1; HASH_BYTES]);
+        expect.extend_from_slice(&[0, 2, 0, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[1, 3, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::Reveal {
+            message: "hi".to_string(),
+            salt: [4; SALT_LEN],
+        };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 25];
+        expect.extend_from_slice(&[2, 0, 0, 0, b'h', b'i']);
+        expect.extend_from_slice(&[4; SALT_LEN]);
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::CreatePoll {
+            seed: "p".to_string(),
+            question: "?".to_string(),
+            options: vec!["a".to_string(), "b".to_string()],
+            opens_at: Timepoint::Slot(1),
+            closes_at: Timepoint::Slot(2),
+            vote_mint: Some(Pubkey::new_from_array([3; 32])),
+        };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 26];
+        expect.extend_from_slice(&[1, 0, 0, 0, b'p', 1, 0, 0, 0, b'?']);
+        expect.extend_from_slice(&[2, 0, 0, 0, 1, 0, 0, 0, b'a', 1, 0, 0, 0, b'b']);
+        expect.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&[0, 2, 0, 0, 0, 0, 0, 0, 0]);
+        expect.push(1);
+        expect.extend_from_slice(&[3; 32]);
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        // 只有一个选项的投票在解析指令时就被拒绝
+        let check = HelloWorldInstruction::CreatePoll {
+            seed: "p".to_string(),
+            question: "?".to_string(),
+            options: vec!["a".to_string()],
+            opens_at: Timepoint::Slot(1),
+            closes_at: Timepoint::Slot(2),
+            vote_mint: None,
+        };
+        assert_eq!(
+            HelloWorldInstruction::unpack(&check.pack()),
+            Err(HelloWorldError::InvalidPollOption.into())
+        );
+
+        let check = HelloWorldInstruction::Vote { option: 1 };
+        let packed = check.pack();
+        let expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 27, 1];
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = HelloWorldInstruction::PostBounty {
+            amount: 1,
+            expires_at: Timepoint::UnixTimestamp(2),
+        };
+        let packed = check.pack();
+        let mut expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 28];
+        expect.extend_from_slice(&1u64.to_le_bytes());
+        expect.extend_from_slice(&[1, 2, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        for (tag, check) in [
+            (12, HelloWorldInstruction::Pause),
+            (13, HelloWorldInstruction::Unpause),
+            (14, HelloWorldInstruction::TransferAdmin),
+            (16, HelloWorldInstruction::PostToChannel),
+            (18, HelloWorldInstruction::Unreact),
+            (20, HelloWorldInstruction::Reap),
+            (29, HelloWorldInstruction::Award),
+            (30, HelloWorldInstruction::Cancel),
+            (31, HelloWorldInstruction::CloseCommitment),
+            (32, HelloWorldInstruction::WithdrawVote),
+        ] {
+            let packed = check.pack();
+            let expect = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, tag];
+            assert_eq!(packed, expect);
+            let unpacked = HelloWorldInstruction::unpack(&expect).unwrap();
+            assert_eq!(unpacked, check);
+        }
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let author = Pubkey::new_from_array([1; 32]);
+        let message = signed_post_message(&Pubkey::new_from_array([2; 32]), 7, "hi");
+        assert_eq!(message.len(), 32 + 8 + 2);
+        assert_eq!(message[32..40], 7u64.to_le_bytes());
+
+        let instruction = verify_signature(&author, &[3; 64], &message);
+        assert_eq!(instruction.program_id, ed25519_program::id());
+        let data = instruction.data;
+        assert_eq!(data[..2], [1, 0]);
+        let offsets: Vec<u16> = data[2..16]
+            .chunks_exact(2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect();
+        assert_eq!(offsets, [48, u16::MAX, 16, u16::MAX, 112, 42, u16::MAX]);
+        assert_eq!(data[16..48], [1; 32]);
+        assert_eq!(data[48..112], [3; 64]);
+        assert_eq!(data[112..], message[..]);
+    }
+
+    #[test]
+    fn test_instruction_unpacking_errors() {
+        assert_eq!(
+            HelloWorldInstruction::unpack(&[VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION + 1, 1])
+                .unwrap_err(),
+            HelloWorldError::UnsupportedInstructionVersion.into()
+        );
+        assert_eq!(
+            HelloWorldInstruction::unpack(&[VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION - 1, 1])
+                .unwrap_err(),
+            HelloWorldError::UnsupportedInstructionVersion.into()
+        );
+        assert_eq!(
+            HelloWorldInstruction::unpack(&[VERSIONED_INSTRUCTION_TAG]).unwrap_err(),
+            HelloWorldError::InvalidInstruction.into()
+        );
+        // 不带版本前缀的数据不是指令
+        for input in [&[][..], &[0], &[1], &b"\x00hello world!"[..]] {
+            assert_eq!(
+                HelloWorldInstruction::unpack(input).unwrap_err(),
+                HelloWorldError::InvalidInstruction.into()
+            );
+        }
+        // trailing bytes are rejected
+        assert_eq!(
+            HelloWorldInstruction::unpack(&[VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, 1, 0])
+                .unwrap_err(),
+            HelloWorldError::InvalidInstruction.into()
+        );
+
+        let too_long = HelloWorldInstruction::Hello {
+            message: "a".repeat(crate::state::MAX_MESSAGE_LEN + 1),
+            expected_version: None,
+            parent: None,
+        };
+        assert_eq!(
+            HelloWorldInstruction::unpack(&too_long.pack()).unwrap_err(),
+            HelloWorldError::MessageTooLong.into()
+        );
+        let too_long = HelloWorldInstruction::AppendChunk {
+            data: "a".repeat(crate::state::MAX_CHUNK_LEN + 1),
+        };
+        assert_eq!(
+            HelloWorldInstruction::unpack(&too_long.pack()).unwrap_err(),
+            HelloWorldError::MessageTooLong.into()
+        );
+    }
+}
@@ -4,6 +4,7 @@
 //! a helloworld onchain program
 
 pub mod error;
+pub mod event;
 pub mod instruction;
 pub mod processor;
 pub mod state;
@@ -13,4 +14,152 @@ pub mod entrypoint;
 
 
 // Export current sdk types for downstream users building with a different sdk version
-pub use solana_program;
\ No newline at end of file
+pub use solana_program;
+
+use solana_program::{bpf_loader_upgradeable, pubkey::Pubkey};
+
+/// Seed prefix of the message accounts derived from an author
+pub const MESSAGE_SEED: &[u8] = b"message";
+
+/// Derives the message account of an `author` for the given `seed`, along with its bump seed.
+pub fn find_message_address(program_id: &Pubkey, author: &Pubkey, seed: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[MESSAGE_SEED, &author.to_bytes(), seed.as_bytes()],
+        program_id,
+    )
+}
+
+
+/// Seed prefix of the chunk accounts holding the rest of a long message
+pub const CHUNK_SEED: &[u8] = b"chunk";
+
+/// Derives the chunk account at `index` of a `message` account, along with its bump seed.
+pub fn find_chunk_address(program_id: &Pubkey, message: &Pubkey, index: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CHUNK_SEED, &message.to_bytes(), &index.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Seed of the program config account
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Derives the config account of the program, along with its bump seed.
+pub fn find_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+/// Seed of the account counting the messages created by the program
+pub const MESSAGE_COUNTER_SEED: &[u8] = b"message_counter";
+
+/// Derives the message counter account of the program, along with its bump seed.
+pub fn find_message_counter_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MESSAGE_COUNTER_SEED], program_id)
+}
+
+/// Derives the ProgramData account holding the upgrade authority of the program, along with
+/// its bump seed.
+pub fn find_program_data_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id())
+}
+
+/// Seed prefix of the channel accounts derived from a topic
+pub const CHANNEL_SEED: &[u8] = b"channel";
+
+/// Derives the channel account of a `topic`, along with its bump seed.
+pub fn find_channel_address(program_id: &Pubkey, topic: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CHANNEL_SEED, topic.as_bytes()], program_id)
+}
+
+/// Seed prefix of the page accounts indexing the messages of a channel
+pub const CHANNEL_PAGE_SEED: &[u8] = b"channel_page";
+
+/// Derives the page account at `index` of a `channel` account, along with its bump seed.
+pub fn find_channel_page_address(program_id: &Pubkey, channel: &Pubkey, index: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CHANNEL_PAGE_SEED, &channel.to_bytes(), &index.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Seed prefix of the reaction receipts of a wallet
+pub const REACTION_SEED: &[u8] = b"reaction";
+
+/// Derives the reaction receipt of `reactor` for a `message` account, along with its bump seed.
+pub fn find_reaction_address(program_id: &Pubkey, message: &Pubkey, reactor: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[REACTION_SEED, &message.to_bytes(), &reactor.to_bytes()],
+        program_id,
+    )
+}
+
+/// Seed prefix of the inbox accounts of the recipients of direct messages
+pub const INBOX_SEED: &[u8] = b"inbox";
+
+/// Derives the inbox account of a `recipient`, along with its bump seed.
+pub fn find_inbox_address(program_id: &Pubkey, recipient: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[INBOX_SEED, &recipient.to_bytes()], program_id)
+}
+
+/// Seed prefix of the direct message accounts listed by an inbox
+pub const DIRECT_MESSAGE_SEED: &[u8] = b"direct_message";
+
+/// Derives the direct message at `index` of the inbox of a `recipient`, along with its bump seed.
+pub fn find_direct_message_address(program_id: &Pubkey, recipient: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[DIRECT_MESSAGE_SEED, &recipient.to_bytes(), &index.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Seed prefix of the commitment accounts of the messages to reveal later
+pub const COMMITMENT_SEED: &[u8] = b"commitment";
+
+/// Derives the commitment account of a `message` account, along with its bump seed.
+pub fn find_commitment_address(program_id: &Pubkey, message: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COMMITMENT_SEED, &message.to_bytes()], program_id)
+}
+
+/// Seed prefix of the nonce accounts of the messages signed off-chain by an author
+pub const NONCE_SEED: &[u8] = b"nonce";
+
+/// Derives the account marking `nonce` of `author` as used, along with its bump seed.
+pub fn find_nonce_address(program_id: &Pubkey, author: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[NONCE_SEED, &author.to_bytes(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Seed prefix of the poll accounts
+pub const POLL_SEED: &[u8] = b"poll";
+
+/// Derives the poll account of `creator` for `seed`, along with its bump seed.
+pub fn find_poll_address(program_id: &Pubkey, creator: &Pubkey, seed: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POLL_SEED, &creator.to_bytes(), seed.as_bytes()], program_id)
+}
+
+/// Seed prefix of the receipts of the votes in a poll
+pub const VOTE_SEED: &[u8] = b"vote";
+
+/// Derives the receipt account of the vote of `voter` in a `poll` account, along with its bump seed.
+pub fn find_vote_address(program_id: &Pubkey, poll: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VOTE_SEED, &poll.to_bytes(), &voter.to_bytes()], program_id)
+}
+
+/// Seed prefix of the token vaults escrowing the weighted votes of the polls
+pub const POLL_VAULT_SEED: &[u8] = b"poll_vault";
+
+/// Derives the token vault of a `poll` account with a vote mint, along with its bump seed.
+/// The vault is its own token owner, so the program signs its transfers.
+pub fn find_poll_vault_address(program_id: &Pubkey, poll: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POLL_VAULT_SEED, &poll.to_bytes()], program_id)
+}
+
+/// Seed prefix of the bounty vaults of the questions
+pub const BOUNTY_SEED: &[u8] = b"bounty";
+
+/// Derives the bounty vault of a `question` message account, along with its bump seed.
+pub fn find_bounty_address(program_id: &Pubkey, question: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BOUNTY_SEED, &question.to_bytes()], program_id)
+}
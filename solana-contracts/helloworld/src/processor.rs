@@ -1,84 +1,2443 @@
 //! Program state processor
 
 use crate::{
-    instruction::{HelloWorldInstruction},
-    state::{HelloWorldState},
+    error::HelloWorldError,
+    event::HelloWorldEvent,
+    find_bounty_address, find_channel_address, find_channel_page_address, find_chunk_address, find_commitment_address,
+    find_config_address, find_direct_message_address, find_inbox_address, find_message_address,
+    find_message_counter_address,
+    find_nonce_address, find_poll_address, find_poll_vault_address, find_program_data_address,
+    find_reaction_address, find_vote_address,
+    instruction::{
+        is_valid_signer_index, signed_post_message, HelloWorldInstruction,
+        ED25519_CURRENT_INSTRUCTION, ED25519_SIGNATURE_OFFSETS_SERIALIZED_SIZE,
+        ED25519_SIGNATURE_OFFSETS_START,
+    },
+    state::{
+        commitment_hash, Bounty, Channel, ChannelPage, Commitment, Config, DirectMessage, HelloWorldState,
+        HelloWorldStateV1, Inbox, MessageChunk, MessageCounter, Multisig, Poll, PollOption, Reaction,
+        ReactionReceipt, SignedNonce, Timepoint, VoteReceipt, CLOSED_ACCOUNT_DISCRIMINATOR,
+        DEFAULT_REAP_BOUNTY_BPS, MAX_BPS, MAX_SIGNERS, SALT_LEN,
+    },
+    BOUNTY_SEED, CHANNEL_PAGE_SEED, CHANNEL_SEED, CHUNK_SEED, COMMITMENT_SEED, CONFIG_SEED, DIRECT_MESSAGE_SEED,
+    INBOX_SEED, MESSAGE_COUNTER_SEED, MESSAGE_SEED, NONCE_SEED, POLL_SEED, POLL_VAULT_SEED, REACTION_SEED, VOTE_SEED,
 };
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable,
+    clock::{Clock, Slot},
+    ed25519_program,
+    hash::{Hash, HASH_BYTES},
     program_error::ProgramError,
     entrypoint::ProgramResult,
     msg,
-    program_pack::{Pack},
-    pubkey::Pubkey,
+    program::{invoke, invoke_signed},
+    program_option::COption,
+    program_pack::{IsInitialized, Pack},
+    pubkey::{Pubkey, MAX_SEED_LEN},
+    rent::Rent,
+    system_instruction,
+    system_program,
+    sysvar::{self, instructions::load_instruction_at_checked, Sysvar},
 };
+use spl_token::state::Account as TokenAccount;
+use std::convert::TryFrom;
+
+/// Enum index of `UpgradeableLoaderState::ProgramData`
+const PROGRAM_DATA_TAG: u32 = 3;
+/// Size of the ProgramData header up to the end of the upgrade authority
+const PROGRAM_DATA_METADATA_LEN: usize = 45;
 
 /// Program state handler.
 pub struct Processor {}
 impl Processor {
 
     /// Processes an [Instruction](enum.Instruction.html).
-    pub fn process(_program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+    pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
         let instruction = HelloWorldInstruction::unpack(input)?;
 
         match instruction {
             HelloWorldInstruction::Hello {
                 message,
+                expected_version,
+                parent,
             } => {
                 msg!("hello-world: HelloWorld");
-                Self::process_hello(accounts, message)
+                Self::process_hello(program_id, accounts, message, expected_version, parent)
             }
             HelloWorldInstruction::Erase=>{
                 msg!("hello-world: Erase");
-                Self::process_erase(accounts)
+                Self::process_erase(program_id, accounts)
+            }
+            HelloWorldInstruction::Initialize {
+                seed,
+            } => {
+                msg!("hello-world: Initialize");
+                Self::process_initialize(program_id, accounts, seed)
+            }
+            HelloWorldInstruction::Migrate {
+                seed,
+            } => {
+                msg!("hello-world: Migrate");
+                Self::process_migrate(program_id, accounts, seed)
+            }
+            HelloWorldInstruction::AppendChunk {
+                data,
+            } => {
+                msg!("hello-world: AppendChunk");
+                Self::process_append_chunk(program_id, accounts, data)
+            }
+            HelloWorldInstruction::Finalize => {
+                msg!("hello-world: Finalize");
+                Self::process_finalize(program_id, accounts)
+            }
+            HelloWorldInstruction::Approve {
+                expiry_slot,
+            } => {
+                msg!("hello-world: Approve");
+                Self::process_approve(program_id, accounts, expiry_slot)
+            }
+            HelloWorldInstruction::Revoke => {
+                msg!("hello-world: Revoke");
+                Self::process_revoke(program_id, accounts)
+            }
+            HelloWorldInstruction::InitializeMultisig {
+                m,
+            } => {
+                msg!("hello-world: InitializeMultisig");
+                Self::process_initialize_multisig(program_id, accounts, m)
+            }
+            HelloWorldInstruction::SetAuthority => {
+                msg!("hello-world: SetAuthority");
+                Self::process_set_authority(program_id, accounts)
+            }
+            HelloWorldInstruction::InitConfig {
+                fee_amount,
+            } => {
+                msg!("hello-world: InitConfig");
+                Self::process_init_config(program_id, accounts, fee_amount)
+            }
+            HelloWorldInstruction::UpdateConfig {
+                fee_amount,
+            } => {
+                msg!("hello-world: UpdateConfig");
+                Self::process_update_config(program_id, accounts, fee_amount)
+            }
+            HelloWorldInstruction::Pause => {
+                msg!("hello-world: Pause");
+                Self::process_set_paused(program_id, accounts, true)
+            }
+            HelloWorldInstruction::Unpause => {
+                msg!("hello-world: Unpause");
+                Self::process_set_paused(program_id, accounts, false)
+            }
+            HelloWorldInstruction::TransferAdmin => {
+                msg!("hello-world: TransferAdmin");
+                Self::process_transfer_admin(program_id, accounts)
+            }
+            HelloWorldInstruction::CreateChannel {
+                topic,
+            } => {
+                msg!("hello-world: CreateChannel");
+                Self::process_create_channel(program_id, accounts, topic)
+            }
+            HelloWorldInstruction::PostToChannel => {
+                msg!("hello-world: PostToChannel");
+                Self::process_post_to_channel(program_id, accounts)
+            }
+            HelloWorldInstruction::React {
+                reaction,
+            } => {
+                msg!("hello-world: React");
+                Self::process_react(program_id, accounts, reaction)
+            }
+            HelloWorldInstruction::Unreact => {
+                msg!("hello-world: Unreact");
+                Self::process_unreact(program_id, accounts)
+            }
+            HelloWorldInstruction::SetTtl {
+                ttl,
+            } => {
+                msg!("hello-world: SetTtl");
+                Self::process_set_ttl(program_id, accounts, ttl)
+            }
+            HelloWorldInstruction::Reap => {
+                msg!("hello-world: Reap");
+                Self::process_reap(program_id, accounts)
+            }
+            HelloWorldInstruction::SetReapBounty {
+                bounty_bps,
+            } => {
+                msg!("hello-world: SetReapBounty");
+                Self::process_set_reap_bounty(program_id, accounts, bounty_bps)
             }
+            HelloWorldInstruction::PostSigned {
+                author,
+                seed,
+                message,
+                nonce,
+            } => {
+                msg!("hello-world: PostSigned");
+                Self::process_post_signed(program_id, accounts, author, seed, message, nonce)
+            }
+            HelloWorldInstruction::SendDirectMessage {
+                recipient,
+                payload,
+            } => {
+                msg!("hello-world: SendDirectMessage");
+                Self::process_send_direct_message(program_id, accounts, recipient, payload)
+            }
+            HelloWorldInstruction::Commit {
+                hash,
+                reveal_after,
+                reveal_deadline,
+            } => {
+                msg!("hello-world: Commit");
+                Self::process_commit(program_id, accounts, hash, reveal_after, reveal_deadline)
+            }
+            HelloWorldInstruction::Reveal {
+                message,
+                salt,
+            } => {
+                msg!("hello-world: Reveal");
+                Self::process_reveal(program_id, accounts, message, salt)
+            }
+            HelloWorldInstruction::CreatePoll {
+                seed,
+                question,
+                options,
+                opens_at,
+                closes_at,
+                vote_mint,
+            } => {
+                msg!("hello-world: CreatePoll");
+                Self::process_create_poll(
+                    program_id, accounts, seed, question, options, opens_at, closes_at, vote_mint,
+                )
+            }
+            HelloWorldInstruction::Vote {
+                option,
+            } => {
+                msg!("hello-world: Vote");
+                Self::process_vote(program_id, accounts, option)
+            }
+            HelloWorldInstruction::PostBounty {
+                amount,
+                expires_at,
+            } => {
+                msg!("hello-world: PostBounty");
+                Self::process_post_bounty(program_id, accounts, amount, expires_at)
+            }
+            HelloWorldInstruction::Award => {
+                msg!("hello-world: Award");
+                Self::process_award(program_id, accounts)
+            }
+            HelloWorldInstruction::Cancel => {
+                msg!("hello-world: Cancel");
+                Self::process_cancel(program_id, accounts)
+            }
+            HelloWorldInstruction::CloseCommitment => {
+                msg!("hello-world: CloseCommitment");
+                Self::process_close_commitment(program_id, accounts)
+            }
+            HelloWorldInstruction::WithdrawVote => {
+                msg!("hello-world: WithdrawVote");
+                Self::process_withdraw_vote(program_id, accounts)
+            }
+        }
+    }
+
+    /// Processes an [Initialize](enum.HelloWorldInstruction.html) instruction.
+    fn process_initialize(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seed: String,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let author_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+        let counter_info = next_account_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        if !author_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let creation_id = Self::create_message_account(
+            program_id,
+            author_info,
+            author_info.key,
+            message_info,
+            counter_info,
+            system_program_info,
+            &seed,
+        )?;
+
+        let state = HelloWorldState::new(*author_info.key, creation_id, &Clock::get()?);
+        HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
+        HelloWorldEvent::MessageInitialized {
+            message: *message_info.key,
+            author: *author_info.key,
         }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [Migrate](enum.HelloWorldInstruction.html) instruction.
+    fn process_migrate(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seed: String,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let author_info = next_account_info(account_info_iter)?;
+        let legacy_message_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+        let counter_info = next_account_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        let legacy = Self::unpack_authorized_legacy_message(program_id, author_info, legacy_message_info)?;
+
+        // Solana上账号创建后长度不能再改，所以把旧格式的消息拷贝到一个新的账号里，再关闭旧账号
+        let creation_id = Self::create_message_account(
+            program_id,
+            author_info,
+            author_info.key,
+            message_info,
+            counter_info,
+            system_program_info,
+            &seed,
+        )?;
+        let state = HelloWorldState::from_v1(legacy, creation_id, &Clock::get()?);
+        HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
+
+        Self::close_account(legacy_message_info, author_info)?;
+        HelloWorldEvent::MessageMigrated {
+            legacy_message: *legacy_message_info.key,
+            message: *message_info.key,
+            author: *author_info.key,
+        }
+        .emit();
+        Ok(())
     }
 
     /// Processes an [Hello](enum.HelloWorldInstruction.html) instruction.
     fn process_hello(
+        program_id: &Pubkey,
         accounts: &[AccountInfo],
         message: String,
+        expected_version: Option<u64>,
+        parent: Option<Pubkey>,
     ) -> ProgramResult {
         // 将消息内容和谁发的信息，进行记录
         let account_info_iter = &mut accounts.iter();
         let client_info = next_account_info(account_info_iter)?;
         let message_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+        let config = Self::unpack_active_config(program_id, config_info)?;
+        // 回复别的消息时，config后面跟着被回复的消息账号
+        let parent_info = match parent {
+            Some(_) => Some(next_account_info(account_info_iter)?),
+            None => None,
+        };
+        // 配置了发帖费用时，后面跟着付费的token账号、国库账号、付费账号的owner和token程序
+        let fee = match config {
+            Some(config) if config.fee_amount > 0 => Some((
+                config,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+            )),
+            _ => None,
+        };
+
+        let mut state = Self::unpack_message(program_id, message_info)?;
+        // 作者本人（或者设置的多签账号）或者还没过期的代理人都可以发消息
+        let clock = Clock::get()?;
+        state.check_poster(client_info.key, clock.slot)?;
+        Self::validate_owner(program_id, client_info.key, client_info, account_info_iter.as_slice())?;
+        if state.finalized {
+            return Err(HelloWorldError::MessageFinalized.into());
+        }
+        // 多个客户端同时修改同一条消息时，基于旧版本的修改会被拒绝，而不是悄悄覆盖别人的修改
+        if let Some(expected_version) = expected_version {
+            if expected_version != state.edit_count {
+                return Err(HelloWorldError::VersionMismatch.into());
+            }
+        }
+
+        let rent = Rent::get()?;
+        if !rent.is_exempt(message_info.lamports(), message_info.data_len()) {
+            return Err(HelloWorldError::NotRentExempt.into());
+        }
+        // 回复只在第一次发的时候计入父消息的回复数
+        let reply = match (parent, parent_info) {
+            (Some(parent), Some(parent_info)) => {
+                if parent != *parent_info.key || parent == *message_info.key {
+                    return Err(HelloWorldError::InvalidParent.into());
+                }
+                let mut parent_state = Self::unpack_message(program_id, parent_info)?;
+                if state.set_parent(&parent)? {
+                    parent_state.add_reply()?;
+                    Some((parent_info, parent_state))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        if let Some((config, source_info, treasury_info, owner_info, token_program_info)) = fee {
+            Self::pay_fee(&config, source_info, treasury_info, owner_info, token_program_info)?;
+        }
+        state.set_message(message, &clock)?;
+
+        let event = HelloWorldEvent::MessagePosted {
+            message: *message_info.key,
+            poster: *client_info.key,
+            edit_count: state.edit_count,
+            text: state.message.clone(),
+        };
+        HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
+        event.emit();
+        if let Some((parent_info, parent_state)) = reply {
+            let event = HelloWorldEvent::ReplyPosted {
+                message: *message_info.key,
+                parent: *parent_info.key,
+                reply_count: parent_state.reply_count,
+            };
+            HelloWorldState::pack(parent_state, &mut parent_info.data.borrow_mut())?;
+            event.emit();
+        }
+        Ok(())
+    }
+
+    /// Processes a [PostSigned](enum.HelloWorldInstruction.html) instruction.
+    fn process_post_signed(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        author: Pubkey,
+        seed: String,
+        message: String,
+        nonce: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let relayer_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let nonce_info = next_account_info(account_info_iter)?;
+        let instructions_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+        let counter_info = next_account_info(account_info_iter)?;
+        let config = Self::unpack_active_config(program_id, config_info)?;
+        let fee = match config {
+            Some(config) if config.fee_amount > 0 => Some((
+                config,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+            )),
+            _ => None,
+        };
 
-        // check permission
-        if !client_info.is_signer {
+        if !relayer_info.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
+        HelloWorldState::check_message(&message)?;
+        if seed.len() > MAX_SEED_LEN
+            || find_message_address(program_id, &author, &seed).0 != *message_info.key
+        {
+            msg!("Error: message address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        // 作者不用签这笔交易，由作者对消息账号、nonce和消息内容的ed25519签名来证明是作者本人发的
+        Self::check_ed25519_signature(
+            instructions_info,
+            &author,
+            &signed_post_message(message_info.key, nonce, &message),
+        )?;
 
-        msg!("before unpack hello");
-        let mut state = HelloWorldState::unpack_unchecked(&message_info.data.borrow())?;
-        msg!("after unpack hello");
+        // 每个nonce只能用一次，nonce账号已经存在说明这个签名已经被提交过了
+        let (nonce_address, bump_seed) = find_nonce_address(program_id, &author, nonce);
+        if nonce_address != *nonce_info.key {
+            msg!("Error: nonce address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if *nonce_info.owner != system_program::id() {
+            return Err(HelloWorldError::NonceAlreadyUsed.into());
+        }
+        let nonce_signer_seeds: &[&[_]] = &[
+            NONCE_SEED,
+            &author.to_bytes(),
+            &nonce.to_le_bytes(),
+            &[bump_seed],
+        ];
+        Self::create_pda_account(
+            relayer_info,
+            nonce_info,
+            system_program_info,
+            SignedNonce::LEN,
+            program_id,
+            nonce_signer_seeds,
+        )?;
+        let signed_nonce = SignedNonce {
+            is_initialized: true,
+            author,
+            nonce,
+            message: *message_info.key,
+        };
+        SignedNonce::pack(signed_nonce, &mut nonce_info.data.borrow_mut())?;
 
-        state.account_key = *client_info.key;
-        state.message = message;
+        // 消息账号还不存在时由relayer代付租金创建，作者仍然是签名的人
+        let clock = Clock::get()?;
+        let mut state = if *message_info.owner == system_program::id() {
+            let creation_id = Self::create_message_account(
+                program_id,
+                relayer_info,
+                &author,
+                message_info,
+                counter_info,
+                system_program_info,
+                &seed,
+            )?;
+            HelloWorldEvent::MessageInitialized {
+                message: *message_info.key,
+                author,
+            }
+            .emit();
+            HelloWorldState::new(author, creation_id, &clock)
+        } else {
+            Self::unpack_message(program_id, message_info)?
+        };
+        state.check_poster(&author, clock.slot)?;
+        if state.finalized {
+            return Err(HelloWorldError::MessageFinalized.into());
+        }
+        if let Some((config, source_info, treasury_info, owner_info, token_program_info)) = fee {
+            Self::pay_fee(&config, source_info, treasury_info, owner_info, token_program_info)?;
+        }
+        state.set_message(message, &clock)?;
 
-        msg!("before pack hello");
+        let event = HelloWorldEvent::MessagePosted {
+            message: *message_info.key,
+            poster: author,
+            edit_count: state.edit_count,
+            text: state.message.clone(),
+        };
         HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
-        msg!("after pack hello");
+        event.emit();
+        HelloWorldEvent::SignedMessageRelayed {
+            message: *message_info.key,
+            author,
+            relayer: *relayer_info.key,
+            nonce,
+        }
+        .emit();
         Ok(())
     }
 
-    /// Processes a [Erase](enum.HelloWorldInstruction.html) instruction.
-    pub fn process_erase(accounts: &[AccountInfo]) -> ProgramResult {
+    /// Checks that an instruction of the transaction is an ed25519 program instruction verifying
+    /// a signature of `author` over `message`.
+    fn check_ed25519_signature(
+        instructions_info: &AccountInfo,
+        author: &Pubkey,
+        message: &[u8],
+    ) -> ProgramResult {
+        if *instructions_info.key != sysvar::instructions::id() {
+            return Err(ProgramError::UnsupportedSysvar);
+        }
+        // 签名本身由ed25519预编译程序在执行交易前验证过了，验证失败整个交易都不会执行，
+        // 这里只需要确认验证的是作者对这条消息的签名
+        let mut index = 0;
+        while let Ok(instruction) = load_instruction_at_checked(index, instructions_info) {
+            if instruction.program_id == ed25519_program::id()
+                && Self::ed25519_verifies(&instruction.data, author, message)
+            {
+                return Ok(());
+            }
+            index += 1;
+        }
+        msg!("Error: no ed25519 verification of the signed message");
+        Err(HelloWorldError::InvalidSignature.into())
+    }
+
+    /// Checks whether the data of an ed25519 program instruction verifies a signature of
+    /// `author` over `message`, both stored in that instruction.
+    fn ed25519_verifies(data: &[u8], author: &Pubkey, message: &[u8]) -> bool {
+        let count = match data.first() {
+            Some(count) => *count as usize,
+            None => return false,
+        };
+        (0..count).any(|signature_index| {
+            let start = ED25519_SIGNATURE_OFFSETS_START
+                + signature_index * ED25519_SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+            let offsets = match data.get(start..start + ED25519_SIGNATURE_OFFSETS_SERIALIZED_SIZE) {
+                Some(offsets) => offsets,
+                None => return false,
+            };
+            let read = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+            // 偏移量依次是签名、公钥、消息的位置和所在的指令，只接受公钥和消息就在这条指令里的
+            if read(6) != ED25519_CURRENT_INSTRUCTION || read(12) != ED25519_CURRENT_INSTRUCTION {
+                return false;
+            }
+            let public_key_offset = read(4) as usize;
+            let message_data_offset = read(8) as usize;
+            let message_data_size = read(10) as usize;
+            data.get(public_key_offset..public_key_offset + 32) == Some(author.as_ref())
+                && data.get(message_data_offset..message_data_offset + message_data_size)
+                    == Some(message)
+        })
+    }
+
+    /// Processes an [AppendChunk](enum.HelloWorldInstruction.html) instruction.
+    fn process_append_chunk(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        data: String,
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
+        let author_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let chunk_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
 
-        // 用户传递instruction里面的keys数组就对应这里的accounts数组，用户将其创建的消息账号通过这个数组传递过来，通过next_account_info进行获取
-        let client_info = next_account_info(account_info_iter)?;
+        Self::unpack_active_config(program_id, config_info)?;
+        // 由多签账号管理的消息不能追加chunk，chunk账号的租金要由作者自己支付
+        let mut state = Self::unpack_authorized_message(program_id, author_info, message_info, &[])?;
+        if state.finalized {
+            return Err(HelloWorldError::MessageFinalized.into());
+        }
+        MessageChunk::check_data(&data)?;
+
+        // 超出单个账号长度的消息按顺序存放在多个chunk账号里，地址由消息账号和序号推导出来
+        let index = state.chunk_count;
+        let (chunk_address, bump_seed) = find_chunk_address(program_id, message_info.key, index);
+        if chunk_address != *chunk_info.key {
+            msg!("Error: chunk address does not match seed derivation");
+            return Err(HelloWorldError::InvalidChunkAccount.into());
+        }
+        if *chunk_info.owner != system_program::id() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let chunk_signer_seeds: &[&[_]] = &[
+            CHUNK_SEED,
+            &message_info.key.to_bytes(),
+            &index.to_le_bytes(),
+            &[bump_seed],
+        ];
+        Self::create_pda_account(
+            author_info,
+            chunk_info,
+            system_program_info,
+            MessageChunk::LEN,
+            program_id,
+            chunk_signer_seeds,
+        )?;
+        let chunk = MessageChunk {
+            is_initialized: true,
+            message_account: *message_info.key,
+            index,
+            data,
+        };
+        MessageChunk::pack(chunk, &mut chunk_info.data.borrow_mut())?;
+
+        state.chunk_count = index.checked_add(1).ok_or(HelloWorldError::Overflow)?;
+        state.record_edit(&Clock::get()?)?;
+        HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
+        HelloWorldEvent::ChunkAppended {
+            message: *message_info.key,
+            chunk: *chunk_info.key,
+            index,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [Finalize](enum.HelloWorldInstruction.html) instruction.
+    fn process_finalize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
         let message_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
 
-        //check permission 用户构建transaction是否用了自己的进行签名，如果是的话，runtime会进行校验，因此只要判断他是否是被校验的单元,无需自己去调用鉴权接口。
-        if !client_info.is_signer {
+        Self::unpack_active_config(program_id, config_info)?;
+        let mut state = Self::unpack_authorized_message(
+            program_id,
+            authority_info,
+            message_info,
+            account_info_iter.as_slice(),
+        )?;
+        if state.finalized {
+            return Err(HelloWorldError::MessageFinalized.into());
+        }
+        state.finalized = true;
+        state.touch(&Clock::get()?);
+        HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
+        HelloWorldEvent::MessageFinalized {
+            message: *message_info.key,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes an [Approve](enum.HelloWorldInstruction.html) instruction.
+    fn process_approve(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        expiry_slot: Slot,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let delegate_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        let mut state = Self::unpack_authorized_message(
+            program_id,
+            authority_info,
+            message_info,
+            account_info_iter.as_slice(),
+        )?;
+        let clock = Clock::get()?;
+        if expiry_slot <= clock.slot {
+            msg!("Error: delegate grant expires in the past");
+            return Err(ProgramError::InvalidArgument);
+        }
+        state.delegate = COption::Some(*delegate_info.key);
+        state.delegate_expiry_slot = expiry_slot;
+        state.touch(&clock);
+        HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
+        HelloWorldEvent::DelegateApproved {
+            message: *message_info.key,
+            delegate: *delegate_info.key,
+            expiry_slot,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [Revoke](enum.HelloWorldInstruction.html) instruction.
+    fn process_revoke(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        let mut state = Self::unpack_authorized_message(
+            program_id,
+            authority_info,
+            message_info,
+            account_info_iter.as_slice(),
+        )?;
+        state.delegate = COption::None;
+        state.delegate_expiry_slot = 0;
+        state.touch(&Clock::get()?);
+        HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
+        HelloWorldEvent::DelegateRevoked {
+            message: *message_info.key,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes an [InitializeMultisig](enum.HelloWorldInstruction.html) instruction.
+    fn process_initialize_multisig(program_id: &Pubkey, accounts: &[AccountInfo], m: u8) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        if multisig_info.owner != program_id {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+        let mut multisig = Multisig::unpack_unchecked(&multisig_info.data.borrow())?;
+        if multisig.is_initialized {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if !Rent::get()?.is_exempt(multisig_info.lamports(), multisig_info.data_len()) {
+            return Err(HelloWorldError::NotRentExempt.into());
+        }
+
+        let signer_infos = account_info_iter.as_slice();
+        if !is_valid_signer_index(signer_infos.len()) {
+            return Err(HelloWorldError::InvalidNumberOfProvidedSigners.into());
+        }
+        if !is_valid_signer_index(m as usize) || m as usize > signer_infos.len() {
+            return Err(HelloWorldError::InvalidNumberOfRequiredSigners.into());
+        }
+        multisig.m = m;
+        multisig.n = signer_infos.len() as u8;
+        for (i, signer_info) in signer_infos.iter().enumerate() {
+            multisig.signers[i] = *signer_info.key;
+        }
+        multisig.is_initialized = true;
+
+        Multisig::pack(multisig, &mut multisig_info.data.borrow_mut())?;
+        HelloWorldEvent::MultisigInitialized {
+            multisig: *multisig_info.key,
+            m: multisig.m,
+            n: multisig.n,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [SetAuthority](enum.HelloWorldInstruction.html) instruction.
+    fn process_set_authority(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let new_authority_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        let mut state = Self::unpack_authorized_message(
+            program_id,
+            authority_info,
+            message_info,
+            account_info_iter.as_slice(),
+        )?;
+        let old_authority = state.authority_key();
+        state.authority = if *new_authority_info.key == state.account_key {
+            COption::None
+        } else {
+            COption::Some(*new_authority_info.key)
+        };
+        state.touch(&Clock::get()?);
+        HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
+        HelloWorldEvent::AuthorityChanged {
+            message: *message_info.key,
+            old_authority,
+            new_authority: *new_authority_info.key,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes an [InitConfig](enum.HelloWorldInstruction.html) instruction.
+    fn process_init_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        fee_amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+        let treasury_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let program_data_info = next_account_info(account_info_iter)?;
+
+        if !admin_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        // 只有程序的升级权限才能成为第一个管理员
+        if Self::unpack_upgrade_authority(program_id, program_data_info)? != Some(*admin_info.key) {
+            msg!("Error: only the upgrade authority of the program can create its config");
+            return Err(HelloWorldError::Unauthorized.into());
+        }
+        let (config_address, bump_seed) = find_config_address(program_id);
+        if config_address != *config_info.key {
+            return Err(HelloWorldError::InvalidConfigAccount.into());
+        }
+        if *config_info.owner != system_program::id() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        let treasury = Self::unpack_token_account(treasury_info)?;
+
+        Self::create_pda_account(
+            admin_info,
+            config_info,
+            system_program_info,
+            Config::LEN,
+            program_id,
+            &[CONFIG_SEED, &[bump_seed]],
+        )?;
+        let config = Config {
+            is_initialized: true,
+            admin: *admin_info.key,
+            fee_mint: treasury.mint,
+            fee_amount,
+            treasury: *treasury_info.key,
+            paused: false,
+            reap_bounty_bps: DEFAULT_REAP_BOUNTY_BPS,
+        };
+        Config::pack(config, &mut config_info.data.borrow_mut())?;
+        HelloWorldEvent::ConfigInitialized {
+            admin: config.admin,
+            fee_mint: config.fee_mint,
+            fee_amount: config.fee_amount,
+            treasury: config.treasury,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes an [UpdateConfig](enum.HelloWorldInstruction.html) instruction.
+    fn process_update_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        fee_amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+        let treasury_info = next_account_info(account_info_iter)?;
+
+        let mut config = Self::unpack_admin_config(program_id, admin_info, config_info)?;
+        let treasury = Self::unpack_token_account(treasury_info)?;
+        config.fee_mint = treasury.mint;
+        config.fee_amount = fee_amount;
+        config.treasury = *treasury_info.key;
+        Config::pack(config, &mut config_info.data.borrow_mut())?;
+        HelloWorldEvent::ConfigUpdated {
+            fee_mint: config.fee_mint,
+            fee_amount: config.fee_amount,
+            treasury: config.treasury,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [Pause or Unpause](enum.HelloWorldInstruction.html) instruction.
+    fn process_set_paused(program_id: &Pubkey, accounts: &[AccountInfo], paused: bool) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        let mut config = Self::unpack_admin_config(program_id, admin_info, config_info)?;
+        config.paused = paused;
+        Config::pack(config, &mut config_info.data.borrow_mut())?;
+        HelloWorldEvent::PauseChanged { paused }.emit();
+        Ok(())
+    }
+
+    /// Processes a [SetReapBounty](enum.HelloWorldInstruction.html) instruction.
+    fn process_set_reap_bounty(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        bounty_bps: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        let mut config = Self::unpack_admin_config(program_id, admin_info, config_info)?;
+        if bounty_bps > MAX_BPS {
+            return Err(ProgramError::InvalidArgument);
+        }
+        config.reap_bounty_bps = bounty_bps;
+        Config::pack(config, &mut config_info.data.borrow_mut())?;
+        HelloWorldEvent::ReapBountyUpdated { bounty_bps }.emit();
+        Ok(())
+    }
+
+    /// Processes a [TransferAdmin](enum.HelloWorldInstruction.html) instruction.
+    fn process_transfer_admin(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+        let new_admin_info = next_account_info(account_info_iter)?;
+
+        let mut config = Self::unpack_admin_config(program_id, admin_info, config_info)?;
+        config.admin = *new_admin_info.key;
+        Config::pack(config, &mut config_info.data.borrow_mut())?;
+        HelloWorldEvent::AdminTransferred {
+            old_admin: *admin_info.key,
+            new_admin: config.admin,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [CreateChannel](enum.HelloWorldInstruction.html) instruction.
+    fn process_create_channel(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        topic: String,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let creator_info = next_account_info(account_info_iter)?;
+        let channel_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        if !creator_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Channel::check_topic(&topic)?;
+        // 频道地址只由话题推导出来，同一个话题只能有一个频道
+        let (channel_address, bump_seed) = find_channel_address(program_id, &topic);
+        if channel_address != *channel_info.key {
+            msg!("Error: channel address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if *channel_info.owner != system_program::id() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let channel_signer_seeds: &[&[_]] = &[CHANNEL_SEED, topic.as_bytes(), &[bump_seed]];
+        Self::create_pda_account(
+            creator_info,
+            channel_info,
+            system_program_info,
+            Channel::LEN,
+            program_id,
+            channel_signer_seeds,
+        )?;
+        let channel = Channel {
+            is_initialized: true,
+            creator: *creator_info.key,
+            topic,
+            message_count: 0,
+        };
+        let event = HelloWorldEvent::ChannelCreated {
+            channel: *channel_info.key,
+            topic: channel.topic.clone(),
+            creator: channel.creator,
+        };
+        Channel::pack(channel, &mut channel_info.data.borrow_mut())?;
+        event.emit();
+        Ok(())
+    }
+
+    /// Processes a [PostToChannel](enum.HelloWorldInstruction.html) instruction.
+    fn process_post_to_channel(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let author_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let channel_info = next_account_info(account_info_iter)?;
+        let page_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        // 新的索引页的租金由作者支付，所以和AppendChunk一样只接受作者自己管理的消息
+        Self::unpack_authorized_message(program_id, author_info, message_info, &[])?;
+        if channel_info.owner != program_id {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+        let mut channel = Channel::unpack(&channel_info.data.borrow())
+            .map_err(|_| HelloWorldError::InvalidChannelAccount)?;
+
+        // 消息按顺序写在固定大小的索引页里，当前页写满后下一条消息创建新的一页
+        let (index, position) = channel.next_position()?;
+        let (page_address, bump_seed) = find_channel_page_address(program_id, channel_info.key, index);
+        if page_address != *page_info.key {
+            msg!("Error: channel page address does not match seed derivation");
+            return Err(HelloWorldError::InvalidChannelAccount.into());
+        }
+        let mut page = if position == 0 {
+            if *page_info.owner != system_program::id() {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            let page_signer_seeds: &[&[_]] = &[
+                CHANNEL_PAGE_SEED,
+                &channel_info.key.to_bytes(),
+                &index.to_le_bytes(),
+                &[bump_seed],
+            ];
+            Self::create_pda_account(
+                author_info,
+                page_info,
+                system_program_info,
+                ChannelPage::LEN,
+                program_id,
+                page_signer_seeds,
+            )?;
+            ChannelPage {
+                is_initialized: true,
+                channel: *channel_info.key,
+                index,
+                messages: Vec::new(),
+            }
+        } else {
+            if page_info.owner != program_id {
+                return Err(HelloWorldError::IncorrectAccountOwner.into());
+            }
+            ChannelPage::unpack(&page_info.data.borrow())?
+        };
+        if page.messages.len() != position {
+            return Err(HelloWorldError::InvalidChannelAccount.into());
+        }
+        page.messages.push(*message_info.key);
+        let event = HelloWorldEvent::ChannelPosted {
+            channel: *channel_info.key,
+            message: *message_info.key,
+            position: channel.message_count,
+        };
+        channel.message_count = channel.message_count.checked_add(1).ok_or(HelloWorldError::Overflow)?;
+
+        ChannelPage::pack(page, &mut page_info.data.borrow_mut())?;
+        Channel::pack(channel, &mut channel_info.data.borrow_mut())?;
+        event.emit();
+        Ok(())
+    }
+
+    /// Processes a [SendDirectMessage](enum.HelloWorldInstruction.html) instruction.
+    fn process_send_direct_message(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        recipient: Pubkey,
+        payload: Vec<u8>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let sender_info = next_account_info(account_info_iter)?;
+        let inbox_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        if !sender_info.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
+        DirectMessage::check_payload(&payload)?;
+
+        // 收件箱在收到第一条私信时由发送人代付租金创建
+        let (inbox_address, bump_seed) = find_inbox_address(program_id, &recipient);
+        if inbox_address != *inbox_info.key {
+            msg!("Error: inbox address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let mut inbox = if *inbox_info.owner == system_program::id() {
+            let inbox_signer_seeds: &[&[_]] = &[INBOX_SEED, &recipient.to_bytes(), &[bump_seed]];
+            Self::create_pda_account(
+                sender_info,
+                inbox_info,
+                system_program_info,
+                Inbox::LEN,
+                program_id,
+                inbox_signer_seeds,
+            )?;
+            Inbox {
+                is_initialized: true,
+                owner: recipient,
+                message_count: 0,
+            }
+        } else if inbox_info.owner == program_id {
+            Inbox::unpack(&inbox_info.data.borrow())?
+        } else {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        };
+
+        // 私信账号按收件箱里的序号推导，收件人按序号就能找到所有私信
+        let index = inbox.message_count;
+        let (message_address, bump_seed) = find_direct_message_address(program_id, &recipient, index);
+        if message_address != *message_info.key {
+            msg!("Error: direct message address is not the next one of the inbox");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let message_signer_seeds: &[&[_]] = &[
+            DIRECT_MESSAGE_SEED,
+            &recipient.to_bytes(),
+            &index.to_le_bytes(),
+            &[bump_seed],
+        ];
+        Self::create_pda_account(
+            sender_info,
+            message_info,
+            system_program_info,
+            DirectMessage::LEN,
+            program_id,
+            message_signer_seeds,
+        )?;
+        let message = DirectMessage {
+            is_initialized: true,
+            sender: *sender_info.key,
+            recipient,
+            index,
+            created_at: Clock::get()?.unix_timestamp,
+            payload,
+        };
+        inbox.message_count = index.checked_add(1).ok_or(HelloWorldError::Overflow)?;
 
-        let client_starting_lamports = client_info.lamports();
-        **client_info.lamports.borrow_mut() = client_starting_lamports + message_info.lamports();
-        **message_info.lamports.borrow_mut() = 0;
+        DirectMessage::pack(message, &mut message_info.data.borrow_mut())?;
+        Inbox::pack(inbox, &mut inbox_info.data.borrow_mut())?;
+        HelloWorldEvent::DirectMessageSent {
+            message: *message_info.key,
+            sender: *sender_info.key,
+            recipient,
+            index,
+        }
+        .emit();
         Ok(())
     }
+
+    /// Processes a [Commit](enum.HelloWorldInstruction.html) instruction.
+    fn process_commit(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        hash: [u8; HASH_BYTES],
+        reveal_after: Timepoint,
+        reveal_deadline: Timepoint,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let author_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let commitment_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        // 承诺账号的租金由作者自己支付，所以和AppendChunk一样不支持多签
+        let state = Self::unpack_authorized_message(program_id, author_info, message_info, &[])?;
+        if state.finalized {
+            return Err(HelloWorldError::MessageFinalized.into());
+        }
+        let clock = Clock::get()?;
+        if reveal_after.is_before(&reveal_deadline) == Some(false)
+            || reveal_deadline.has_passed(&clock)
+        {
+            msg!("Error: the reveal deadline must follow the start of the window, in the future");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (commitment_address, bump_seed) = find_commitment_address(program_id, message_info.key);
+        if commitment_address != *commitment_info.key {
+            msg!("Error: commitment address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if *commitment_info.owner == system_program::id() {
+            let commitment_signer_seeds: &[&[_]] = &[
+                COMMITMENT_SEED,
+                &message_info.key.to_bytes(),
+                &[bump_seed],
+            ];
+            Self::create_pda_account(
+                author_info,
+                commitment_info,
+                system_program_info,
+                Commitment::LEN,
+                program_id,
+                commitment_signer_seeds,
+            )?;
+        } else {
+            // 过了截止时间还没揭示的承诺作废，可以被新的承诺覆盖
+            let pending = Commitment::unpack(&commitment_info.data.borrow())?;
+            if !pending.reveal_deadline.has_passed(&clock) {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            // 作废的承诺的租金退给原来的承诺人，由新的承诺人重新支付
+            if pending.committer != *author_info.key {
+                let committer_info = next_account_info(account_info_iter)?;
+                if *committer_info.key != pending.committer {
+                    msg!("Error: the committer of the expired commitment must be refunded");
+                    return Err(ProgramError::InvalidArgument);
+                }
+                // 先通过CPI收新的租金再退旧的：CPI之前直接改过lamports的账号都得传给CPI
+                let refund = commitment_info.lamports();
+                invoke(
+                    &system_instruction::transfer(
+                        author_info.key,
+                        commitment_info.key,
+                        Rent::get()?.minimum_balance(Commitment::LEN),
+                    ),
+                    &[author_info.clone(), commitment_info.clone(), system_program_info.clone()],
+                )?;
+                **commitment_info.lamports.borrow_mut() = commitment_info
+                    .lamports()
+                    .checked_sub(refund)
+                    .ok_or(HelloWorldError::Overflow)?;
+                **committer_info.lamports.borrow_mut() = committer_info
+                    .lamports()
+                    .checked_add(refund)
+                    .ok_or(HelloWorldError::Overflow)?;
+            }
+        }
+        let commitment = Commitment {
+            is_initialized: true,
+            message: *message_info.key,
+            committer: *author_info.key,
+            hash: Hash::new_from_array(hash),
+            reveal_after,
+            reveal_deadline,
+        };
+        Commitment::pack(commitment, &mut commitment_info.data.borrow_mut())?;
+        HelloWorldEvent::MessageCommitted {
+            message: *message_info.key,
+            commitment: *commitment_info.key,
+            hash,
+            reveal_after,
+            reveal_deadline,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [Reveal](enum.HelloWorldInstruction.html) instruction.
+    fn process_reveal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        message: String,
+        salt: [u8; SALT_LEN],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let author_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let commitment_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        if !author_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if find_commitment_address(program_id, message_info.key).0 != *commitment_info.key {
+            msg!("Error: commitment address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if commitment_info.owner != program_id {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+        let commitment = Commitment::unpack(&commitment_info.data.borrow())?;
+        Self::check_author(author_info, &commitment.committer)?;
+        let clock = Clock::get()?;
+        commitment.check_reveal_window(&clock)?;
+        if commitment_hash(&message, &salt) != commitment.hash {
+            return Err(HelloWorldError::CommitmentMismatch.into());
+        }
+
+        let mut state = Self::unpack_message(program_id, message_info)?;
+        state.check_poster(author_info.key, clock.slot)?;
+        if state.finalized {
+            return Err(HelloWorldError::MessageFinalized.into());
+        }
+        state.set_message(message, &clock)?;
+        let event = HelloWorldEvent::MessagePosted {
+            message: *message_info.key,
+            poster: *author_info.key,
+            edit_count: state.edit_count,
+            text: state.message.clone(),
+        };
+        HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
+        Self::close_account(commitment_info, author_info)?;
+        event.emit();
+        HelloWorldEvent::MessageRevealed {
+            message: *message_info.key,
+            commitment: *commitment_info.key,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [CloseCommitment](enum.HelloWorldInstruction.html) instruction.
+    fn process_close_commitment(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let committer_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let commitment_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        if !committer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if find_commitment_address(program_id, message_info.key).0 != *commitment_info.key {
+            msg!("Error: commitment address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if commitment_info.owner != program_id {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+        let commitment = Commitment::unpack(&commitment_info.data.borrow())?;
+        Self::check_author(committer_info, &commitment.committer)?;
+        if !commitment.reveal_deadline.has_passed(&Clock::get()?) {
+            return Err(HelloWorldError::CommitmentNotExpired.into());
+        }
+
+        Self::close_account(commitment_info, committer_info)?;
+        HelloWorldEvent::CommitmentClosed {
+            message: *message_info.key,
+            commitment: *commitment_info.key,
+            committer: commitment.committer,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [CreatePoll](enum.HelloWorldInstruction.html) instruction.
+    #[allow(clippy::too_many_arguments)]
+    fn process_create_poll(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seed: String,
+        question: String,
+        options: Vec<String>,
+        opens_at: Timepoint,
+        closes_at: Timepoint,
+        vote_mint: Option<Pubkey>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let creator_info = next_account_info(account_info_iter)?;
+        let poll_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        if !creator_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if seed.len() > MAX_SEED_LEN {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Poll::check_content(&question, &options)?;
+        if opens_at.is_before(&closes_at) == Some(false) || closes_at.has_passed(&Clock::get()?) {
+            msg!("Error: the poll must close after it opens, in the future");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (poll_address, bump_seed) = find_poll_address(program_id, creator_info.key, &seed);
+        if poll_address != *poll_info.key {
+            msg!("Error: poll address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if *poll_info.owner != system_program::id() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        let poll_signer_seeds: &[&[_]] = &[
+            POLL_SEED,
+            &creator_info.key.to_bytes(),
+            seed.as_bytes(),
+            &[bump_seed],
+        ];
+        Self::create_pda_account(
+            creator_info,
+            poll_info,
+            system_program_info,
+            Poll::LEN,
+            program_id,
+            poll_signer_seeds,
+        )?;
+
+        let event = HelloWorldEvent::PollCreated {
+            poll: *poll_info.key,
+            creator: *creator_info.key,
+            question: question.clone(),
+            options: options.clone(),
+            opens_at,
+            closes_at,
+            vote_mint,
+        };
+        let poll = Poll {
+            is_initialized: true,
+            creator: *creator_info.key,
+            question,
+            options: options
+                .into_iter()
+                .map(|label| PollOption { label, votes: 0 })
+                .collect(),
+            opens_at,
+            closes_at,
+            vote_mint: vote_mint.into(),
+            voter_count: 0,
+        };
+        Poll::pack(poll, &mut poll_info.data.borrow_mut())?;
+
+        if let Some(vote_mint) = vote_mint {
+            let vault_info = next_account_info(account_info_iter)?;
+            let mint_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            let rent_info = next_account_info(account_info_iter)?;
+            let vault_bump_seed = Self::check_poll_vault(program_id, poll_info, vault_info, token_program_info)?;
+            if *mint_info.key != vote_mint {
+                msg!("Error: the vault must hold the vote mint of the poll");
+                return Err(ProgramError::InvalidArgument);
+            }
+            // 托管账号自己就是代币的所有者，只有本程序能用它的种子签名转出
+            let vault_signer_seeds: &[&[_]] = &[POLL_VAULT_SEED, &poll_info.key.to_bytes(), &[vault_bump_seed]];
+            Self::create_pda_account(
+                creator_info,
+                vault_info,
+                system_program_info,
+                TokenAccount::LEN,
+                &spl_token::id(),
+                vault_signer_seeds,
+            )?;
+            invoke(
+                &spl_token::instruction::initialize_account2(
+                    token_program_info.key,
+                    vault_info.key,
+                    mint_info.key,
+                    vault_info.key,
+                )?,
+                &[
+                    vault_info.clone(),
+                    mint_info.clone(),
+                    rent_info.clone(),
+                    token_program_info.clone(),
+                ],
+            )?;
+        }
+        event.emit();
+        Ok(())
+    }
+
+    /// Processes a [Vote](enum.HelloWorldInstruction.html) instruction.
+    fn process_vote(program_id: &Pubkey, accounts: &[AccountInfo], option: u8) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let voter_info = next_account_info(account_info_iter)?;
+        let poll_info = next_account_info(account_info_iter)?;
+        let receipt_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        if !voter_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if poll_info.owner != program_id {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+        let mut poll = Poll::unpack(&poll_info.data.borrow())?;
+        let clock = Clock::get()?;
+        poll.check_open(&clock)?;
+
+        let (weight, escrow) = match poll.vote_mint {
+            COption::None => (1, None),
+            COption::Some(vote_mint) => {
+                let weight_info = next_account_info(account_info_iter)?;
+                let vault_info = next_account_info(account_info_iter)?;
+                let token_program_info = next_account_info(account_info_iter)?;
+                let weight_account = Self::unpack_token_account(weight_info)
+                    .map_err(|_| HelloWorldError::InvalidWeightAccount)?;
+                // 只能用投票人自己持有的投票代币计票，余额为零的投票没有意义
+                if weight_account.mint != vote_mint
+                    || weight_account.owner != *voter_info.key
+                    || weight_account.amount == 0
+                {
+                    msg!("Error: the vote must be weighted by a funded vote mint account of the voter");
+                    return Err(HelloWorldError::InvalidWeightAccount.into());
+                }
+                Self::check_poll_vault(program_id, poll_info, vault_info, token_program_info)?;
+                (weight_account.amount, Some((weight_info, vault_info, token_program_info)))
+            }
+        };
+        let votes = poll.add_vote(option, weight)?;
+
+        // 每个钱包在每个投票里只有一个回执账号，回执账号已经存在说明已经投过票了
+        let (receipt_address, bump_seed) = find_vote_address(program_id, poll_info.key, voter_info.key);
+        if receipt_address != *receipt_info.key {
+            msg!("Error: vote receipt address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if *receipt_info.owner != system_program::id() {
+            return Err(HelloWorldError::AlreadyVoted.into());
+        }
+        let receipt_signer_seeds: &[&[_]] = &[
+            VOTE_SEED,
+            &poll_info.key.to_bytes(),
+            &voter_info.key.to_bytes(),
+            &[bump_seed],
+        ];
+        Self::create_pda_account(
+            voter_info,
+            receipt_info,
+            system_program_info,
+            VoteReceipt::LEN,
+            program_id,
+            receipt_signer_seeds,
+        )?;
+        let receipt = VoteReceipt {
+            is_initialized: true,
+            poll: *poll_info.key,
+            voter: *voter_info.key,
+            option,
+            weight,
+            slot: clock.slot,
+        };
+
+        // 计票的代币转入投票的托管账号，投票结束前不能再拿去别的钱包重复投票
+        if let Some((weight_info, vault_info, token_program_info)) = escrow {
+            invoke(
+                &spl_token::instruction::transfer(
+                    token_program_info.key,
+                    weight_info.key,
+                    vault_info.key,
+                    voter_info.key,
+                    &[],
+                    weight,
+                )?,
+                &[
+                    weight_info.clone(),
+                    vault_info.clone(),
+                    voter_info.clone(),
+                    token_program_info.clone(),
+                ],
+            )?;
+        }
+
+        VoteReceipt::pack(receipt, &mut receipt_info.data.borrow_mut())?;
+        Poll::pack(poll, &mut poll_info.data.borrow_mut())?;
+        HelloWorldEvent::Voted {
+            poll: *poll_info.key,
+            voter: *voter_info.key,
+            option,
+            weight,
+            votes,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [WithdrawVote](enum.HelloWorldInstruction.html) instruction.
+    fn process_withdraw_vote(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let voter_info = next_account_info(account_info_iter)?;
+        let poll_info = next_account_info(account_info_iter)?;
+        let receipt_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        if !voter_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if poll_info.owner != program_id {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+        let poll = Poll::unpack(&poll_info.data.borrow())?;
+        if !poll.closes_at.has_passed(&Clock::get()?) {
+            return Err(HelloWorldError::PollNotClosed.into());
+        }
+        if find_vote_address(program_id, poll_info.key, voter_info.key).0 != *receipt_info.key {
+            msg!("Error: vote receipt address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if receipt_info.owner != program_id {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+        let receipt = VoteReceipt::unpack(&receipt_info.data.borrow())?;
+
+        if let COption::Some(_) = poll.vote_mint {
+            let destination_info = next_account_info(account_info_iter)?;
+            let vault_info = next_account_info(account_info_iter)?;
+            let token_program_info = next_account_info(account_info_iter)?;
+            let vault_bump_seed = Self::check_poll_vault(program_id, poll_info, vault_info, token_program_info)?;
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    token_program_info.key,
+                    vault_info.key,
+                    destination_info.key,
+                    vault_info.key,
+                    &[],
+                    receipt.weight,
+                )?,
+                &[
+                    vault_info.clone(),
+                    destination_info.clone(),
+                    vault_info.clone(),
+                    token_program_info.clone(),
+                ],
+                &[&[POLL_VAULT_SEED, &poll_info.key.to_bytes(), &[vault_bump_seed]]],
+            )?;
+        }
+
+        Self::close_account(receipt_info, voter_info)?;
+        HelloWorldEvent::VoteWithdrawn {
+            poll: *poll_info.key,
+            voter: *voter_info.key,
+            weight: receipt.weight,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [PostBounty](enum.HelloWorldInstruction.html) instruction.
+    fn process_post_bounty(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        expires_at: Timepoint,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let author_info = next_account_info(account_info_iter)?;
+        let question_info = next_account_info(account_info_iter)?;
+        let bounty_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        // 赏金和金库的租金由作者自己支付，所以和Commit一样不支持多签
+        Self::unpack_authorized_message(program_id, author_info, question_info, &[])?;
+        if amount == 0 || expires_at.has_passed(&Clock::get()?) {
+            msg!("Error: the bounty must be positive and expire in the future");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (bounty_address, bump_seed) = find_bounty_address(program_id, question_info.key);
+        if bounty_address != *bounty_info.key {
+            msg!("Error: bounty address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if *bounty_info.owner != system_program::id() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        let bounty_signer_seeds: &[&[_]] = &[BOUNTY_SEED, &question_info.key.to_bytes(), &[bump_seed]];
+        Self::create_pda_account(
+            author_info,
+            bounty_info,
+            system_program_info,
+            Bounty::LEN,
+            program_id,
+            bounty_signer_seeds,
+        )?;
+        // 金库账号已经属于本合约，转入不需要它签名
+        invoke(
+            &system_instruction::transfer(author_info.key, bounty_info.key, amount),
+            &[author_info.clone(), bounty_info.clone(), system_program_info.clone()],
+        )?;
+
+        let bounty = Bounty {
+            is_initialized: true,
+            question: *question_info.key,
+            author: *author_info.key,
+            amount,
+            expires_at,
+        };
+        Bounty::pack(bounty, &mut bounty_info.data.borrow_mut())?;
+        HelloWorldEvent::BountyPosted {
+            question: *question_info.key,
+            bounty: *bounty_info.key,
+            author: *author_info.key,
+            amount,
+            expires_at,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes an [Award](enum.HelloWorldInstruction.html) instruction.
+    fn process_award(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let author_info = next_account_info(account_info_iter)?;
+        let bounty_info = next_account_info(account_info_iter)?;
+        let reply_info = next_account_info(account_info_iter)?;
+        let recipient_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        let bounty = Self::unpack_bounty(program_id, author_info, bounty_info)?;
+        let reply = Self::unpack_message(program_id, reply_info)?;
+        // 作者给自己的回复发赏金没有意义，想拿回赏金要等过期后Cancel
+        if reply.parent != COption::Some(bounty.question)
+            || reply.account_key == bounty.author
+            || reply.account_key != *recipient_info.key
+        {
+            msg!("Error: the bounty must be paid to the author of a reply by someone else");
+            return Err(HelloWorldError::InvalidReply.into());
+        }
+
+        **bounty_info.lamports.borrow_mut() = bounty_info
+            .lamports()
+            .checked_sub(bounty.amount)
+            .ok_or(HelloWorldError::Overflow)?;
+        **recipient_info.lamports.borrow_mut() = recipient_info
+            .lamports()
+            .checked_add(bounty.amount)
+            .ok_or(HelloWorldError::Overflow)?;
+        Self::close_account(bounty_info, author_info)?;
+        HelloWorldEvent::BountyAwarded {
+            question: bounty.question,
+            reply: *reply_info.key,
+            recipient: *recipient_info.key,
+            amount: bounty.amount,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [Cancel](enum.HelloWorldInstruction.html) instruction.
+    fn process_cancel(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let author_info = next_account_info(account_info_iter)?;
+        let bounty_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        let bounty = Self::unpack_bounty(program_id, author_info, bounty_info)?;
+        if !bounty.expires_at.has_passed(&Clock::get()?) {
+            return Err(HelloWorldError::BountyNotExpired.into());
+        }
+
+        Self::close_account(bounty_info, author_info)?;
+        HelloWorldEvent::BountyCancelled {
+            question: bounty.question,
+            author: bounty.author,
+            amount: bounty.amount,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [React](enum.HelloWorldInstruction.html) instruction.
+    fn process_react(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        reaction: Reaction,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let reactor_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let receipt_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        if !reactor_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let mut state = Self::unpack_message(program_id, message_info)?;
+
+        // 每个钱包对每条消息只有一个回执账号，回执账号已经存在说明已经表过态了
+        let (receipt_address, bump_seed) =
+            find_reaction_address(program_id, message_info.key, reactor_info.key);
+        if receipt_address != *receipt_info.key {
+            msg!("Error: reaction receipt address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if *receipt_info.owner != system_program::id() {
+            return Err(HelloWorldError::AlreadyReacted.into());
+        }
+        let receipt_signer_seeds: &[&[_]] = &[
+            REACTION_SEED,
+            &message_info.key.to_bytes(),
+            &reactor_info.key.to_bytes(),
+            &[bump_seed],
+        ];
+        Self::create_pda_account(
+            reactor_info,
+            receipt_info,
+            system_program_info,
+            ReactionReceipt::LEN,
+            program_id,
+            receipt_signer_seeds,
+        )?;
+        let receipt = ReactionReceipt {
+            is_initialized: true,
+            message: *message_info.key,
+            reactor: *reactor_info.key,
+            reaction,
+            message_creation_id: state.creation_id,
+        };
+        state.add_reaction(reaction)?;
+
+        let event = HelloWorldEvent::Reacted {
+            message: *message_info.key,
+            reactor: *reactor_info.key,
+            reaction,
+            count: state.reactions[u8::from(reaction) as usize],
+        };
+        ReactionReceipt::pack(receipt, &mut receipt_info.data.borrow_mut())?;
+        HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
+        event.emit();
+        Ok(())
+    }
+
+    /// Processes an [Unreact](enum.HelloWorldInstruction.html) instruction.
+    fn process_unreact(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let reactor_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let receipt_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        if !reactor_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if receipt_info.owner != program_id {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+        let (receipt_address, _) = find_reaction_address(program_id, message_info.key, reactor_info.key);
+        if receipt_address != *receipt_info.key {
+            msg!("Error: reaction receipt address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let receipt = ReactionReceipt::unpack(&receipt_info.data.borrow())?;
+
+        // 消息被删除以后回执的租金也要能取回来；同一个地址上后来重新创建的消息不包含这个表态，
+        // 这时只关掉回执，不动计数。计数已经是0时也一样，回执不能因为减不下去而取不回来
+        let erased = message_info.owner != program_id
+            || message_info.data.borrow().starts_with(&CLOSED_ACCOUNT_DISCRIMINATOR);
+        let mut count = 0;
+        if !erased {
+            let mut state = Self::unpack_message(program_id, message_info)?;
+            if receipt.is_counted_by(&state) && state.remove_reaction(receipt.reaction).is_ok() {
+                count = state.reactions[u8::from(receipt.reaction) as usize];
+                HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
+            }
+        }
+        Self::close_account(receipt_info, reactor_info)?;
+
+        HelloWorldEvent::Unreacted {
+            message: *message_info.key,
+            reactor: *reactor_info.key,
+            reaction: receipt.reaction,
+            count,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [SetTtl](enum.HelloWorldInstruction.html) instruction.
+    fn process_set_ttl(program_id: &Pubkey, accounts: &[AccountInfo], ttl: u64) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        let mut state = Self::unpack_authorized_message(
+            program_id,
+            authority_info,
+            message_info,
+            account_info_iter.as_slice(),
+        )?;
+        state.ttl = ttl;
+        state.touch(&Clock::get()?);
+        HelloWorldState::pack(state, &mut message_info.data.borrow_mut())?;
+        HelloWorldEvent::TtlSet {
+            message: *message_info.key,
+            ttl,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [Reap](enum.HelloWorldInstruction.html) instruction.
+    fn process_reap(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let reaper_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let author_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        let bounty_bps = Self::unpack_active_config(program_id, config_info)?
+            .map_or(DEFAULT_REAP_BOUNTY_BPS, |config| config.reap_bounty_bps);
+        if !reaper_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let state = Self::unpack_message(program_id, message_info)?;
+        if !state.is_expired(Clock::get()?.unix_timestamp) {
+            return Err(HelloWorldError::MessageNotExpired.into());
+        }
+        // 剩下的租金只能退给消息的作者，不能由调用者随便指定
+        Self::check_author(author_info, &state.account_key)?;
+
+        let chunk_infos = account_info_iter.as_slice();
+        if chunk_infos.len() != state.chunk_count as usize {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let mut bounty = 0u64;
+        let mut refund = 0u64;
+        for (index, chunk_info) in chunk_infos.iter().enumerate() {
+            Self::check_chunk_account(program_id, message_info.key, index as u32, chunk_info)?;
+            let (chunk_bounty, chunk_refund) =
+                Self::reap_account(chunk_info, reaper_info, author_info, bounty_bps)?;
+            bounty = bounty.checked_add(chunk_bounty).ok_or(HelloWorldError::Overflow)?;
+            refund = refund.checked_add(chunk_refund).ok_or(HelloWorldError::Overflow)?;
+        }
+        let (message_bounty, message_refund) =
+            Self::reap_account(message_info, reaper_info, author_info, bounty_bps)?;
+        HelloWorldEvent::MessageReaped {
+            message: *message_info.key,
+            reaper: *reaper_info.key,
+            author: *author_info.key,
+            bounty: bounty.checked_add(message_bounty).ok_or(HelloWorldError::Overflow)?,
+            refund: refund.checked_add(message_refund).ok_or(HelloWorldError::Overflow)?,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Closes a program account for `Reap`: `bounty_bps` of its lamports go to `reaper_info`,
+    /// the rest to `author_info`. Returns the lamports paid to each.
+    fn reap_account(
+        account_info: &AccountInfo,
+        reaper_info: &AccountInfo,
+        author_info: &AccountInfo,
+        bounty_bps: u16,
+    ) -> Result<(u64, u64), ProgramError> {
+        let lamports = account_info.lamports();
+        let bounty = (lamports as u128 * bounty_bps as u128 / MAX_BPS as u128) as u64;
+        let reaper_starting_lamports = reaper_info.lamports();
+        **reaper_info.lamports.borrow_mut() = reaper_starting_lamports
+            .checked_add(bounty)
+            .ok_or(HelloWorldError::Overflow)?;
+        **account_info.lamports.borrow_mut() = lamports - bounty;
+        Self::close_account(account_info, author_info)?;
+        Ok((bounty, lamports - bounty))
+    }
+
+    /// Processes a [Erase](enum.HelloWorldInstruction.html) instruction.
+    pub fn process_erase(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        // 用户传递instruction里面的keys数组就对应这里的accounts数组，用户将其创建的消息账号通过这个数组传递过来，通过next_account_info进行获取
+        let client_info = next_account_info(account_info_iter)?;
+        let message_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let config_info = Self::next_config_info(account_info_iter)?;
+
+        Self::unpack_active_config(program_id, config_info)?;
+        // 旧格式的消息也可以直接删除，不用先迁移
+        if message_info.data_len() == HelloWorldStateV1::LEN {
+            Self::unpack_authorized_legacy_message(program_id, client_info, message_info)?;
+        } else {
+            let state = Self::unpack_message(program_id, message_info)?;
+            // 所有chunk账号都要一起关闭，否则它们的租金就再也取不回来了；多签的签名账号排在chunk账号后面
+            let chunk_infos = accounts
+                .get(4..4 + state.chunk_count as usize)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let signer_infos = &accounts[4 + chunk_infos.len()..];
+            Self::validate_owner(program_id, &state.authority_key(), client_info, signer_infos)?;
+            for (index, chunk_info) in chunk_infos.iter().enumerate() {
+                Self::check_chunk_account(program_id, message_info.key, index as u32, chunk_info)?;
+                Self::close_account(chunk_info, destination_info)?;
+            }
+        }
+        Self::close_account(message_info, destination_info)?;
+        HelloWorldEvent::MessageErased {
+            message: *message_info.key,
+            destination: *destination_info.key,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Checks that `chunk_info` is the chunk account at `index` of the message account.
+    fn check_chunk_account(
+        program_id: &Pubkey,
+        message: &Pubkey,
+        index: u32,
+        chunk_info: &AccountInfo,
+    ) -> ProgramResult {
+        if chunk_info.owner != program_id {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+        let chunk = MessageChunk::unpack(&chunk_info.data.borrow())?;
+        if chunk.message_account != *message || chunk.index != index {
+            return Err(HelloWorldError::InvalidChunkAccount.into());
+        }
+        Ok(())
+    }
+
+    /// Unpacks the message account and checks that `authority_info` may modify it:
+    /// the account must be owned by this program, initialized, and the authority must be
+    /// the one governing it, with the signatures it requires.
+    fn unpack_authorized_message(
+        program_id: &Pubkey,
+        authority_info: &AccountInfo,
+        message_info: &AccountInfo,
+        signers: &[AccountInfo],
+    ) -> Result<HelloWorldState, ProgramError> {
+        let state = Self::unpack_message(program_id, message_info)?;
+        Self::validate_owner(program_id, &state.authority_key(), authority_info, signers)?;
+        Ok(state)
+    }
+
+    /// Same as `unpack_authorized_message`, without checking who may modify the message.
+    fn unpack_message(
+        program_id: &Pubkey,
+        message_info: &AccountInfo,
+    ) -> Result<HelloWorldState, ProgramError> {
+        Self::check_message_account(program_id, message_info)?;
+        if message_info.data_len() == HelloWorldStateV1::LEN {
+            return Err(HelloWorldError::AccountNotMigrated.into());
+        }
+
+        let state = HelloWorldState::unpack_unchecked(&message_info.data.borrow())?;
+        if !state.is_initialized() {
+            return Err(HelloWorldError::AccountNotInitialized.into());
+        }
+        Ok(state)
+    }
+
+    /// Same as `unpack_authorized_message`, for a message account using the legacy layout.
+    fn unpack_authorized_legacy_message(
+        program_id: &Pubkey,
+        signer_info: &AccountInfo,
+        message_info: &AccountInfo,
+    ) -> Result<HelloWorldStateV1, ProgramError> {
+        //check permission 用户构建transaction是否用了自己的进行签名，如果是的话，runtime会进行校验，因此只要判断他是否是被校验的单元,无需自己去调用鉴权接口。
+        if !signer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Self::check_message_account(program_id, message_info)?;
+
+        let legacy = HelloWorldStateV1::unpack_unchecked(&message_info.data.borrow())?;
+        if !legacy.is_initialized() {
+            return Err(HelloWorldError::AccountNotInitialized.into());
+        }
+        Self::check_author(signer_info, &legacy.account_key)?;
+        Ok(legacy)
+    }
+
+    /// Unpacks the program config, `None` while it has not been initialized.
+    fn unpack_config(
+        program_id: &Pubkey,
+        config_info: &AccountInfo,
+    ) -> Result<Option<Config>, ProgramError> {
+        if find_config_address(program_id).0 != *config_info.key {
+            return Err(HelloWorldError::InvalidConfigAccount.into());
+        }
+        if config_info.owner != program_id {
+            return Ok(None);
+        }
+        let config = Config::unpack_unchecked(&config_info.data.borrow())?;
+        Ok(Some(config).filter(|config| config.is_initialized))
+    }
+
+    /// Takes the program config account, a missing one fails with `InvalidConfigAccount`
+    /// like a wrong one.
+    fn next_config_info<'a, 'b, I: Iterator<Item = &'a AccountInfo<'b>>>(
+        iter: &mut I,
+    ) -> Result<I::Item, ProgramError> {
+        next_account_info(iter).map_err(|_| HelloWorldError::InvalidConfigAccount.into())
+    }
+
+    /// Same as `unpack_config`, failing while the program is paused.
+    fn unpack_active_config(
+        program_id: &Pubkey,
+        config_info: &AccountInfo,
+    ) -> Result<Option<Config>, ProgramError> {
+        let config = Self::unpack_config(program_id, config_info)?;
+        if matches!(config, Some(config) if config.paused) {
+            return Err(HelloWorldError::ProgramPaused.into());
+        }
+        Ok(config)
+    }
+
+    /// Unpacks the initialized program config and checks that `admin_info` is its signing admin.
+    fn unpack_admin_config(
+        program_id: &Pubkey,
+        admin_info: &AccountInfo,
+        config_info: &AccountInfo,
+    ) -> Result<Config, ProgramError> {
+        let config = Self::unpack_config(program_id, config_info)?
+            .ok_or(HelloWorldError::AccountNotInitialized)?;
+        if !admin_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Self::check_author(admin_info, &config.admin)?;
+        Ok(config)
+    }
+
+    /// Unpacks a bounty vault and checks that `author_info` is the signing author of the bounty.
+    fn unpack_bounty(
+        program_id: &Pubkey,
+        author_info: &AccountInfo,
+        bounty_info: &AccountInfo,
+    ) -> Result<Bounty, ProgramError> {
+        if !author_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if bounty_info.owner != program_id {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+        let bounty = Bounty::unpack(&bounty_info.data.borrow())?;
+        // 其他账号的数据也可能被解析成金库，金库地址必须由问题推导出来
+        if find_bounty_address(program_id, &bounty.question).0 != *bounty_info.key {
+            msg!("Error: bounty address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Self::check_author(author_info, &bounty.author)?;
+        Ok(bounty)
+    }
+
+    /// Reads the upgrade authority from the ProgramData account of the program, `None` once
+    /// the program is immutable.
+    fn unpack_upgrade_authority(
+        program_id: &Pubkey,
+        program_data_info: &AccountInfo,
+    ) -> Result<Option<Pubkey>, ProgramError> {
+        if *program_data_info.key != find_program_data_address(program_id).0 {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if *program_data_info.owner != bpf_loader_upgradeable::id() {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+        // bincode编码的UpgradeableLoaderState::ProgramData：u32枚举序号3，u64部署的slot，
+        // 然后是Option<Pubkey>
+        let data = program_data_info.data.borrow();
+        if data.len() < PROGRAM_DATA_METADATA_LEN || data[..4] != PROGRAM_DATA_TAG.to_le_bytes() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        match data[12] {
+            0 => Ok(None),
+            1 => {
+                let authority = <[u8; 32]>::try_from(&data[13..PROGRAM_DATA_METADATA_LEN]).unwrap();
+                Ok(Some(Pubkey::new_from_array(authority)))
+            }
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    /// Checks the vault and the token program of a poll with a vote mint, returns the bump
+    /// seed of the vault.
+    fn check_poll_vault(
+        program_id: &Pubkey,
+        poll_info: &AccountInfo,
+        vault_info: &AccountInfo,
+        token_program_info: &AccountInfo,
+    ) -> Result<u8, ProgramError> {
+        if *token_program_info.key != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let (vault_address, bump_seed) = find_poll_vault_address(program_id, poll_info.key);
+        if vault_address != *vault_info.key {
+            msg!("Error: poll vault address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(bump_seed)
+    }
+
+    fn unpack_token_account(account_info: &AccountInfo) -> Result<TokenAccount, ProgramError> {
+        if *account_info.owner != spl_token::id() {
+            return Err(HelloWorldError::InvalidFeeAccount.into());
+        }
+        TokenAccount::unpack(&account_info.data.borrow())
+            .map_err(|_| HelloWorldError::InvalidFeeAccount.into())
+    }
+
+    /// Transfers the posting fee of `config` from `source_info` to the treasury.
+    fn pay_fee<'a>(
+        config: &Config,
+        source_info: &AccountInfo<'a>,
+        treasury_info: &AccountInfo<'a>,
+        owner_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        if *token_program_info.key != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if *treasury_info.key != config.treasury {
+            msg!("Error: fee must be paid to the treasury of the config");
+            return Err(HelloWorldError::InvalidFeeAccount.into());
+        }
+        let source = Self::unpack_token_account(source_info)?;
+        if source.mint != config.fee_mint {
+            msg!("Error: fee must be paid in the fee mint of the config");
+            return Err(HelloWorldError::InvalidFeeAccount.into());
+        }
+        // 余额不足时由本程序给出明确的错误，而不是token程序的通用错误
+        if source.amount < config.fee_amount {
+            return Err(HelloWorldError::InsufficientFeeFunds.into());
+        }
+
+        invoke(
+            &spl_token::instruction::transfer(
+                token_program_info.key,
+                source_info.key,
+                treasury_info.key,
+                owner_info.key,
+                &[],
+                config.fee_amount,
+            )?,
+            &[
+                source_info.clone(),
+                treasury_info.clone(),
+                owner_info.clone(),
+                token_program_info.clone(),
+            ],
+        )
+    }
+
+    fn check_message_account(program_id: &Pubkey, message_info: &AccountInfo) -> ProgramResult {
+        // 只有本合约拥有的账号才是可信的消息账号，否则任何人都可以伪造一个内容相同的账号
+        if message_info.owner != program_id {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+        Ok(())
+    }
+
+    fn check_author(signer_info: &AccountInfo, author: &Pubkey) -> ProgramResult {
+        if author != signer_info.key {
+            return Err(HelloWorldError::Unauthorized.into());
+        }
+        Ok(())
+    }
+
+    /// Validates owner(s) are present: `owner_account_info` must be `expected_owner` and either
+    /// sign, or be a multisignature account with M of its signers among `signers`.
+    pub fn validate_owner(
+        program_id: &Pubkey,
+        expected_owner: &Pubkey,
+        owner_account_info: &AccountInfo,
+        signers: &[AccountInfo],
+    ) -> ProgramResult {
+        Self::check_author(owner_account_info, expected_owner)?;
+        if program_id == owner_account_info.owner && Multisig::is_multisig(&owner_account_info.data.borrow()) {
+            let multisig = Multisig::unpack(&owner_account_info.data.borrow())?;
+            let mut num_signers = 0;
+            let mut matched = [false; MAX_SIGNERS];
+            for signer in signers.iter() {
+                for (position, key) in multisig.signers[0..multisig.n as usize].iter().enumerate() {
+                    if key == signer.key && !matched[position] {
+                        if !signer.is_signer {
+                            return Err(ProgramError::MissingRequiredSignature);
+                        }
+                        matched[position] = true;
+                        num_signers += 1;
+                    }
+                }
+            }
+            if num_signers < multisig.m {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            return Ok(());
+        } else if !owner_account_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(())
+    }
+
+    /// Creates the message account of `author` for `seed` paid by `payer_info`, it must not
+    /// exist yet.
+    fn create_message_account<'a>(
+        program_id: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        author: &Pubkey,
+        message_info: &AccountInfo<'a>,
+        counter_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        seed: &str,
+    ) -> Result<u64, ProgramError> {
+        if seed.len() > MAX_SEED_LEN {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // 消息账号由作者和seed推导出来，客户端无需再保存消息账号的私钥
+        let (message_address, bump_seed) = find_message_address(program_id, author, seed);
+        if message_address != *message_info.key {
+            msg!("Error: message address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if *message_info.owner != system_program::id() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let message_signer_seeds: &[&[_]] = &[
+            MESSAGE_SEED,
+            &author.to_bytes(),
+            seed.as_bytes(),
+            &[bump_seed],
+        ];
+        Self::create_pda_account(
+            payer_info,
+            message_info,
+            system_program_info,
+            HelloWorldState::LEN,
+            program_id,
+            message_signer_seeds,
+        )?;
+        Self::next_creation_id(program_id, payer_info, counter_info, system_program_info)
+    }
+
+    /// Counts a new message in the message counter and returns its `creation_id`, the
+    /// counter is created by `payer_info` along with the first message.
+    fn next_creation_id<'a>(
+        program_id: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        counter_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+    ) -> Result<u64, ProgramError> {
+        let (counter_address, bump_seed) = find_message_counter_address(program_id);
+        if counter_address != *counter_info.key {
+            msg!("Error: message counter address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+        // 同一个地址上删掉又重新创建的消息，即使在同一个slot里也会拿到不同的编号
+        if *counter_info.owner == system_program::id() {
+            Self::create_pda_account(
+                payer_info,
+                counter_info,
+                system_program_info,
+                MessageCounter::LEN,
+                program_id,
+                &[MESSAGE_COUNTER_SEED, &[bump_seed]],
+            )?;
+        } else if counter_info.owner != program_id {
+            return Err(HelloWorldError::IncorrectAccountOwner.into());
+        }
+
+        let mut counter = MessageCounter::unpack_unchecked(&counter_info.data.borrow())?;
+        counter.is_initialized = true;
+        let creation_id = counter.next_creation_id()?;
+        MessageCounter::pack(counter, &mut counter_info.data.borrow_mut())?;
+        Ok(creation_id)
+    }
+
+    /// Closes a program account: its lamports go to `destination_info` and its data is wiped.
+    fn close_account(account_info: &AccountInfo, destination_info: &AccountInfo) -> ProgramResult {
+        // 租金退回到指定的账号，如果退回给消息账号自己，清零lamports后钱就凭空消失了
+        if account_info.key == destination_info.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let destination_starting_lamports = destination_info.lamports();
+        **destination_info.lamports.borrow_mut() = destination_starting_lamports
+            .checked_add(account_info.lamports())
+            .ok_or(HelloWorldError::Overflow)?;
+        **account_info.lamports.borrow_mut() = 0;
+
+        // 同一个交易里还可以再给这个账号转入lamports让它"复活"，所以要清空数据并写入关闭标记
+        HelloWorldState::close(&mut account_info.data.borrow_mut());
+        Ok(())
+    }
+
+    /// Creates a rent-exempt account at a program derived address, owned by `owner`.
+    ///
+    /// The address may already hold lamports sent by anyone, in which case it is topped
+    /// up, allocated and assigned instead of created, since `create_account` would fail.
+    fn create_pda_account<'a>(
+        payer_info: &AccountInfo<'a>,
+        new_account_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        space: usize,
+        owner: &Pubkey,
+        signer_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        let rent = Rent::get()?;
+        let required_lamports = rent.minimum_balance(space);
+
+        if new_account_info.lamports() > 0 {
+            let lamports_needed = required_lamports.saturating_sub(new_account_info.lamports());
+            if lamports_needed > 0 {
+                invoke(
+                    &system_instruction::transfer(payer_info.key, new_account_info.key, lamports_needed),
+                    &[payer_info.clone(), new_account_info.clone(), system_program_info.clone()],
+                )?;
+            }
+            invoke_signed(
+                &system_instruction::allocate(new_account_info.key, space as u64),
+                &[new_account_info.clone(), system_program_info.clone()],
+                &[signer_seeds],
+            )?;
+            invoke_signed(
+                &system_instruction::assign(new_account_info.key, owner),
+                &[new_account_info.clone(), system_program_info.clone()],
+                &[signer_seeds],
+            )
+        } else {
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer_info.key,
+                    new_account_info.key,
+                    required_lamports,
+                    space as u64,
+                    owner,
+                ),
+                &[payer_info.clone(), new_account_info.clone(), system_program_info.clone()],
+                &[signer_seeds],
+            )
+        }
+    }
 }
\ No newline at end of file
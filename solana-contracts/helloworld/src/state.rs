@@ -1,21 +1,132 @@
 //! State transition types
 
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use borsh::{BorshDeserialize, BorshSerialize};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 use solana_program::{
+    clock::{Clock, Slot, UnixTimestamp},
+    hash::{hash, hashv, Hash, HASH_BYTES},
     program_error::ProgramError,
+    program_option::COption,
     program_pack::{IsInitialized, Pack, Sealed},
-    pubkey::Pubkey,
-    msg,
+    pubkey::{Pubkey, MAX_SEED_LEN},
 };
-use std::str::from_utf8;
+use std::{convert::TryFrom, str::from_utf8};
+use crate::error::HelloWorldError;
+
+/// Marker written at the start of an erased message account. The account data is zeroed
+/// when it is closed, so if it is revived within the same transaction by sending lamports
+/// back, the marker makes sure the stale account can never be unpacked again.
+pub const CLOSED_ACCOUNT_DISCRIMINATOR: [u8; 8] = [255; 8];
+
+/// Maximum length in bytes of a message, its length is stored in a single byte
+pub const MAX_MESSAGE_LEN: usize = u8::MAX as usize;
+
+/// Version of the current [HelloWorldState](struct.HelloWorldState.html) layout, stored in its first byte
+pub const STATE_VERSION: u8 = 2;
+
+/// Bytes of the message account kept zeroed for fields added to the current layout later,
+/// so they do not need another migration.
+pub const STATE_RESERVED_LEN: usize = 46;
+
+/// Offset of the `parent` field in a [HelloWorldState](struct.HelloWorldState.html) account,
+/// for `memcmp` filters looking for the replies of a message
+pub const PARENT_OFFSET: usize = 1+32+8+8+8+4+1+8+8+1+HISTORY_LEN*HASH_BYTES+36+8+36;
+
+/// Number of previous message hashes kept in the edit history of a message account
+pub const HISTORY_LEN: usize = 8;
+
+/// Number of kinds of [Reaction](enum.Reaction.html), each message account counts them all
+pub const REACTION_KINDS: usize = 6;
+
+/// Reaction to a message, its value is the index of its counter in `HelloWorldState::reactions`
+#[repr(u8)]
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Clone,
+    Copy,
+    Debug,
+    Eq,
+    IntoPrimitive,
+    PartialEq,
+    TryFromPrimitive,
+)]
+pub enum Reaction {
+    /// 👍
+    Like,
+    /// 👎
+    Dislike,
+    /// 😂
+    Laugh,
+    /// ❤️
+    Heart,
+    /// 🎉
+    Party,
+    /// 👀
+    Eyes,
+}
+
+/// Maximum length in bytes of the text stored in a [MessageChunk](struct.MessageChunk.html),
+/// small enough for an `AppendChunk` instruction to fit into a transaction
+pub const MAX_CHUNK_LEN: usize = 512;
 
 ///state用来将内容存储到对应的文件时，存储格式的定义，类似一个ORM或者所谓的MVC中Model层
+///
+/// This is the current (v2) layout: a version header, the author, the timestamps and edit
+/// counter, the chunks appended to the message, the slots and edit history, the delegate, the
+/// authority, the parent and reply count of the thread, the reaction counters, the time to
+/// live, the creation id, a reserved area and the message. The message stored here is the head of the full
+/// message, the rest is in `chunk_count` [MessageChunk](struct.MessageChunk.html) accounts.
+/// Accounts created before the header was introduced use
+/// [HelloWorldStateV1](struct.HelloWorldStateV1.html) and are upgraded by `Migrate`.
 #[repr(C)]
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct HelloWorldState {
+    /// layout version, `STATE_VERSION` once initialized
+    pub version: u8,
     /// account
     pub account_key: Pubkey,
-    /// message 
+    /// unix timestamp of the creation of the message
+    pub created_at: UnixTimestamp,
+    /// unix timestamp of the last update of the message
+    pub updated_at: UnixTimestamp,
+    /// number of times the message was updated
+    pub edit_count: u64,
+    /// number of chunk accounts holding the rest of the message
+    pub chunk_count: u32,
+    /// the message is complete, it can not be updated or extended anymore
+    pub finalized: bool,
+    /// slot of the creation of the message
+    pub created_slot: Slot,
+    /// slot of the last update of the message
+    pub updated_slot: Slot,
+    /// position in `history` of the next previous message hash
+    pub history_index: u8,
+    /// ring buffer of the hashes of the last `HISTORY_LEN` replaced messages,
+    /// unused entries are the default hash
+    pub history: [Hash; HISTORY_LEN],
+    /// key allowed to post on behalf of the author until `delegate_expiry_slot`
+    pub delegate: COption<Pubkey>,
+    /// first slot at which the delegate can not post anymore
+    pub delegate_expiry_slot: Slot,
+    /// key governing the message instead of the author, it can be a
+    /// [Multisig](struct.Multisig.html) account
+    pub authority: COption<Pubkey>,
+    /// message this one replies to, set once
+    pub parent: COption<Pubkey>,
+    /// number of messages which replied to this one
+    pub reply_count: u64,
+    /// number of reactions of each kind, indexed by [Reaction](enum.Reaction.html)
+    pub reactions: [u64; REACTION_KINDS],
+    /// seconds without update after which anyone can reap the message, it never expires
+    /// when zero
+    pub ttl: u64,
+    /// number of the message among all the messages created by the program, taken from the
+    /// [MessageCounter](struct.MessageCounter.html), a message created again at the same
+    /// address gets another one
+    pub creation_id: u64,
+    /// message
     pub message: String
 }
 
@@ -23,12 +134,1340 @@ impl Sealed for HelloWorldState {}
 
 impl IsInitialized for HelloWorldState {
     fn is_initialized(&self) -> bool {
-        return true;
+        self.version == STATE_VERSION
     }
 }
 
-///SDK提供的Pack trate来实现其序列化和反序列化，
 impl Pack for HelloWorldState {
+    const LEN: usize = 1+32+8+8+8+4+1+8+8+1+HISTORY_LEN*HASH_BYTES+36+8+36+36+8+REACTION_KINDS*8+8+8+STATE_RESERVED_LEN+1+256;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.starts_with(&CLOSED_ACCOUNT_DISCRIMINATOR) {
+            return Err(HelloWorldError::AccountClosed.into());
+        }
+        let src = array_ref![src, 0, HelloWorldState::LEN];
+        let (
+            version_buf,
+            account_key_buf,
+            created_at_buf,
+            updated_at_buf,
+            edit_count_buf,
+            chunk_count_buf,
+            finalized_buf,
+            created_slot_buf,
+            updated_slot_buf,
+            history_index_buf,
+            history_buf,
+            delegate_buf,
+            delegate_expiry_slot_buf,
+            authority_buf,
+            parent_buf,
+            reply_count_buf,
+            reactions_buf,
+            ttl_buf,
+            creation_id_buf,
+            _reserved,
+            message_len_buf,
+            message_buf,
+        ) = array_refs![src, 1, 32, 8, 8, 8, 4, 1, 8, 8, 1, HISTORY_LEN * HASH_BYTES, 36, 8, 36, 36, 8, REACTION_KINDS * 8, 8, 8, STATE_RESERVED_LEN, 1, 256];
+        // 全零的账号是还没有初始化的账号，其他未知的版本号不能按当前格式解析
+        let version = version_buf[0];
+        if version != 0 && version != STATE_VERSION {
+            return Err(HelloWorldError::UnsupportedStateVersion.into());
+        }
+        if history_index_buf[0] as usize >= HISTORY_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut history = [Hash::default(); HISTORY_LEN];
+        for (entry, src) in history.iter_mut().zip(history_buf.chunks_exact(HASH_BYTES)) {
+            *entry = Hash::new(src);
+        }
+        let mut reactions = [0; REACTION_KINDS];
+        for (count, src) in reactions.iter_mut().zip(reactions_buf.chunks_exact(8)) {
+            *count = u64::from_le_bytes(*array_ref![src, 0, 8]);
+        }
+        let message_len = message_len_buf[0];
+        let (msg_buf, _rest) = message_buf.split_at(message_len.into());
+        let message = String::from(from_utf8(msg_buf).map_err(|_| HelloWorldError::InvalidUtf8)?);
+        Ok(HelloWorldState {
+            version,
+            account_key: Pubkey::new_from_array(*account_key_buf),
+            created_at: i64::from_le_bytes(*created_at_buf),
+            updated_at: i64::from_le_bytes(*updated_at_buf),
+            edit_count: u64::from_le_bytes(*edit_count_buf),
+            chunk_count: u32::from_le_bytes(*chunk_count_buf),
+            finalized: unpack_bool(finalized_buf)?,
+            created_slot: u64::from_le_bytes(*created_slot_buf),
+            updated_slot: u64::from_le_bytes(*updated_slot_buf),
+            history_index: history_index_buf[0],
+            history,
+            delegate: unpack_coption_key(delegate_buf)?,
+            delegate_expiry_slot: u64::from_le_bytes(*delegate_expiry_slot_buf),
+            authority: unpack_coption_key(authority_buf)?,
+            parent: unpack_coption_key(parent_buf)?,
+            reply_count: u64::from_le_bytes(*reply_count_buf),
+            reactions,
+            ttl: u64::from_le_bytes(*ttl_buf),
+            creation_id: u64::from_le_bytes(*creation_id_buf),
+            message,
+        })
+    }
+
+    /// The message must be at most `MAX_MESSAGE_LEN` bytes, see `HelloWorldState::check_message`.
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, HelloWorldState::LEN];
+        let (
+            version_buf,
+            account_key_buf,
+            created_at_buf,
+            updated_at_buf,
+            edit_count_buf,
+            chunk_count_buf,
+            finalized_buf,
+            created_slot_buf,
+            updated_slot_buf,
+            history_index_buf,
+            history_buf,
+            delegate_buf,
+            delegate_expiry_slot_buf,
+            authority_buf,
+            parent_buf,
+            reply_count_buf,
+            reactions_buf,
+            ttl_buf,
+            creation_id_buf,
+            _reserved,
+            message_len_buf,
+            message_buf,
+        ) = mut_array_refs![dst, 1, 32, 8, 8, 8, 4, 1, 8, 8, 1, HISTORY_LEN * HASH_BYTES, 36, 8, 36, 36, 8, REACTION_KINDS * 8, 8, 8, STATE_RESERVED_LEN, 1, 256];
+        version_buf[0] = self.version;
+        account_key_buf.copy_from_slice(self.account_key.as_ref());
+        *created_at_buf = self.created_at.to_le_bytes();
+        *updated_at_buf = self.updated_at.to_le_bytes();
+        *edit_count_buf = self.edit_count.to_le_bytes();
+        *chunk_count_buf = self.chunk_count.to_le_bytes();
+        finalized_buf[0] = self.finalized as u8;
+        *created_slot_buf = self.created_slot.to_le_bytes();
+        *updated_slot_buf = self.updated_slot.to_le_bytes();
+        history_index_buf[0] = self.history_index;
+        for (entry, dst) in self.history.iter().zip(history_buf.chunks_exact_mut(HASH_BYTES)) {
+            dst.copy_from_slice(entry.as_ref());
+        }
+        pack_coption_key(&self.delegate, delegate_buf);
+        *delegate_expiry_slot_buf = self.delegate_expiry_slot.to_le_bytes();
+        pack_coption_key(&self.authority, authority_buf);
+        pack_coption_key(&self.parent, parent_buf);
+        *reply_count_buf = self.reply_count.to_le_bytes();
+        for (count, dst) in self.reactions.iter().zip(reactions_buf.chunks_exact_mut(8)) {
+            dst.copy_from_slice(&count.to_le_bytes());
+        }
+        *ttl_buf = self.ttl.to_le_bytes();
+        *creation_id_buf = self.creation_id.to_le_bytes();
+        message_len_buf[0] = self.message.len() as u8;
+        let (msg_buf, rest) = message_buf.split_at_mut(self.message.len());
+        msg_buf.copy_from_slice(self.message.as_bytes());
+        rest.iter_mut().for_each(|byte| *byte = 0);
+    }
+}
+
+/// Maximum number of multisignature signers (max N)
+pub const MAX_SIGNERS: usize = 11;
+/// Minimum number of multisignature signers (min N)
+pub const MIN_SIGNERS: usize = 1;
+
+/// First byte of an initialized multisignature account, so that no other account of this
+/// program with the same size is mistaken for one
+pub const MULTISIG_DISCRIMINATOR: u8 = b'M';
+
+/// Multisignature data, an authority of message accounts requiring M of its N signers.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Multisig {
+    /// Number of signers required
+    pub m: u8,
+    /// Number of valid signers
+    pub n: u8,
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// Signer public keys
+    pub signers: [Pubkey; MAX_SIGNERS],
+}
+
+impl Sealed for Multisig {}
+
+impl IsInitialized for Multisig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Multisig {
+    /// Whether `data` is the data of an initialized multisignature account.
+    pub fn is_multisig(data: &[u8]) -> bool {
+        data.len() == Multisig::LEN && data[0] == MULTISIG_DISCRIMINATOR
+    }
+}
+
+impl Pack for Multisig {
+    const LEN: usize = 356;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 356];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (discriminator, m, n, is_initialized, signers_flat) = array_refs![src, 1, 1, 1, 1, 32 * MAX_SIGNERS];
+        let is_initialized = unpack_bool(is_initialized)?;
+        // 未初始化的账号全是零，初始化过的账号必须带着多签的标记
+        if is_initialized != (discriminator[0] == MULTISIG_DISCRIMINATOR) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut result = Multisig {
+            m: m[0],
+            n: n[0],
+            is_initialized,
+            signers: [Pubkey::default(); MAX_SIGNERS],
+        };
+        for (src, dst) in signers_flat.chunks(32).zip(result.signers.iter_mut()) {
+            *dst = Pubkey::new_from_array(*array_ref![src, 0, 32]);
+        }
+        Ok(result)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 356];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (discriminator, m, n, is_initialized, signers_flat) =
+            mut_array_refs![dst, 1, 1, 1, 1, 32 * MAX_SIGNERS];
+        *discriminator = [if self.is_initialized { MULTISIG_DISCRIMINATOR } else { 0 }];
+        *m = [self.m];
+        *n = [self.n];
+        *is_initialized = [self.is_initialized as u8];
+        for (i, src) in self.signers.iter().enumerate() {
+            let dst_array = array_mut_ref![signers_flat, 32 * i, 32];
+            dst_array.copy_from_slice(src.as_ref());
+        }
+    }
+}
+
+/// Continuation of a message longer than `MAX_MESSAGE_LEN`, stored at the address derived by
+/// `find_chunk_address` from the head message account and the chunk index.
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MessageChunk {
+    /// set once the chunk is written
+    pub is_initialized: bool,
+    /// head message account the chunk belongs to
+    pub message_account: Pubkey,
+    /// position of the chunk in the message, starting at 0
+    pub index: u32,
+    /// text of the chunk
+    pub data: String,
+}
+
+impl Sealed for MessageChunk {}
+
+impl IsInitialized for MessageChunk {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for MessageChunk {
+    const LEN: usize = 1+32+4+2+MAX_CHUNK_LEN;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.starts_with(&CLOSED_ACCOUNT_DISCRIMINATOR) {
+            return Err(HelloWorldError::AccountClosed.into());
+        }
+        let src = array_ref![src, 0, MessageChunk::LEN];
+        let (is_initialized_buf, message_account_buf, index_buf, data_len_buf, data_buf) =
+            array_refs![src, 1, 32, 4, 2, MAX_CHUNK_LEN];
+        let data_len = u16::from_le_bytes(*data_len_buf) as usize;
+        if data_len > MAX_CHUNK_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let data = String::from(from_utf8(&data_buf[..data_len]).map_err(|_| HelloWorldError::InvalidUtf8)?);
+        Ok(MessageChunk {
+            is_initialized: unpack_bool(is_initialized_buf)?,
+            message_account: Pubkey::new_from_array(*message_account_buf),
+            index: u32::from_le_bytes(*index_buf),
+            data,
+        })
+    }
+
+    /// The data must be at most `MAX_CHUNK_LEN` bytes, see `MessageChunk::check_data`.
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, MessageChunk::LEN];
+        let (is_initialized_buf, message_account_buf, index_buf, data_len_buf, data_buf) =
+            mut_array_refs![dst, 1, 32, 4, 2, MAX_CHUNK_LEN];
+        is_initialized_buf[0] = self.is_initialized as u8;
+        message_account_buf.copy_from_slice(self.message_account.as_ref());
+        *index_buf = self.index.to_le_bytes();
+        *data_len_buf = (self.data.len() as u16).to_le_bytes();
+        let (chunk_buf, rest) = data_buf.split_at_mut(self.data.len());
+        chunk_buf.copy_from_slice(self.data.as_bytes());
+        rest.iter_mut().for_each(|byte| *byte = 0);
+    }
+}
+
+impl MessageChunk {
+    /// Checks that a chunk of text fits into a chunk account.
+    pub fn check_data(data: &str) -> Result<(), ProgramError> {
+        if data.len() > MAX_CHUNK_LEN {
+            return Err(HelloWorldError::MessageTooLong.into());
+        }
+        Ok(())
+    }
+}
+
+/// Number of message accounts indexed by a [ChannelPage](struct.ChannelPage.html)
+pub const CHANNEL_PAGE_LEN: usize = 32;
+
+/// Named channel, stored at the address derived by `find_channel_address` from its topic.
+/// The messages posted to it are indexed in order by `CHANNEL_PAGE_LEN` sized
+/// [ChannelPage](struct.ChannelPage.html) accounts.
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Channel {
+    /// set once the channel is created
+    pub is_initialized: bool,
+    /// account which created the channel
+    pub creator: Pubkey,
+    /// topic the channel address is derived from, at most `MAX_SEED_LEN` bytes
+    pub topic: String,
+    /// number of messages posted to the channel
+    pub message_count: u64,
+}
+
+impl Sealed for Channel {}
+
+impl IsInitialized for Channel {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Channel {
+    const LEN: usize = 1+32+1+MAX_SEED_LEN+8;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Channel::LEN];
+        let (is_initialized_buf, creator_buf, topic_len_buf, topic_buf, message_count_buf) =
+            array_refs![src, 1, 32, 1, MAX_SEED_LEN, 8];
+        let topic_len = topic_len_buf[0] as usize;
+        if topic_len > MAX_SEED_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let topic = String::from(from_utf8(&topic_buf[..topic_len]).map_err(|_| HelloWorldError::InvalidUtf8)?);
+        Ok(Channel {
+            is_initialized: unpack_bool(is_initialized_buf)?,
+            creator: Pubkey::new_from_array(*creator_buf),
+            topic,
+            message_count: u64::from_le_bytes(*message_count_buf),
+        })
+    }
+
+    /// The topic must be at most `MAX_SEED_LEN` bytes, see `Channel::check_topic`.
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Channel::LEN];
+        let (is_initialized_buf, creator_buf, topic_len_buf, topic_buf, message_count_buf) =
+            mut_array_refs![dst, 1, 32, 1, MAX_SEED_LEN, 8];
+        is_initialized_buf[0] = self.is_initialized as u8;
+        creator_buf.copy_from_slice(self.creator.as_ref());
+        topic_len_buf[0] = self.topic.len() as u8;
+        let (name_buf, rest) = topic_buf.split_at_mut(self.topic.len());
+        name_buf.copy_from_slice(self.topic.as_bytes());
+        rest.iter_mut().for_each(|byte| *byte = 0);
+        *message_count_buf = self.message_count.to_le_bytes();
+    }
+}
+
+impl Channel {
+    /// Checks that a topic can be used as the seed of a channel account.
+    pub fn check_topic(topic: &str) -> Result<(), ProgramError> {
+        if topic.len() > MAX_SEED_LEN {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
+    }
+
+    /// Number of page accounts holding the index of the channel.
+    pub fn page_count(&self) -> u64 {
+        // 最后一页没有写满也算一页
+        let full_pages = self.message_count / CHANNEL_PAGE_LEN as u64;
+        match self.message_count % CHANNEL_PAGE_LEN as u64 {
+            0 => full_pages,
+            _ => full_pages + 1,
+        }
+    }
+
+    /// Index of the page account receiving the next message, and the position of the
+    /// message in it. A new page starts at position 0.
+    pub fn next_position(&self) -> Result<(u32, usize), ProgramError> {
+        let page = self.message_count / CHANNEL_PAGE_LEN as u64;
+        let page = u32::try_from(page).map_err(|_| HelloWorldError::Overflow)?;
+        Ok((page, (self.message_count % CHANNEL_PAGE_LEN as u64) as usize))
+    }
+}
+
+/// Page of the message index of a [Channel](struct.Channel.html), stored at the address
+/// derived by `find_channel_page_address` from the channel account and the page index.
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChannelPage {
+    /// set once the page is created
+    pub is_initialized: bool,
+    /// channel account the page belongs to
+    pub channel: Pubkey,
+    /// position of the page in the channel, starting at 0
+    pub index: u32,
+    /// message accounts posted to the channel, oldest first, at most `CHANNEL_PAGE_LEN`
+    pub messages: Vec<Pubkey>,
+}
+
+impl Sealed for ChannelPage {}
+
+impl IsInitialized for ChannelPage {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for ChannelPage {
+    const LEN: usize = 1+32+4+1+CHANNEL_PAGE_LEN*32;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, ChannelPage::LEN];
+        let (is_initialized_buf, channel_buf, index_buf, len_buf, messages_buf) =
+            array_refs![src, 1, 32, 4, 1, CHANNEL_PAGE_LEN * 32];
+        let len = len_buf[0] as usize;
+        if len > CHANNEL_PAGE_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let messages = messages_buf
+            .chunks_exact(32)
+            .take(len)
+            .map(|src| Pubkey::new_from_array(*array_ref![src, 0, 32]))
+            .collect();
+        Ok(ChannelPage {
+            is_initialized: unpack_bool(is_initialized_buf)?,
+            channel: Pubkey::new_from_array(*channel_buf),
+            index: u32::from_le_bytes(*index_buf),
+            messages,
+        })
+    }
+
+    /// The page must hold at most `CHANNEL_PAGE_LEN` messages.
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, ChannelPage::LEN];
+        let (is_initialized_buf, channel_buf, index_buf, len_buf, messages_buf) =
+            mut_array_refs![dst, 1, 32, 4, 1, CHANNEL_PAGE_LEN * 32];
+        is_initialized_buf[0] = self.is_initialized as u8;
+        channel_buf.copy_from_slice(self.channel.as_ref());
+        *index_buf = self.index.to_le_bytes();
+        len_buf[0] = self.messages.len() as u8;
+        messages_buf.iter_mut().for_each(|byte| *byte = 0);
+        for (message, dst) in self.messages.iter().zip(messages_buf.chunks_exact_mut(32)) {
+            dst.copy_from_slice(message.as_ref());
+        }
+    }
+}
+
+/// Receipt of the reaction of a wallet to a message, stored at the address derived by
+/// `find_reaction_address` so that a wallet reacts at most once to a message.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReactionReceipt {
+    /// set once the reaction is counted
+    pub is_initialized: bool,
+    /// message account reacted to
+    pub message: Pubkey,
+    /// wallet which reacted, receiving the rent back on `Unreact`
+    pub reactor: Pubkey,
+    /// counted reaction
+    pub reaction: Reaction,
+    /// `creation_id` of the message reacted to, a message created later at the same address
+    /// does not count the reaction
+    pub message_creation_id: u64,
+}
+
+impl ReactionReceipt {
+    /// Checks whether `state` is the message which counted the reaction.
+    pub fn is_counted_by(&self, state: &HelloWorldState) -> bool {
+        state.creation_id == self.message_creation_id
+    }
+}
+
+impl Sealed for ReactionReceipt {}
+
+impl IsInitialized for ReactionReceipt {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for ReactionReceipt {
+    const LEN: usize = 1+32+32+1+8;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, ReactionReceipt::LEN];
+        let (is_initialized_buf, message_buf, reactor_buf, reaction_buf, creation_id_buf) =
+            array_refs![src, 1, 32, 32, 1, 8];
+        Ok(ReactionReceipt {
+            is_initialized: unpack_bool(is_initialized_buf)?,
+            message: Pubkey::new_from_array(*message_buf),
+            reactor: Pubkey::new_from_array(*reactor_buf),
+            reaction: Reaction::try_from(reaction_buf[0]).map_err(|_| ProgramError::InvalidAccountData)?,
+            message_creation_id: u64::from_le_bytes(*creation_id_buf),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, ReactionReceipt::LEN];
+        let (is_initialized_buf, message_buf, reactor_buf, reaction_buf, creation_id_buf) =
+            mut_array_refs![dst, 1, 32, 32, 1, 8];
+        is_initialized_buf[0] = self.is_initialized as u8;
+        message_buf.copy_from_slice(self.message.as_ref());
+        reactor_buf.copy_from_slice(self.reactor.as_ref());
+        reaction_buf[0] = self.reaction.into();
+        *creation_id_buf = self.message_creation_id.to_le_bytes();
+    }
+}
+
+/// Maximum length in bytes of the encrypted payload of a [DirectMessage](struct.DirectMessage.html)
+pub const MAX_DIRECT_MESSAGE_LEN: usize = 512;
+
+/// Inbox of the direct messages received by a wallet, stored at the address derived by
+/// `find_inbox_address`. It lists its messages by index: the message at `index` is stored at
+/// the address derived by `find_direct_message_address`, for every index below `message_count`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Inbox {
+    /// set once the first message is received
+    pub is_initialized: bool,
+    /// recipient of the messages
+    pub owner: Pubkey,
+    /// number of messages received
+    pub message_count: u64,
+}
+
+impl Sealed for Inbox {}
+
+impl IsInitialized for Inbox {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Inbox {
+    const LEN: usize = 1+32+8;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Inbox::LEN];
+        let (is_initialized_buf, owner_buf, message_count_buf) = array_refs![src, 1, 32, 8];
+        Ok(Inbox {
+            is_initialized: unpack_bool(is_initialized_buf)?,
+            owner: Pubkey::new_from_array(*owner_buf),
+            message_count: u64::from_le_bytes(*message_count_buf),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Inbox::LEN];
+        let (is_initialized_buf, owner_buf, message_count_buf) = mut_array_refs![dst, 1, 32, 8];
+        is_initialized_buf[0] = self.is_initialized as u8;
+        owner_buf.copy_from_slice(self.owner.as_ref());
+        *message_count_buf = self.message_count.to_le_bytes();
+    }
+}
+
+/// Direct message between two wallets. Unlike a [HelloWorldState](struct.HelloWorldState.html)
+/// message the payload is opaque to the program: clients seal it for the recipient, so only
+/// the sender and the recipient can read it.
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DirectMessage {
+    /// set once the message is sent
+    pub is_initialized: bool,
+    /// wallet which sent the message
+    pub sender: Pubkey,
+    /// wallet the payload is sealed for
+    pub recipient: Pubkey,
+    /// position of the message in the inbox of the recipient
+    pub index: u64,
+    /// unix timestamp of the message
+    pub created_at: UnixTimestamp,
+    /// encrypted payload, at most `MAX_DIRECT_MESSAGE_LEN` bytes
+    pub payload: Vec<u8>,
+}
+
+impl Sealed for DirectMessage {}
+
+impl IsInitialized for DirectMessage {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for DirectMessage {
+    const LEN: usize = 1+32+32+8+8+2+MAX_DIRECT_MESSAGE_LEN;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, DirectMessage::LEN];
+        let (
+            is_initialized_buf,
+            sender_buf,
+            recipient_buf,
+            index_buf,
+            created_at_buf,
+            payload_len_buf,
+            payload_buf,
+        ) = array_refs![src, 1, 32, 32, 8, 8, 2, MAX_DIRECT_MESSAGE_LEN];
+        let payload_len = u16::from_le_bytes(*payload_len_buf) as usize;
+        if payload_len > MAX_DIRECT_MESSAGE_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(DirectMessage {
+            is_initialized: unpack_bool(is_initialized_buf)?,
+            sender: Pubkey::new_from_array(*sender_buf),
+            recipient: Pubkey::new_from_array(*recipient_buf),
+            index: u64::from_le_bytes(*index_buf),
+            created_at: i64::from_le_bytes(*created_at_buf),
+            payload: payload_buf[..payload_len].to_vec(),
+        })
+    }
+
+    /// The payload must be at most `MAX_DIRECT_MESSAGE_LEN` bytes, see `DirectMessage::check_payload`.
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, DirectMessage::LEN];
+        let (
+            is_initialized_buf,
+            sender_buf,
+            recipient_buf,
+            index_buf,
+            created_at_buf,
+            payload_len_buf,
+            payload_buf,
+        ) = mut_array_refs![dst, 1, 32, 32, 8, 8, 2, MAX_DIRECT_MESSAGE_LEN];
+        is_initialized_buf[0] = self.is_initialized as u8;
+        sender_buf.copy_from_slice(self.sender.as_ref());
+        recipient_buf.copy_from_slice(self.recipient.as_ref());
+        *index_buf = self.index.to_le_bytes();
+        *created_at_buf = self.created_at.to_le_bytes();
+        *payload_len_buf = (self.payload.len() as u16).to_le_bytes();
+        let (data_buf, rest) = payload_buf.split_at_mut(self.payload.len());
+        data_buf.copy_from_slice(&self.payload);
+        rest.iter_mut().for_each(|byte| *byte = 0);
+    }
+}
+
+impl DirectMessage {
+    /// Checks that a payload fits into a direct message account.
+    pub fn check_payload(payload: &[u8]) -> Result<(), ProgramError> {
+        if payload.len() > MAX_DIRECT_MESSAGE_LEN {
+            return Err(HelloWorldError::MessageTooLong.into());
+        }
+        Ok(())
+    }
+}
+
+/// Length in bytes of the salt hashed with a committed message
+pub const SALT_LEN: usize = 32;
+
+/// Point in time of the reveal window of a [Commitment](struct.Commitment.html), the
+/// voting window of a [Poll](struct.Poll.html) or the expiry of a [Bounty](struct.Bounty.html),
+/// either a slot or a unix timestamp.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Timepoint {
+    /// slot of the cluster
+    Slot(Slot),
+    /// unix timestamp of the cluster
+    UnixTimestamp(UnixTimestamp),
+}
+
+impl Timepoint {
+    /// Checks whether the cluster reached this point at the time of `clock`.
+    pub fn has_passed(&self, clock: &Clock) -> bool {
+        match *self {
+            Timepoint::Slot(slot) => clock.slot >= slot,
+            Timepoint::UnixTimestamp(timestamp) => clock.unix_timestamp >= timestamp,
+        }
+    }
+
+    /// Checks whether this point is strictly before `other`, points of different kinds can
+    /// not be compared.
+    pub fn is_before(&self, other: &Timepoint) -> Option<bool> {
+        match (*self, *other) {
+            (Timepoint::Slot(slot), Timepoint::Slot(other)) => Some(slot < other),
+            (Timepoint::UnixTimestamp(timestamp), Timepoint::UnixTimestamp(other)) => {
+                Some(timestamp < other)
+            }
+            _ => None,
+        }
+    }
+
+    fn unpack(src: &[u8; 9]) -> Result<Self, ProgramError> {
+        let (kind, value) = array_refs![src, 1, 8];
+        match kind[0] {
+            0 => Ok(Timepoint::Slot(u64::from_le_bytes(*value))),
+            1 => Ok(Timepoint::UnixTimestamp(i64::from_le_bytes(*value))),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    fn pack(&self, dst: &mut [u8; 9]) {
+        let (kind, value) = mut_array_refs![dst, 1, 8];
+        match *self {
+            Timepoint::Slot(slot) => {
+                kind[0] = 0;
+                *value = slot.to_le_bytes();
+            }
+            Timepoint::UnixTimestamp(timestamp) => {
+                kind[0] = 1;
+                *value = timestamp.to_le_bytes();
+            }
+        }
+    }
+}
+
+/// Hash of a message and its salt, committed by `Commit` and checked by `Reveal`.
+pub fn commitment_hash(message: &str, salt: &[u8; SALT_LEN]) -> Hash {
+    hashv(&[message.as_bytes(), salt])
+}
+
+/// Pending message of a commit-reveal announcement, stored at the address derived by
+/// `find_commitment_address` from the message account. Only the hash of the message and
+/// its salt is known until it is revealed, within `[reveal_after, reveal_deadline)`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Commitment {
+    /// set once the hash is committed
+    pub is_initialized: bool,
+    /// message account the text is revealed to
+    pub message: Pubkey,
+    /// author who committed, revealing the message and receiving the rent back
+    pub committer: Pubkey,
+    /// `commitment_hash` of the message and its salt
+    pub hash: Hash,
+    /// the message can be revealed from this point on
+    pub reveal_after: Timepoint,
+    /// the message can not be revealed anymore from this point on
+    pub reveal_deadline: Timepoint,
+}
+
+impl Sealed for Commitment {}
+
+impl IsInitialized for Commitment {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Commitment {
+    const LEN: usize = 1+32+32+HASH_BYTES+9+9;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Commitment::LEN];
+        let (
+            is_initialized_buf,
+            message_buf,
+            committer_buf,
+            hash_buf,
+            reveal_after_buf,
+            reveal_deadline_buf,
+        ) = array_refs![src, 1, 32, 32, HASH_BYTES, 9, 9];
+        Ok(Commitment {
+            is_initialized: unpack_bool(is_initialized_buf)?,
+            message: Pubkey::new_from_array(*message_buf),
+            committer: Pubkey::new_from_array(*committer_buf),
+            hash: Hash::new_from_array(*hash_buf),
+            reveal_after: Timepoint::unpack(reveal_after_buf)?,
+            reveal_deadline: Timepoint::unpack(reveal_deadline_buf)?,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Commitment::LEN];
+        let (
+            is_initialized_buf,
+            message_buf,
+            committer_buf,
+            hash_buf,
+            reveal_after_buf,
+            reveal_deadline_buf,
+        ) = mut_array_refs![dst, 1, 32, 32, HASH_BYTES, 9, 9];
+        is_initialized_buf[0] = self.is_initialized as u8;
+        message_buf.copy_from_slice(self.message.as_ref());
+        committer_buf.copy_from_slice(self.committer.as_ref());
+        hash_buf.copy_from_slice(self.hash.as_ref());
+        self.reveal_after.pack(reveal_after_buf);
+        self.reveal_deadline.pack(reveal_deadline_buf);
+    }
+}
+
+impl Commitment {
+    /// Checks that the message can be revealed at the time of `clock`.
+    pub fn check_reveal_window(&self, clock: &Clock) -> Result<(), ProgramError> {
+        if !self.reveal_after.has_passed(clock) {
+            return Err(HelloWorldError::RevealTooEarly.into());
+        }
+        if self.reveal_deadline.has_passed(clock) {
+            return Err(HelloWorldError::RevealTooLate.into());
+        }
+        Ok(())
+    }
+}
+
+/// Counter of the messages created by the program, stored at the address derived by
+/// `find_message_counter_address`. It gives every new message its `creation_id`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MessageCounter {
+    /// set once the first message is created
+    pub is_initialized: bool,
+    /// number of messages created so far, the `creation_id` of the last one
+    pub count: u64,
+}
+
+impl MessageCounter {
+    /// Counts a new message and returns its `creation_id`.
+    pub fn next_creation_id(&mut self) -> Result<u64, ProgramError> {
+        self.count = self.count.checked_add(1).ok_or(HelloWorldError::Overflow)?;
+        Ok(self.count)
+    }
+}
+
+impl Sealed for MessageCounter {}
+
+impl IsInitialized for MessageCounter {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for MessageCounter {
+    const LEN: usize = 1+8;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, MessageCounter::LEN];
+        let (is_initialized_buf, count_buf) = array_refs![src, 1, 8];
+        Ok(MessageCounter {
+            is_initialized: unpack_bool(is_initialized_buf)?,
+            count: u64::from_le_bytes(*count_buf),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, MessageCounter::LEN];
+        let (is_initialized_buf, count_buf) = mut_array_refs![dst, 1, 8];
+        is_initialized_buf[0] = self.is_initialized as u8;
+        *count_buf = self.count.to_le_bytes();
+    }
+}
+
+/// Nonce of a message signed off-chain, stored at the address derived by `find_nonce_address`
+/// so that a signature is relayed at most once.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SignedNonce {
+    /// set once the signed message is posted
+    pub is_initialized: bool,
+    /// author who signed the message
+    pub author: Pubkey,
+    /// nonce included in the signature
+    pub nonce: u64,
+    /// message account the signed message was posted to
+    pub message: Pubkey,
+}
+
+impl Sealed for SignedNonce {}
+
+impl IsInitialized for SignedNonce {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for SignedNonce {
+    const LEN: usize = 1+32+8+32;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, SignedNonce::LEN];
+        let (is_initialized_buf, author_buf, nonce_buf, message_buf) = array_refs![src, 1, 32, 8, 32];
+        Ok(SignedNonce {
+            is_initialized: unpack_bool(is_initialized_buf)?,
+            author: Pubkey::new_from_array(*author_buf),
+            nonce: u64::from_le_bytes(*nonce_buf),
+            message: Pubkey::new_from_array(*message_buf),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, SignedNonce::LEN];
+        let (is_initialized_buf, author_buf, nonce_buf, message_buf) =
+            mut_array_refs![dst, 1, 32, 8, 32];
+        is_initialized_buf[0] = self.is_initialized as u8;
+        author_buf.copy_from_slice(self.author.as_ref());
+        *nonce_buf = self.nonce.to_le_bytes();
+        message_buf.copy_from_slice(self.message.as_ref());
+    }
+}
+
+/// Maximum length in bytes of the question of a [Poll](struct.Poll.html)
+pub const MAX_POLL_QUESTION_LEN: usize = u8::MAX as usize;
+
+/// Maximum number of options of a [Poll](struct.Poll.html)
+pub const MAX_POLL_OPTIONS: usize = 8;
+
+/// Minimum number of options of a [Poll](struct.Poll.html)
+pub const MIN_POLL_OPTIONS: usize = 2;
+
+/// Maximum length in bytes of the label of a poll option
+pub const MAX_POLL_OPTION_LEN: usize = 32;
+
+/// Bytes of a packed [PollOption](struct.PollOption.html): label length, label and votes
+const POLL_OPTION_LEN: usize = 1+MAX_POLL_OPTION_LEN+8;
+
+/// Option of a [Poll](struct.Poll.html) and its tally.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PollOption {
+    /// label of the option, at most `MAX_POLL_OPTION_LEN` bytes
+    pub label: String,
+    /// sum of the weights of the votes for the option
+    pub votes: u64,
+}
+
+impl PollOption {
+    fn unpack(src: &[u8; POLL_OPTION_LEN]) -> Result<Self, ProgramError> {
+        let (label_len_buf, label_buf, votes_buf) = array_refs![src, 1, MAX_POLL_OPTION_LEN, 8];
+        let label_len = label_len_buf[0] as usize;
+        if label_len > MAX_POLL_OPTION_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let label = from_utf8(&label_buf[..label_len]).map_err(|_| HelloWorldError::InvalidUtf8)?;
+        Ok(PollOption {
+            label: String::from(label),
+            votes: u64::from_le_bytes(*votes_buf),
+        })
+    }
+
+    fn pack(&self, dst: &mut [u8; POLL_OPTION_LEN]) {
+        let (label_len_buf, label_buf, votes_buf) = mut_array_refs![dst, 1, MAX_POLL_OPTION_LEN, 8];
+        label_len_buf[0] = self.label.len() as u8;
+        let (text_buf, rest) = label_buf.split_at_mut(self.label.len());
+        text_buf.copy_from_slice(self.label.as_bytes());
+        rest.iter_mut().for_each(|byte| *byte = 0);
+        *votes_buf = self.votes.to_le_bytes();
+    }
+}
+
+/// Poll stored at the address derived by `find_poll_address` from its creator and a seed.
+/// Votes are accepted within `[opens_at, closes_at)`, each wallet votes once, see
+/// [VoteReceipt](struct.VoteReceipt.html).
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Poll {
+    /// set once the poll is created
+    pub is_initialized: bool,
+    /// account which created the poll
+    pub creator: Pubkey,
+    /// question, at most `MAX_POLL_QUESTION_LEN` bytes
+    pub question: String,
+    /// options and their tallies, between `MIN_POLL_OPTIONS` and `MAX_POLL_OPTIONS`
+    pub options: Vec<PollOption>,
+    /// votes are accepted from this point on
+    pub opens_at: Timepoint,
+    /// votes are not accepted anymore from this point on
+    pub closes_at: Timepoint,
+    /// mint weighting the votes by the tokens escrowed, one vote per wallet when `None`
+    pub vote_mint: COption<Pubkey>,
+    /// number of wallets which voted
+    pub voter_count: u64,
+}
+
+impl Sealed for Poll {}
+
+impl IsInitialized for Poll {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Poll {
+    const LEN: usize = 1+32+1+MAX_POLL_QUESTION_LEN+1+MAX_POLL_OPTIONS*POLL_OPTION_LEN+9+9+36+8;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Poll::LEN];
+        let (
+            is_initialized_buf,
+            creator_buf,
+            question_len_buf,
+            question_buf,
+            option_count_buf,
+            options_buf,
+            opens_at_buf,
+            closes_at_buf,
+            vote_mint_buf,
+            voter_count_buf,
+        ) = array_refs![
+            src,
+            1,
+            32,
+            1,
+            MAX_POLL_QUESTION_LEN,
+            1,
+            MAX_POLL_OPTIONS * POLL_OPTION_LEN,
+            9,
+            9,
+            36,
+            8
+        ];
+        let question_len = question_len_buf[0] as usize;
+        let option_count = option_count_buf[0] as usize;
+        if option_count > MAX_POLL_OPTIONS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let question = from_utf8(&question_buf[..question_len]).map_err(|_| HelloWorldError::InvalidUtf8)?;
+        let options = options_buf
+            .chunks_exact(POLL_OPTION_LEN)
+            .take(option_count)
+            .map(|src| PollOption::unpack(array_ref![src, 0, POLL_OPTION_LEN]))
+            .collect::<Result<_, _>>()?;
+        Ok(Poll {
+            is_initialized: unpack_bool(is_initialized_buf)?,
+            creator: Pubkey::new_from_array(*creator_buf),
+            question: String::from(question),
+            options,
+            opens_at: Timepoint::unpack(opens_at_buf)?,
+            closes_at: Timepoint::unpack(closes_at_buf)?,
+            vote_mint: unpack_coption_key(vote_mint_buf)?,
+            voter_count: u64::from_le_bytes(*voter_count_buf),
+        })
+    }
+
+    /// The question and options must pass `Poll::check_content`.
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Poll::LEN];
+        let (
+            is_initialized_buf,
+            creator_buf,
+            question_len_buf,
+            question_buf,
+            option_count_buf,
+            options_buf,
+            opens_at_buf,
+            closes_at_buf,
+            vote_mint_buf,
+            voter_count_buf,
+        ) = mut_array_refs![
+            dst,
+            1,
+            32,
+            1,
+            MAX_POLL_QUESTION_LEN,
+            1,
+            MAX_POLL_OPTIONS * POLL_OPTION_LEN,
+            9,
+            9,
+            36,
+            8
+        ];
+        is_initialized_buf[0] = self.is_initialized as u8;
+        creator_buf.copy_from_slice(self.creator.as_ref());
+        question_len_buf[0] = self.question.len() as u8;
+        let (text_buf, rest) = question_buf.split_at_mut(self.question.len());
+        text_buf.copy_from_slice(self.question.as_bytes());
+        rest.iter_mut().for_each(|byte| *byte = 0);
+        option_count_buf[0] = self.options.len() as u8;
+        options_buf.iter_mut().for_each(|byte| *byte = 0);
+        for (option, dst) in self.options.iter().zip(options_buf.chunks_exact_mut(POLL_OPTION_LEN)) {
+            option.pack(array_mut_ref![dst, 0, POLL_OPTION_LEN]);
+        }
+        self.opens_at.pack(opens_at_buf);
+        self.closes_at.pack(closes_at_buf);
+        pack_coption_key(&self.vote_mint, vote_mint_buf);
+        *voter_count_buf = self.voter_count.to_le_bytes();
+    }
+}
+
+impl Poll {
+    /// Checks that a question and its options fit into a poll account.
+    pub fn check_content(question: &str, options: &[String]) -> Result<(), ProgramError> {
+        if question.len() > MAX_POLL_QUESTION_LEN {
+            return Err(HelloWorldError::MessageTooLong.into());
+        }
+        if options.len() < MIN_POLL_OPTIONS
+            || options.len() > MAX_POLL_OPTIONS
+            || options.iter().any(|label| label.is_empty() || label.len() > MAX_POLL_OPTION_LEN)
+        {
+            return Err(HelloWorldError::InvalidPollOption.into());
+        }
+        Ok(())
+    }
+
+    /// Checks that the poll accepts votes at the time of `clock`.
+    pub fn check_open(&self, clock: &Clock) -> Result<(), ProgramError> {
+        if !self.opens_at.has_passed(clock) {
+            return Err(HelloWorldError::PollNotOpen.into());
+        }
+        if self.closes_at.has_passed(clock) {
+            return Err(HelloWorldError::PollClosed.into());
+        }
+        Ok(())
+    }
+
+    /// Counts the vote of a new voter for `option` with `weight`, returns the new tally of
+    /// the option.
+    pub fn add_vote(&mut self, option: u8, weight: u64) -> Result<u64, ProgramError> {
+        let option = self
+            .options
+            .get_mut(option as usize)
+            .ok_or(HelloWorldError::InvalidPollOption)?;
+        option.votes = option.votes.checked_add(weight).ok_or(HelloWorldError::Overflow)?;
+        self.voter_count = self.voter_count.checked_add(1).ok_or(HelloWorldError::Overflow)?;
+        Ok(option.votes)
+    }
+
+    /// Sum of the tallies of all options.
+    pub fn total_votes(&self) -> u128 {
+        self.options.iter().map(|option| option.votes as u128).sum()
+    }
+}
+
+/// Receipt of the vote of a wallet in a poll, stored at the address derived by
+/// `find_vote_address` so that a wallet votes at most once in a poll.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VoteReceipt {
+    /// set once the vote is counted
+    pub is_initialized: bool,
+    /// poll account voted in
+    pub poll: Pubkey,
+    /// wallet which voted
+    pub voter: Pubkey,
+    /// index of the option voted for
+    pub option: u8,
+    /// weight of the vote, the tokens escrowed in the vault of the poll, or 1
+    pub weight: u64,
+    /// slot of the vote
+    pub slot: Slot,
+}
+
+impl Sealed for VoteReceipt {}
+
+impl IsInitialized for VoteReceipt {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for VoteReceipt {
+    const LEN: usize = 1+32+32+1+8+8;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, VoteReceipt::LEN];
+        let (is_initialized_buf, poll_buf, voter_buf, option_buf, weight_buf, slot_buf) =
+            array_refs![src, 1, 32, 32, 1, 8, 8];
+        Ok(VoteReceipt {
+            is_initialized: unpack_bool(is_initialized_buf)?,
+            poll: Pubkey::new_from_array(*poll_buf),
+            voter: Pubkey::new_from_array(*voter_buf),
+            option: option_buf[0],
+            weight: u64::from_le_bytes(*weight_buf),
+            slot: u64::from_le_bytes(*slot_buf),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, VoteReceipt::LEN];
+        let (is_initialized_buf, poll_buf, voter_buf, option_buf, weight_buf, slot_buf) =
+            mut_array_refs![dst, 1, 32, 32, 1, 8, 8];
+        is_initialized_buf[0] = self.is_initialized as u8;
+        poll_buf.copy_from_slice(self.poll.as_ref());
+        voter_buf.copy_from_slice(self.voter.as_ref());
+        option_buf[0] = self.option;
+        *weight_buf = self.weight.to_le_bytes();
+        *slot_buf = self.slot.to_le_bytes();
+    }
+}
+
+/// Bounty of a question, stored at the address derived by `find_bounty_address` from the
+/// question message. The account is also the vault: it holds `amount` lamports on top of its
+/// rent until the bounty is awarded to a reply or cancelled.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounty {
+    /// set once the bounty is posted
+    pub is_initialized: bool,
+    /// message account asking the question
+    pub question: Pubkey,
+    /// author who posted the bounty, awarding it and receiving the rent back
+    pub author: Pubkey,
+    /// lamports paid to the author of the awarded reply
+    pub amount: u64,
+    /// the author can cancel the bounty from this point on
+    pub expires_at: Timepoint,
+}
+
+impl Sealed for Bounty {}
+
+impl IsInitialized for Bounty {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Bounty {
+    const LEN: usize = 1+32+32+8+9;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Bounty::LEN];
+        let (is_initialized_buf, question_buf, author_buf, amount_buf, expires_at_buf) =
+            array_refs![src, 1, 32, 32, 8, 9];
+        Ok(Bounty {
+            is_initialized: unpack_bool(is_initialized_buf)?,
+            question: Pubkey::new_from_array(*question_buf),
+            author: Pubkey::new_from_array(*author_buf),
+            amount: u64::from_le_bytes(*amount_buf),
+            expires_at: Timepoint::unpack(expires_at_buf)?,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Bounty::LEN];
+        let (is_initialized_buf, question_buf, author_buf, amount_buf, expires_at_buf) =
+            mut_array_refs![dst, 1, 32, 32, 8, 9];
+        is_initialized_buf[0] = self.is_initialized as u8;
+        question_buf.copy_from_slice(self.question.as_ref());
+        author_buf.copy_from_slice(self.author.as_ref());
+        *amount_buf = self.amount.to_le_bytes();
+        self.expires_at.pack(expires_at_buf);
+    }
+}
+
+/// Bytes of the config account kept zeroed for settings added later
+pub const CONFIG_RESERVED_LEN: usize = 61;
+
+/// Denominator of the shares expressed in basis points
+pub const MAX_BPS: u16 = 10_000;
+
+/// Share of the lamports of a reaped message paid to the caller of `Reap`, in basis points,
+/// when the program has no config and for new configs
+pub const DEFAULT_REAP_BOUNTY_BPS: u16 = 500;
+
+/// Program wide settings, stored in the singleton account derived by `find_config_address`.
+/// Until it is initialized, posting is free.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Config {
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// key allowed to change the settings
+    pub admin: Pubkey,
+    /// mint of the token the posting fee is paid in
+    pub fee_mint: Pubkey,
+    /// amount of `fee_mint` tokens charged by every `Hello`, posting is free when zero
+    pub fee_amount: u64,
+    /// token account of `fee_mint` receiving the fees
+    pub treasury: Pubkey,
+    /// while `true` every instruction but the admin ones is rejected
+    pub paused: bool,
+    /// share of the lamports of a reaped message paid to the caller of `Reap`, in basis points
+    pub reap_bounty_bps: u16,
+}
+
+impl Sealed for Config {}
+
+impl IsInitialized for Config {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Config {
+    const LEN: usize = 1+32+32+8+32+1+2+CONFIG_RESERVED_LEN;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Config::LEN];
+        let (is_initialized, admin, fee_mint, fee_amount, treasury, paused, reap_bounty_bps, _reserved) =
+            array_refs![src, 1, 32, 32, 8, 32, 1, 2, CONFIG_RESERVED_LEN];
+        Ok(Config {
+            is_initialized: unpack_bool(is_initialized)?,
+            admin: Pubkey::new_from_array(*admin),
+            fee_mint: Pubkey::new_from_array(*fee_mint),
+            fee_amount: u64::from_le_bytes(*fee_amount),
+            treasury: Pubkey::new_from_array(*treasury),
+            paused: unpack_bool(paused)?,
+            reap_bounty_bps: u16::from_le_bytes(*reap_bounty_bps),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Config::LEN];
+        let (is_initialized, admin, fee_mint, fee_amount, treasury, paused, reap_bounty_bps, reserved) =
+            mut_array_refs![dst, 1, 32, 32, 8, 32, 1, 2, CONFIG_RESERVED_LEN];
+        *is_initialized = [self.is_initialized as u8];
+        admin.copy_from_slice(self.admin.as_ref());
+        fee_mint.copy_from_slice(self.fee_mint.as_ref());
+        *fee_amount = self.fee_amount.to_le_bytes();
+        treasury.copy_from_slice(self.treasury.as_ref());
+        *paused = [self.paused as u8];
+        *reap_bounty_bps = self.reap_bounty_bps.to_le_bytes();
+        *reserved = [0; CONFIG_RESERVED_LEN];
+    }
+}
+
+// Helpers
+fn pack_coption_key(src: &COption<Pubkey>, dst: &mut [u8; 36]) {
+    let (tag, body) = mut_array_refs![dst, 4, 32];
+    match src {
+        COption::Some(key) => {
+            *tag = [1, 0, 0, 0];
+            body.copy_from_slice(key.as_ref());
+        }
+        COption::None => {
+            *tag = [0; 4];
+            *body = [0; 32];
+        }
+    }
+}
+fn unpack_coption_key(src: &[u8; 36]) -> Result<COption<Pubkey>, ProgramError> {
+    let (tag, body) = array_refs![src, 4, 32];
+    match *tag {
+        [0, 0, 0, 0] => Ok(COption::None),
+        [1, 0, 0, 0] => Ok(COption::Some(Pubkey::new_from_array(*body))),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+fn unpack_bool(src: &[u8; 1]) -> Result<bool, ProgramError> {
+    match src[0] {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Layout of the message accounts before the version header was introduced: the author
+/// followed by the message, 289 bytes in total.
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HelloWorldStateV1 {
+    /// account
+    pub account_key: Pubkey,
+    /// message 
+    pub message: String
+}
+
+impl Sealed for HelloWorldStateV1 {}
+
+impl IsInitialized for HelloWorldStateV1 {
+    /// The author is recorded when the account is initialized, a zeroed key means nobody owns it yet
+    fn is_initialized(&self) -> bool {
+        self.account_key != Pubkey::default()
+    }
+}
+
+///SDK提供的Pack trate来实现其序列化和反序列化，
+impl Pack for HelloWorldStateV1 {
     //当前Solana上，Account仅可以初始化一次长度信息，创建后不可更改。
     const LEN: usize = 32+1+256; // max hello message's length is 256
 
@@ -36,13 +1475,16 @@ impl Pack for HelloWorldState {
         //通过array_refs指定三个成员的内容，这里我们在序列化文件内容时，采用和Instruction一样的二进制序列化方法，
         //对于Pubkey其固定为32个字节。
         //对于Message，其长度约定小于256，这样用一个字节表示长度，后面256个字节表示内容（256不一定全部用完，分配空间)
+        if src.starts_with(&CLOSED_ACCOUNT_DISCRIMINATOR) {
+            return Err(HelloWorldError::AccountClosed.into());
+        }
         let src = array_ref![src, 0, 289];
         let (account_key_buf, message_len_buf, message_buf) = array_refs![src, 32, 1, 256];
         let account_key = Pubkey::new_from_array(*account_key_buf);
-        let message_len = message_len_buf[0] as u8;
+        let message_len = message_len_buf[0];
         let (msg_buf, _rest) = message_buf.split_at(message_len.into());
-        let message = String::from(from_utf8(msg_buf).unwrap()) ;
-        Ok(HelloWorldState {
+        let message = String::from(from_utf8(msg_buf).map_err(|_| HelloWorldError::InvalidUtf8)?);
+        Ok(HelloWorldStateV1 {
             account_key,
             message
         })
@@ -58,7 +1500,723 @@ impl Pack for HelloWorldState {
         ) = mut_array_refs![dst, 32, 1, 256];
         account_key_buf.copy_from_slice(self.account_key.as_ref());
         message_len_buf[0] = self.message.len() as u8;
-        msg!("pack copy slice");
-        message_buf[..self.message.len()].copy_from_slice(&self.message.as_bytes());
+        message_buf[..self.message.len()].copy_from_slice(self.message.as_bytes());
+    }
+}
+
+impl HelloWorldState {
+    /// Checks that a message fits into the message account.
+    pub fn check_message(message: &str) -> Result<(), ProgramError> {
+        if message.len() > MAX_MESSAGE_LEN {
+            return Err(HelloWorldError::MessageTooLong.into());
+        }
+        Ok(())
+    }
+
+    /// Creates the empty message of `author` numbered `creation_id` at the time of `clock`.
+    pub fn new(author: Pubkey, creation_id: u64, clock: &Clock) -> Self {
+        HelloWorldState {
+            version: STATE_VERSION,
+            account_key: author,
+            creation_id,
+            created_at: clock.unix_timestamp,
+            updated_at: clock.unix_timestamp,
+            created_slot: clock.slot,
+            updated_slot: clock.slot,
+            ..HelloWorldState::default()
+        }
+    }
+
+    /// Upgrades a legacy message to the current layout, `clock` gives its creation time
+    /// since the legacy layout did not record one.
+    pub fn from_v1(legacy: HelloWorldStateV1, creation_id: u64, clock: &Clock) -> Self {
+        HelloWorldState {
+            message: legacy.message,
+            ..HelloWorldState::new(legacy.account_key, creation_id, clock)
+        }
+    }
+
+    /// Sets the time of the last update of the message.
+    pub fn touch(&mut self, clock: &Clock) {
+        self.updated_at = clock.unix_timestamp;
+        self.updated_slot = clock.slot;
+    }
+
+    /// Records an update of the message content at the time of `clock`.
+    pub fn record_edit(&mut self, clock: &Clock) -> Result<(), ProgramError> {
+        self.touch(clock);
+        self.edit_count = self.edit_count.checked_add(1).ok_or(HelloWorldError::Overflow)?;
+        Ok(())
+    }
+
+    /// Replaces the message, the hash of the previous one is kept in the edit history.
+    pub fn set_message(&mut self, message: String, clock: &Clock) -> Result<(), ProgramError> {
+        Self::check_message(&message)?;
+        let index = self.history_index as usize;
+        self.history[index] = hash(self.message.as_bytes());
+        self.history_index = ((index + 1) % HISTORY_LEN) as u8;
+        self.message = message;
+        self.record_edit(clock)
+    }
+
+    /// Hashes of the replaced messages kept in the edit history, the most recent first.
+    pub fn previous_hashes(&self) -> Vec<Hash> {
+        let index = self.history_index as usize;
+        self.history[index..]
+            .iter()
+            .chain(self.history[..index].iter())
+            .rev()
+            .filter(|entry| **entry != Hash::default())
+            .copied()
+            .collect()
+    }
+
+    /// Makes the message a reply to `parent`, returns `false` if it already was one.
+    /// A message replies to a single parent, and only while it has no replies itself so
+    /// that threads can never loop back onto themselves.
+    pub fn set_parent(&mut self, parent: &Pubkey) -> Result<bool, ProgramError> {
+        match self.parent {
+            COption::Some(current) if current == *parent => Ok(false),
+            COption::Some(_) => Err(HelloWorldError::InvalidParent.into()),
+            COption::None if self.reply_count > 0 => Err(HelloWorldError::InvalidParent.into()),
+            COption::None => {
+                self.parent = COption::Some(*parent);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Counts a new reply to the message.
+    pub fn add_reply(&mut self) -> Result<(), ProgramError> {
+        self.reply_count = self.reply_count.checked_add(1).ok_or(HelloWorldError::Overflow)?;
+        Ok(())
+    }
+
+    /// Counts a reaction to the message.
+    pub fn add_reaction(&mut self, reaction: Reaction) -> Result<(), ProgramError> {
+        let count = &mut self.reactions[u8::from(reaction) as usize];
+        *count = count.checked_add(1).ok_or(HelloWorldError::Overflow)?;
+        Ok(())
+    }
+
+    /// Takes back a reaction counted by `add_reaction`.
+    pub fn remove_reaction(&mut self, reaction: Reaction) -> Result<(), ProgramError> {
+        let count = &mut self.reactions[u8::from(reaction) as usize];
+        *count = count.checked_sub(1).ok_or(HelloWorldError::Overflow)?;
+        Ok(())
+    }
+
+    /// Time from which the message can be reaped, `None` if it never expires.
+    pub fn expires_at(&self) -> Option<UnixTimestamp> {
+        if self.ttl == 0 {
+            return None;
+        }
+        let ttl = i64::try_from(self.ttl).unwrap_or(i64::MAX);
+        Some(self.updated_at.saturating_add(ttl))
+    }
+
+    /// Checks whether the message can be reaped at `now`.
+    pub fn is_expired(&self, now: UnixTimestamp) -> bool {
+        matches!(self.expires_at(), Some(expires_at) if now >= expires_at)
+    }
+
+    /// Key governing the message: its authority if one is set, otherwise the author.
+    pub fn authority_key(&self) -> Pubkey {
+        match self.authority {
+            COption::Some(authority) => authority,
+            COption::None => self.account_key,
+        }
+    }
+
+    /// Checks that `signer` may post the message at `slot`: the governing authority always
+    /// can, the delegate only before its grant expires.
+    pub fn check_poster(&self, signer: &Pubkey, slot: Slot) -> Result<(), ProgramError> {
+        if *signer == self.authority_key() {
+            return Ok(());
+        }
+        match self.delegate {
+            COption::Some(delegate) if delegate == *signer => {
+                if slot >= self.delegate_expiry_slot {
+                    return Err(HelloWorldError::DelegateExpired.into());
+                }
+                Ok(())
+            }
+            _ => Err(HelloWorldError::Unauthorized.into()),
+        }
+    }
+
+    /// Zeroes an account's data and writes the closed marker, so it is rejected by `unpack`.
+    pub fn close(dst: &mut [u8]) {
+        dst.iter_mut().for_each(|byte| *byte = 0);
+        dst[..CLOSED_ACCOUNT_DISCRIMINATOR.len()].copy_from_slice(&CLOSED_ACCOUNT_DISCRIMINATOR);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_state_packing() {
+        let check = HelloWorldState {
+            version: STATE_VERSION,
+            account_key: Pubkey::new_from_array([1; 32]),
+            created_at: 1_631_000_000,
+            updated_at: 1_631_000_042,
+            edit_count: 3,
+            chunk_count: 2,
+            finalized: true,
+            created_slot: 42,
+            updated_slot: 43,
+            history_index: 1,
+            history: [Hash::new_from_array([2; 32]); HISTORY_LEN],
+            delegate: COption::Some(Pubkey::new_from_array([3; 32])),
+            delegate_expiry_slot: 100,
+            authority: COption::Some(Pubkey::new_from_array([4; 32])),
+            parent: COption::Some(Pubkey::new_from_array([5; 32])),
+            reply_count: 6,
+            reactions: [7, 0, 1, 0, 0, 2],
+            ttl: 3_600,
+            creation_id: 8,
+            message: "hello world!".to_string(),
+        };
+        let mut packed = vec![0; HelloWorldState::LEN];
+        HelloWorldState::pack(check.clone(), &mut packed).unwrap();
+        assert_eq!(packed[0], STATE_VERSION);
+        assert_eq!(packed[PARENT_OFFSET..PARENT_OFFSET + 4], [1, 0, 0, 0]);
+        assert_eq!(packed[PARENT_OFFSET + 4..PARENT_OFFSET + 36], [5; 32]);
+        assert_eq!(HelloWorldState::unpack(&packed).unwrap(), check);
+
+        // a zeroed account is not initialized yet
+        let zeroed = vec![0; HelloWorldState::LEN];
+        assert!(!HelloWorldState::unpack_unchecked(&zeroed).unwrap().is_initialized());
+
+        packed[0] = STATE_VERSION + 1;
+        assert_eq!(
+            HelloWorldState::unpack(&packed).unwrap_err(),
+            HelloWorldError::UnsupportedStateVersion.into()
+        );
+
+        HelloWorldState::close(&mut packed);
+        assert_eq!(
+            HelloWorldState::unpack(&packed).unwrap_err(),
+            HelloWorldError::AccountClosed.into()
+        );
+    }
+
+    #[test]
+    fn test_state_expiry() {
+        let mut state = HelloWorldState {
+            updated_at: 1_000,
+            ..HelloWorldState::default()
+        };
+        assert_eq!(state.expires_at(), None);
+        assert!(!state.is_expired(i64::MAX));
+
+        state.ttl = 60;
+        assert_eq!(state.expires_at(), Some(1_060));
+        assert!(!state.is_expired(1_059));
+        assert!(state.is_expired(1_060));
+
+        state.ttl = u64::MAX;
+        assert_eq!(state.expires_at(), Some(i64::MAX));
+    }
+
+    #[test]
+    fn test_state_check_poster() {
+        let author = Pubkey::new_from_array([1; 32]);
+        let delegate = Pubkey::new_from_array([2; 32]);
+        let mut state = HelloWorldState::new(author, 1, &Clock::default());
+        assert!(state.check_poster(&author, 0).is_ok());
+        assert_eq!(
+            state.check_poster(&delegate, 0).unwrap_err(),
+            HelloWorldError::Unauthorized.into()
+        );
+
+        state.delegate = COption::Some(delegate);
+        state.delegate_expiry_slot = 100;
+        assert!(state.check_poster(&delegate, 99).is_ok());
+        assert_eq!(
+            state.check_poster(&delegate, 100).unwrap_err(),
+            HelloWorldError::DelegateExpired.into()
+        );
+        assert_eq!(
+            state.check_poster(&Pubkey::new_from_array([3; 32]), 0).unwrap_err(),
+            HelloWorldError::Unauthorized.into()
+        );
+        // the author is never limited by the grant of its delegate
+        assert!(state.check_poster(&author, 100).is_ok());
+
+        // once an authority is set, the author is a regular key
+        let authority = Pubkey::new_from_array([4; 32]);
+        state.authority = COption::Some(authority);
+        assert_eq!(state.authority_key(), authority);
+        assert!(state.check_poster(&authority, 100).is_ok());
+        assert_eq!(
+            state.check_poster(&author, 0).unwrap_err(),
+            HelloWorldError::Unauthorized.into()
+        );
+    }
+
+    #[test]
+    fn test_state_set_parent() {
+        let parent = Pubkey::new_from_array([1; 32]);
+        let mut state = HelloWorldState::default();
+        assert!(state.set_parent(&parent).unwrap());
+        assert_eq!(state.parent, COption::Some(parent));
+        // posting again in the same thread does not count as another reply
+        assert!(!state.set_parent(&parent).unwrap());
+        assert_eq!(
+            state.set_parent(&Pubkey::new_from_array([2; 32])).unwrap_err(),
+            HelloWorldError::InvalidParent.into()
+        );
+
+        let mut state = HelloWorldState {
+            reply_count: 1,
+            ..HelloWorldState::default()
+        };
+        assert_eq!(state.set_parent(&parent).unwrap_err(), HelloWorldError::InvalidParent.into());
+        state.reply_count = u64::MAX;
+        assert_eq!(state.add_reply().unwrap_err(), HelloWorldError::Overflow.into());
+    }
+
+    #[test]
+    fn test_multisig_packing() {
+        let mut check = Multisig {
+            m: 2,
+            n: 3,
+            is_initialized: true,
+            ..Multisig::default()
+        };
+        for (i, signer) in check.signers.iter_mut().take(3).enumerate() {
+            *signer = Pubkey::new_from_array([i as u8 + 1; 32]);
+        }
+        let mut packed = vec![0; Multisig::LEN];
+        Multisig::pack(check, &mut packed).unwrap();
+        assert_eq!(&packed[..4], &[MULTISIG_DISCRIMINATOR, 2, 3, 1]);
+        assert!(Multisig::is_multisig(&packed));
+        assert_eq!(Multisig::unpack(&packed).unwrap(), check);
+
+        // 大小相同但没有标记的账号不是多签账号
+        packed[0] = 0;
+        assert!(!Multisig::is_multisig(&packed));
+        assert_eq!(Multisig::unpack(&packed).unwrap_err(), ProgramError::InvalidAccountData);
+        assert!(!Multisig::is_multisig(&packed[1..]));
+    }
+
+    #[test]
+    fn test_direct_message_packing() {
+        let check = DirectMessage {
+            is_initialized: true,
+            sender: Pubkey::new_from_array([1; 32]),
+            recipient: Pubkey::new_from_array([2; 32]),
+            index: 3,
+            created_at: 42,
+            payload: vec![7; 40],
+        };
+        let mut packed = vec![0xff; DirectMessage::LEN];
+        DirectMessage::pack(check.clone(), &mut packed).unwrap();
+        assert_eq!(packed[81..83], 40u16.to_le_bytes());
+        assert!(packed[83 + 40..].iter().all(|byte| *byte == 0));
+        assert_eq!(DirectMessage::unpack(&packed).unwrap(), check);
+
+        packed[81..83].copy_from_slice(&(MAX_DIRECT_MESSAGE_LEN as u16 + 1).to_le_bytes());
+        assert_eq!(
+            DirectMessage::unpack(&packed).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+        assert_eq!(
+            DirectMessage::check_payload(&[0; MAX_DIRECT_MESSAGE_LEN + 1]).unwrap_err(),
+            HelloWorldError::MessageTooLong.into()
+        );
+
+        let check = Inbox {
+            is_initialized: true,
+            owner: Pubkey::new_from_array([2; 32]),
+            message_count: 4,
+        };
+        let mut packed = vec![0; Inbox::LEN];
+        Inbox::pack(check, &mut packed).unwrap();
+        assert_eq!(Inbox::unpack(&packed).unwrap(), check);
+    }
+
+    #[test]
+    fn test_commitment() {
+        let salt = [7; SALT_LEN];
+        let check = Commitment {
+            is_initialized: true,
+            message: Pubkey::new_from_array([1; 32]),
+            committer: Pubkey::new_from_array([2; 32]),
+            hash: commitment_hash("sealed bid", &salt),
+            reveal_after: Timepoint::Slot(10),
+            reveal_deadline: Timepoint::UnixTimestamp(-1),
+        };
+        let mut packed = vec![0; Commitment::LEN];
+        Commitment::pack(check, &mut packed).unwrap();
+        assert_eq!(packed[97..106], [0, 10, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed[106..115], [1, 255, 255, 255, 255, 255, 255, 255, 255]);
+        assert_eq!(Commitment::unpack(&packed).unwrap(), check);
+        packed[97] = 2;
+        assert_eq!(Commitment::unpack(&packed).unwrap_err(), ProgramError::InvalidAccountData);
+
+        assert_ne!(check.hash, commitment_hash("sealed bid!", &salt));
+        assert_ne!(check.hash, commitment_hash("sealed bid", &[8; SALT_LEN]));
+
+        // 揭示窗口是 [reveal_after, reveal_deadline)
+        let check = Commitment {
+            reveal_deadline: Timepoint::Slot(20),
+            ..check
+        };
+        for (slot, result) in [
+            (9, Err(HelloWorldError::RevealTooEarly.into())),
+            (10, Ok(())),
+            (19, Ok(())),
+            (20, Err(HelloWorldError::RevealTooLate.into())),
+        ] {
+            let clock = Clock {
+                slot,
+                ..Clock::default()
+            };
+            assert_eq!(check.check_reveal_window(&clock), result);
+        }
+        assert_eq!(Timepoint::Slot(1).is_before(&Timepoint::Slot(2)), Some(true));
+        assert_eq!(Timepoint::Slot(2).is_before(&Timepoint::Slot(2)), Some(false));
+        assert_eq!(Timepoint::Slot(1).is_before(&Timepoint::UnixTimestamp(2)), None);
+    }
+
+    #[test]
+    fn test_poll() {
+        let options = vec!["yes".to_string(), "no".to_string()];
+        let mut check = Poll {
+            is_initialized: true,
+            creator: Pubkey::new_from_array([1; 32]),
+            question: "ship it?".to_string(),
+            options: options
+                .iter()
+                .map(|label| PollOption {
+                    label: label.clone(),
+                    votes: 0,
+                })
+                .collect(),
+            opens_at: Timepoint::Slot(10),
+            closes_at: Timepoint::Slot(20),
+            vote_mint: COption::Some(Pubkey::new_from_array([2; 32])),
+            voter_count: 0,
+        };
+        assert_eq!(check.add_vote(1, 5), Ok(5));
+        assert_eq!(check.add_vote(1, 2), Ok(7));
+        assert_eq!(check.add_vote(2, 1), Err(HelloWorldError::InvalidPollOption.into()));
+        assert_eq!(check.voter_count, 2);
+        assert_eq!(check.total_votes(), 7);
+        check.options[0].votes = u64::MAX;
+        assert_eq!(check.add_vote(0, 1), Err(HelloWorldError::Overflow.into()));
+        assert_eq!(check.total_votes(), u64::MAX as u128 + 7);
+
+        let mut packed = vec![0; Poll::LEN];
+        Poll::pack(check.clone(), &mut packed).unwrap();
+        assert_eq!(Poll::unpack(&packed).unwrap(), check);
+        // 选项数量超过上限的账号数据无效
+        packed[1 + 32 + 1 + MAX_POLL_QUESTION_LEN] = MAX_POLL_OPTIONS as u8 + 1;
+        assert_eq!(Poll::unpack(&packed).unwrap_err(), ProgramError::InvalidAccountData);
+
+        assert_eq!(Poll::check_content("ship it?", &options), Ok(()));
+        assert_eq!(
+            Poll::check_content(&"?".repeat(MAX_POLL_QUESTION_LEN + 1), &options),
+            Err(HelloWorldError::MessageTooLong.into())
+        );
+        for options in [
+            vec!["yes".to_string()],
+            vec!["option".to_string(); MAX_POLL_OPTIONS + 1],
+            vec!["yes".to_string(), String::new()],
+            vec!["yes".to_string(), "n".repeat(MAX_POLL_OPTION_LEN + 1)],
+        ] {
+            assert_eq!(
+                Poll::check_content("ship it?", &options),
+                Err(HelloWorldError::InvalidPollOption.into())
+            );
+        }
+
+        // 投票窗口是 [opens_at, closes_at)
+        for (slot, result) in [
+            (9, Err(HelloWorldError::PollNotOpen.into())),
+            (10, Ok(())),
+            (19, Ok(())),
+            (20, Err(HelloWorldError::PollClosed.into())),
+        ] {
+            let clock = Clock {
+                slot,
+                ..Clock::default()
+            };
+            assert_eq!(check.check_open(&clock), result);
+        }
+    }
+
+    #[test]
+    fn test_vote_receipt_packing() {
+        let check = VoteReceipt {
+            is_initialized: true,
+            poll: Pubkey::new_from_array([1; 32]),
+            voter: Pubkey::new_from_array([2; 32]),
+            option: 3,
+            weight: 4,
+            slot: 5,
+        };
+        let mut packed = vec![0; VoteReceipt::LEN];
+        VoteReceipt::pack(check, &mut packed).unwrap();
+        let mut expect = vec![1];
+        expect.extend_from_slice(&[1; 32]);
+        expect.extend_from_slice(&[2; 32]);
+        expect.push(3);
+        expect.extend_from_slice(&4u64.to_le_bytes());
+        expect.extend_from_slice(&5u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        assert_eq!(VoteReceipt::unpack(&packed).unwrap(), check);
+    }
+
+    #[test]
+    fn test_bounty_packing() {
+        let check = Bounty {
+            is_initialized: true,
+            question: Pubkey::new_from_array([1; 32]),
+            author: Pubkey::new_from_array([2; 32]),
+            amount: 3,
+            expires_at: Timepoint::UnixTimestamp(4),
+        };
+        let mut packed = vec![0; Bounty::LEN];
+        Bounty::pack(check, &mut packed).unwrap();
+        let mut expect = vec![1];
+        expect.extend_from_slice(&[1; 32]);
+        expect.extend_from_slice(&[2; 32]);
+        expect.extend_from_slice(&3u64.to_le_bytes());
+        expect.extend_from_slice(&[1, 4, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        assert_eq!(Bounty::unpack(&packed).unwrap(), check);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_signed_nonce_packing() {
+        let check = SignedNonce {
+            is_initialized: true,
+            author: Pubkey::new_from_array([1; 32]),
+            nonce: 7,
+            message: Pubkey::new_from_array([2; 32]),
+        };
+        let mut packed = vec![0; SignedNonce::LEN];
+        SignedNonce::pack(check, &mut packed).unwrap();
+        assert_eq!(packed[33..41], 7u64.to_le_bytes());
+        assert_eq!(SignedNonce::unpack(&packed).unwrap(), check);
+    }
+
+    #[test]
+    fn test_message_counter_packing() {
+        let mut check = MessageCounter::default();
+        assert_eq!(check.next_creation_id().unwrap(), 1);
+        assert_eq!(check.next_creation_id().unwrap(), 2);
+        check.is_initialized = true;
+        let mut packed = vec![0; MessageCounter::LEN];
+        MessageCounter::pack(check, &mut packed).unwrap();
+        assert_eq!(packed, [1, 2, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(MessageCounter::unpack(&packed).unwrap(), check);
+
+        check.count = u64::MAX;
+        assert_eq!(
+            check.next_creation_id().unwrap_err(),
+            HelloWorldError::Overflow.into()
+        );
+    }
+
+    #[test]
+    fn test_config_packing() {
+        let check = Config {
+            is_initialized: true,
+            admin: Pubkey::new_from_array([1; 32]),
+            fee_mint: Pubkey::new_from_array([2; 32]),
+            fee_amount: 42,
+            treasury: Pubkey::new_from_array([3; 32]),
+            paused: true,
+            reap_bounty_bps: 250,
+        };
+        let mut packed = vec![0; Config::LEN];
+        Config::pack(check, &mut packed).unwrap();
+        assert_eq!(packed[0], 1);
+        assert_eq!(packed[65..73], 42u64.to_le_bytes());
+        assert_eq!(packed[105], 1);
+        assert_eq!(packed[106..108], 250u16.to_le_bytes());
+        assert_eq!(Config::unpack(&packed).unwrap(), check);
+    }
+
+    #[test]
+    fn test_channel_packing() {
+        let check = Channel {
+            is_initialized: true,
+            creator: Pubkey::new_from_array([1; 32]),
+            topic: "rust".to_string(),
+            message_count: CHANNEL_PAGE_LEN as u64 + 1,
+        };
+        let mut packed = vec![0; Channel::LEN];
+        Channel::pack(check.clone(), &mut packed).unwrap();
+        assert_eq!(Channel::unpack(&packed).unwrap(), check);
+        assert_eq!(check.page_count(), 2);
+        assert_eq!(check.next_position().unwrap(), (1, 1));
+        assert_eq!(Channel::default().page_count(), 0);
+        assert_eq!(Channel::default().next_position().unwrap(), (0, 0));
+        assert_eq!(
+            Channel::check_topic(&"a".repeat(MAX_SEED_LEN + 1)).unwrap_err(),
+            ProgramError::InvalidSeeds
+        );
+
+        let check = ChannelPage {
+            is_initialized: true,
+            channel: Pubkey::new_from_array([1; 32]),
+            index: 1,
+            messages: vec![Pubkey::new_from_array([2; 32]), Pubkey::new_from_array([3; 32])],
+        };
+        let mut packed = vec![0; ChannelPage::LEN];
+        ChannelPage::pack(check.clone(), &mut packed).unwrap();
+        assert_eq!(ChannelPage::unpack(&packed).unwrap(), check);
+        packed[37] = CHANNEL_PAGE_LEN as u8 + 1;
+        assert_eq!(
+            ChannelPage::unpack(&packed).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+
+    #[test]
+    fn test_reaction_receipt_packing() {
+        let check = ReactionReceipt {
+            is_initialized: true,
+            message: Pubkey::new_from_array([1; 32]),
+            reactor: Pubkey::new_from_array([2; 32]),
+            reaction: Reaction::Party,
+            message_creation_id: 42,
+        };
+        let mut packed = vec![0; ReactionReceipt::LEN];
+        ReactionReceipt::pack(check, &mut packed).unwrap();
+        assert_eq!(packed[65], 4);
+        assert_eq!(ReactionReceipt::unpack(&packed).unwrap(), check);
+        packed[65] = REACTION_KINDS as u8;
+        assert_eq!(
+            ReactionReceipt::unpack(&packed).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+
+        let mut state = HelloWorldState::default();
+        state.add_reaction(Reaction::Like).unwrap();
+        state.add_reaction(Reaction::Eyes).unwrap();
+        assert_eq!(state.reactions, [1, 0, 0, 0, 0, 1]);
+        state.remove_reaction(Reaction::Like).unwrap();
+        assert_eq!(
+            state.remove_reaction(Reaction::Like).unwrap_err(),
+            HelloWorldError::Overflow.into()
+        );
+
+        // 同一个地址上重新创建的消息不计这个表态
+        state.creation_id = 42;
+        assert!(check.is_counted_by(&state));
+        state.creation_id = 43;
+        assert!(!check.is_counted_by(&state));
+    }
+
+    #[test]
+    fn test_chunk_packing() {
+        let check = MessageChunk {
+            is_initialized: true,
+            message_account: Pubkey::new_from_array([1; 32]),
+            index: 7,
+            data: "a".repeat(MAX_CHUNK_LEN),
+        };
+        let mut packed = vec![0; MessageChunk::LEN];
+        MessageChunk::pack(check.clone(), &mut packed).unwrap();
+        assert_eq!(MessageChunk::unpack(&packed).unwrap(), check);
+
+        // a shorter chunk clears the rest of the data
+        let check = MessageChunk {
+            data: "hello".to_string(),
+            ..check
+        };
+        MessageChunk::pack(check.clone(), &mut packed).unwrap();
+        assert_eq!(MessageChunk::unpack(&packed).unwrap(), check);
+        assert!(packed[1 + 32 + 4 + 2 + 5..].iter().all(|byte| *byte == 0));
+
+        assert_eq!(
+            MessageChunk::check_data(&"a".repeat(MAX_CHUNK_LEN + 1)).unwrap_err(),
+            HelloWorldError::MessageTooLong.into()
+        );
+
+        HelloWorldState::close(&mut packed);
+        assert_eq!(
+            MessageChunk::unpack(&packed).unwrap_err(),
+            HelloWorldError::AccountClosed.into()
+        );
+    }
+
+    #[test]
+    fn test_state_from_v1() {
+        let legacy = HelloWorldStateV1 {
+            account_key: Pubkey::new_from_array([1; 32]),
+            message: "hello world!".to_string(),
+        };
+        let mut packed = vec![0; HelloWorldStateV1::LEN];
+        HelloWorldStateV1::pack(legacy.clone(), &mut packed).unwrap();
+        let legacy = HelloWorldStateV1::unpack(&packed).unwrap();
+
+        let clock = Clock {
+            slot: 42,
+            unix_timestamp: 1_631_000_000,
+            ..Clock::default()
+        };
+        let state = HelloWorldState::from_v1(legacy, 9, &clock);
+        assert!(state.is_initialized());
+        assert_eq!(state.account_key, Pubkey::new_from_array([1; 32]));
+        assert_eq!(state.created_at, 1_631_000_000);
+        assert_eq!(state.updated_at, 1_631_000_000);
+        assert_eq!(state.created_slot, 42);
+        assert_eq!(state.updated_slot, 42);
+        assert_eq!(state.creation_id, 9);
+        assert_eq!(state.edit_count, 0);
+        assert_eq!(state.chunk_count, 0);
+        assert!(!state.finalized);
+        assert!(state.previous_hashes().is_empty());
+        assert_eq!(state.message, "hello world!");
+    }
+
+    #[test]
+    fn test_state_edit_history() {
+        let clock = Clock {
+            slot: 42,
+            unix_timestamp: 1_631_000_000,
+            ..Clock::default()
+        };
+        let mut state = HelloWorldState::new(Pubkey::new_from_array([1; 32]), 1, &clock);
+        state.message = "hello 0".to_string();
+
+        let later = Clock {
+            slot: 50,
+            unix_timestamp: 1_631_000_004,
+            ..Clock::default()
+        };
+        for i in 1..=HISTORY_LEN + 2 {
+            state.set_message(format!("hello {}", i), &later).unwrap();
+        }
+        assert_eq!(state.edit_count, HISTORY_LEN as u64 + 2);
+        assert_eq!(state.created_slot, 42);
+        assert_eq!(state.updated_slot, 50);
+        assert_eq!(state.updated_at, 1_631_000_004);
+
+        // only the last HISTORY_LEN replaced messages are kept
+        let expected: Vec<Hash> = (2..=HISTORY_LEN + 1)
+            .rev()
+            .map(|i| hash(format!("hello {}", i).as_bytes()))
+            .collect();
+        assert_eq!(state.previous_hashes(), expected);
+
+        let mut packed = vec![0; HelloWorldState::LEN];
+        HelloWorldState::pack(state.clone(), &mut packed).unwrap();
+        assert_eq!(HelloWorldState::unpack(&packed).unwrap(), state);
+
+        assert_eq!(
+            state.set_message("a".repeat(MAX_MESSAGE_LEN + 1), &later).unwrap_err(),
+            HelloWorldError::MessageTooLong.into()
+        );
+    }
+}
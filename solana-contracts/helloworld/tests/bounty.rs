@@ -0,0 +1,291 @@
+mod common;
+
+use {
+    common::{advance_clock, custom_error, get_balance, get_clock, process},
+    helloworld::{
+        error::HelloWorldError,
+        find_bounty_address, find_message_address,
+        instruction::{award, cancel, hello, initialize, post_bounty, reply},
+        processor::Processor,
+        state::{Bounty, Timepoint},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        instruction::InstructionError,
+        program_error::ProgramError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::Signer,
+        signer::keypair::Keypair,
+        system_instruction,
+        transaction::TransactionError,
+    },
+};
+
+const SEED: &str = "question";
+const BOUNTY: u64 = 250_000_000;
+const TIMEOUT: i64 = 3_600;
+
+/// A question of the asker and the reply of the replier, both wallets funded by the payer
+/// which also pays the fees, so their balances only move by the bounty and the vault rent
+struct Setup {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    asker: Keypair,
+    replier: Keypair,
+    question: Pubkey,
+    reply: Pubkey,
+    bounty: Pubkey,
+}
+
+async fn setup() -> Setup {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let (asker, replier) = (Keypair::new(), Keypair::new());
+    let (question, _) = find_message_address(&program_id, &asker.pubkey(), SEED);
+    let (reply_message, _) = find_message_address(&program_id, &replier.pubkey(), SEED);
+    process(
+        &mut context,
+        &[
+            system_instruction::transfer(&payer, &asker.pubkey(), 1_000_000_000),
+            system_instruction::transfer(&payer, &replier.pubkey(), 1_000_000_000),
+            initialize(&program_id, &asker.pubkey(), &question, SEED).unwrap(),
+            hello(&program_id, &asker.pubkey(), &question, &[], "How do I close an account?").unwrap(),
+            initialize(&program_id, &replier.pubkey(), &reply_message, SEED).unwrap(),
+            reply(
+                &program_id,
+                &replier.pubkey(),
+                &reply_message,
+                &question,
+                &[],
+                None,
+                "Move its lamports out and zero its data",
+            )
+            .unwrap(),
+        ],
+        &[&asker, &replier],
+    )
+    .await
+    .unwrap();
+    Setup {
+        context,
+        program_id,
+        asker,
+        replier,
+        question,
+        reply: reply_message,
+        bounty: find_bounty_address(&program_id, &question).0,
+    }
+}
+
+async fn now(context: &mut ProgramTestContext) -> i64 {
+    get_clock(context).await.unix_timestamp
+}
+
+fn vault_rent() -> u64 {
+    Rent::default().minimum_balance(Bounty::LEN)
+}
+
+/// Posts a bounty of `BOUNTY` lamports on the question, expiring in `TIMEOUT` seconds
+async fn post(setup: &mut Setup) {
+    let expires_at = Timepoint::UnixTimestamp(now(&mut setup.context).await + TIMEOUT);
+    process(
+        &mut setup.context,
+        &[post_bounty(
+            &setup.program_id,
+            &setup.asker.pubkey(),
+            &setup.question,
+            BOUNTY,
+            expires_at,
+        )
+        .unwrap()],
+        &[&setup.asker],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn success_award() {
+    let mut setup = setup().await;
+    let (asker, replier) = (setup.asker.pubkey(), setup.replier.pubkey());
+    let asker_lamports = get_balance(&mut setup.context, &asker).await;
+    let replier_lamports = get_balance(&mut setup.context, &replier).await;
+
+    // 作者支付赏金和金库的租金
+    post(&mut setup).await;
+    assert_eq!(get_balance(&mut setup.context, &asker).await, asker_lamports - BOUNTY - vault_rent());
+    assert_eq!(get_balance(&mut setup.context, &setup.bounty).await, BOUNTY + vault_rent());
+    let account = setup.context.banks_client.get_account(setup.bounty).await.unwrap().unwrap();
+    assert_eq!(account.owner, setup.program_id);
+    let bounty = Bounty::unpack(&account.data).unwrap();
+    assert_eq!((bounty.question, bounty.author, bounty.amount), (setup.question, asker, BOUNTY));
+
+    // 赏金给回复的作者，租金退给提问的作者
+    process(
+        &mut setup.context,
+        &[award(&setup.program_id, &asker, &setup.question, &setup.reply, &replier).unwrap()],
+        &[&setup.asker],
+    )
+    .await
+    .unwrap();
+    assert_eq!(setup.context.banks_client.get_account(setup.bounty).await.unwrap(), None);
+    assert_eq!(get_balance(&mut setup.context, &replier).await, replier_lamports + BOUNTY);
+    assert_eq!(get_balance(&mut setup.context, &asker).await, asker_lamports - BOUNTY);
+
+    // 赏金只能发一次
+    assert_eq!(
+        process(
+            &mut setup.context,
+            &[award(&setup.program_id, &asker, &setup.question, &setup.reply, &replier).unwrap()],
+            &[&setup.asker],
+        )
+        .await
+        .unwrap_err(),
+        custom_error(HelloWorldError::IncorrectAccountOwner)
+    );
+    assert_eq!(get_balance(&mut setup.context, &replier).await, replier_lamports + BOUNTY);
+}
+
+#[tokio::test]
+async fn success_cancel() {
+    let mut setup = setup().await;
+    let asker = setup.asker.pubkey();
+    // 有人提前往金库地址转了钱，作者少付这部分租金，取消时一并退给作者
+    let prefund = Rent::default().minimum_balance(0);
+    let payer = setup.context.payer.pubkey();
+    process(
+        &mut setup.context,
+        &[system_instruction::transfer(&payer, &setup.bounty, prefund)],
+        &[],
+    )
+    .await
+    .unwrap();
+    let asker_lamports = get_balance(&mut setup.context, &asker).await;
+    post(&mut setup).await;
+    assert_eq!(
+        get_balance(&mut setup.context, &asker).await,
+        asker_lamports - BOUNTY - (vault_rent() - prefund)
+    );
+    assert_eq!(get_balance(&mut setup.context, &setup.bounty).await, BOUNTY + vault_rent());
+
+    let cancel_instruction = cancel(&setup.program_id, &asker, &setup.question).unwrap();
+    assert_eq!(
+        process(&mut setup.context, std::slice::from_ref(&cancel_instruction), &[&setup.asker])
+            .await
+            .unwrap_err(),
+        custom_error(HelloWorldError::BountyNotExpired)
+    );
+    assert_eq!(get_balance(&mut setup.context, &setup.bounty).await, BOUNTY + vault_rent());
+
+    advance_clock(&mut setup.context, TIMEOUT).await;
+    process(&mut setup.context, &[cancel_instruction], &[&setup.asker]).await.unwrap();
+    assert_eq!(setup.context.banks_client.get_account(setup.bounty).await.unwrap(), None);
+    assert_eq!(get_balance(&mut setup.context, &asker).await, asker_lamports + prefund);
+
+    // 取消以后不能再发赏金
+    let replier = setup.replier.pubkey();
+    assert_eq!(
+        process(
+            &mut setup.context,
+            &[award(&setup.program_id, &asker, &setup.question, &setup.reply, &replier).unwrap()],
+            &[&setup.asker],
+        )
+        .await
+        .unwrap_err(),
+        custom_error(HelloWorldError::IncorrectAccountOwner)
+    );
+}
+
+#[tokio::test]
+async fn fail_bounty() {
+    let mut setup = setup().await;
+    let (asker, replier) = (setup.asker.pubkey(), setup.replier.pubkey());
+    let program_id = setup.program_id;
+
+    // 只有问题的作者能给它悬赏，金额不能为零
+    let expires_at = Timepoint::UnixTimestamp(now(&mut setup.context).await + TIMEOUT);
+    assert_eq!(
+        post_bounty(&program_id, &asker, &setup.question, 0, expires_at).unwrap_err(),
+        ProgramError::InvalidArgument
+    );
+    assert_eq!(
+        process(
+            &mut setup.context,
+            &[post_bounty(&program_id, &replier, &setup.question, BOUNTY, expires_at).unwrap()],
+            &[&setup.replier],
+        )
+        .await
+        .unwrap_err(),
+        custom_error(HelloWorldError::Unauthorized)
+    );
+    post(&mut setup).await;
+    assert_eq!(
+        process(
+            &mut setup.context,
+            &[post_bounty(&program_id, &asker, &setup.question, BOUNTY, expires_at).unwrap()],
+            &[&setup.asker],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+
+    // 一条不是回复的消息，和提问者自己的回复
+    let (other, own_reply) = (Keypair::new(), find_message_address(&program_id, &asker, "own").0);
+    let (unrelated, _) = find_message_address(&program_id, &other.pubkey(), SEED);
+    let payer = setup.context.payer.pubkey();
+    process(
+        &mut setup.context,
+        &[
+            system_instruction::transfer(&payer, &other.pubkey(), 1_000_000_000),
+            initialize(&program_id, &other.pubkey(), &unrelated, SEED).unwrap(),
+            initialize(&program_id, &asker, &own_reply, "own").unwrap(),
+            reply(&program_id, &asker, &own_reply, &setup.question, &[], None, "never mind").unwrap(),
+        ],
+        &[&other, &setup.asker],
+    )
+    .await
+    .unwrap();
+
+    let asker_lamports = get_balance(&mut setup.context, &asker).await;
+    let replier_lamports = get_balance(&mut setup.context, &replier).await;
+    let other_lamports = get_balance(&mut setup.context, &other.pubkey()).await;
+    for (signer, reply_message, recipient, error) in [
+        (&setup.replier, &setup.reply, &replier, custom_error(HelloWorldError::Unauthorized)),
+        (&setup.asker, &unrelated, &other.pubkey(), custom_error(HelloWorldError::InvalidReply)),
+        (&setup.asker, &own_reply, &asker, custom_error(HelloWorldError::InvalidReply)),
+        (&setup.asker, &setup.reply, &other.pubkey(), custom_error(HelloWorldError::InvalidReply)),
+    ] {
+        assert_eq!(
+            process(
+                &mut setup.context,
+                &[award(&program_id, &signer.pubkey(), &setup.question, reply_message, recipient).unwrap()],
+                &[signer],
+            )
+            .await
+            .unwrap_err(),
+            error
+        );
+    }
+    advance_clock(&mut setup.context, TIMEOUT).await;
+    assert_eq!(
+        process(
+            &mut setup.context,
+            &[cancel(&program_id, &replier, &setup.question).unwrap()],
+            &[&setup.replier],
+        )
+        .await
+        .unwrap_err(),
+        custom_error(HelloWorldError::Unauthorized)
+    );
+
+    // 失败的指令不动任何人的lamports
+    assert_eq!(get_balance(&mut setup.context, &setup.bounty).await, BOUNTY + vault_rent());
+    assert_eq!(get_balance(&mut setup.context, &asker).await, asker_lamports);
+    assert_eq!(get_balance(&mut setup.context, &replier).await, replier_lamports);
+    assert_eq!(get_balance(&mut setup.context, &other.pubkey()).await, other_lamports);
+}
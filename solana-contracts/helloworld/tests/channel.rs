@@ -0,0 +1,186 @@
+mod common;
+
+use {
+    common::process,
+    helloworld::{
+        error::HelloWorldError,
+        find_channel_address, find_channel_page_address, find_message_address,
+        instruction::{create_channel, initialize, post_to_channel},
+        processor::Processor,
+        state::{Channel, ChannelPage, CHANNEL_PAGE_LEN},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        system_instruction,
+        transaction::TransactionError,
+    },
+};
+
+const TOPIC: &str = "rust";
+const SEED: &str = "hello";
+
+/// Creates the channel of `TOPIC` and a message of the payer
+async fn setup(context: &mut ProgramTestContext, program_id: &Pubkey) -> (Pubkey, Pubkey) {
+    let payer = context.payer.pubkey();
+    let (channel, _) = find_channel_address(program_id, TOPIC);
+    let (message, _) = find_message_address(program_id, &payer, SEED);
+    process(
+        context,
+        &[
+            create_channel(program_id, &payer, &channel, TOPIC).unwrap(),
+            initialize(program_id, &payer, &message, SEED).unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    (channel, message)
+}
+
+async fn get_channel(context: &mut ProgramTestContext, channel: &Pubkey) -> Channel {
+    let account = context.banks_client.get_account(*channel).await.unwrap().unwrap();
+    Channel::unpack(&account.data).unwrap()
+}
+
+async fn get_page(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    channel: &Pubkey,
+    index: u32,
+) -> ChannelPage {
+    let page = find_channel_page_address(program_id, channel, index).0;
+    let account = context.banks_client.get_account(page).await.unwrap().unwrap();
+    ChannelPage::unpack(&account.data).unwrap()
+}
+
+/// Posts the message to the current page of the channel
+async fn post(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    channel: &Pubkey,
+    message: &Pubkey,
+) -> Result<(), TransactionError> {
+    let author = context.payer.pubkey();
+    let (index, _) = get_channel(context, channel).await.next_position().unwrap();
+    let page = find_channel_page_address(program_id, channel, index).0;
+    process(
+        context,
+        &[post_to_channel(program_id, &author, message, channel, &page).unwrap()],
+        &[],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn success_post_to_channel() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let (channel, message) = setup(&mut context, &program_id).await;
+
+    let state = get_channel(&mut context, &channel).await;
+    assert_eq!(state.creator, payer);
+    assert_eq!(state.topic, TOPIC);
+    assert_eq!(state.message_count, 0);
+
+    // the first post of a page creates it
+    let (other_message, _) = find_message_address(&program_id, &payer, "other");
+    process(&mut context, &[initialize(&program_id, &payer, &other_message, "other").unwrap()], &[])
+        .await
+        .unwrap();
+    for _ in 0..CHANNEL_PAGE_LEN {
+        post(&mut context, &program_id, &channel, &message).await.unwrap();
+    }
+    post(&mut context, &program_id, &channel, &other_message).await.unwrap();
+
+    let state = get_channel(&mut context, &channel).await;
+    assert_eq!(state.message_count, CHANNEL_PAGE_LEN as u64 + 1);
+    assert_eq!(state.page_count(), 2);
+    let first_page = get_page(&mut context, &program_id, &channel, 0).await;
+    assert_eq!(first_page.channel, channel);
+    assert_eq!(first_page.messages, vec![message; CHANNEL_PAGE_LEN]);
+    let second_page = get_page(&mut context, &program_id, &channel, 1).await;
+    assert_eq!(second_page.index, 1);
+    assert_eq!(second_page.messages, vec![other_message]);
+}
+
+#[tokio::test]
+async fn fail_channel_accounts() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let (channel, message) = setup(&mut context, &program_id).await;
+
+    // a topic has a single channel, at the address derived from it
+    assert_eq!(
+        process(&mut context, &[create_channel(&program_id, &payer, &channel, TOPIC).unwrap()], &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+    assert_eq!(
+        process(&mut context, &[create_channel(&program_id, &payer, &channel, "go").unwrap()], &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+
+    // the page must be the current one of the channel
+    let next_page = find_channel_page_address(&program_id, &channel, 1).0;
+    assert_eq!(
+        process(
+            &mut context,
+            &[post_to_channel(&program_id, &payer, &message, &channel, &next_page).unwrap()],
+            &[],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::InvalidChannelAccount as u32)
+        )
+    );
+
+    // only the author posts a message
+    let other = Keypair::new();
+    let page = find_channel_page_address(&program_id, &channel, 0).0;
+    assert_eq!(
+        process(
+            &mut context,
+            &[
+                system_instruction::transfer(&payer, &other.pubkey(), 1_000_000_000),
+                post_to_channel(&program_id, &other.pubkey(), &message, &channel, &page).unwrap(),
+            ],
+            &[&other],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(HelloWorldError::Unauthorized as u32)
+        )
+    );
+
+    // a message account is not a channel
+    assert_eq!(
+        process(
+            &mut context,
+            &[post_to_channel(&program_id, &payer, &message, &message, &page).unwrap()],
+            &[],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::InvalidChannelAccount as u32)
+        )
+    );
+    assert_eq!(get_channel(&mut context, &channel).await.message_count, 0);
+}
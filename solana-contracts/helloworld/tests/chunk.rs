@@ -0,0 +1,163 @@
+use {
+    helloworld::{
+        error::HelloWorldError,
+        find_chunk_address, find_message_address,
+        instruction::{append_chunk, erase, finalize, hello, initialize},
+        processor::Processor,
+        state::{HelloWorldState, MessageChunk},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::Signer,
+        transaction::{Transaction, TransactionError},
+    },
+};
+
+const SEED: &str = "hello";
+
+/// Writes a message made of the head and two chunks, returns the message and chunk accounts
+async fn setup_long_message(context: &mut ProgramTestContext, program_id: &Pubkey) -> (Pubkey, Vec<Pubkey>) {
+    let author = context.payer.pubkey();
+    let (message, _) = find_message_address(program_id, &author, SEED);
+    let chunks: Vec<Pubkey> = (0..2)
+        .map(|index| find_chunk_address(program_id, &message, index).0)
+        .collect();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            initialize(program_id, &author, &message, SEED).unwrap(),
+            hello(program_id, &author, &message, &[], "hello ").unwrap(),
+            append_chunk(program_id, &author, &message, &chunks[0], "long ").unwrap(),
+            append_chunk(program_id, &author, &message, &chunks[1], "world!").unwrap(),
+        ],
+        Some(&author),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+    (message, chunks)
+}
+
+#[tokio::test]
+async fn success_append_and_finalize() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let (message, chunks) = setup_long_message(&mut context, &program_id).await;
+    let author = context.payer.pubkey();
+
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    let state = HelloWorldState::unpack(&account.data).unwrap();
+    assert_eq!(state.message, "hello ");
+    assert_eq!(state.chunk_count, 2);
+    assert_eq!(state.edit_count, 3);
+    assert!(!state.finalized);
+    for (index, (chunk, data)) in chunks.iter().zip(["long ", "world!"]).enumerate() {
+        let account = context.banks_client.get_account(*chunk).await.unwrap().unwrap();
+        assert_eq!(account.owner, program_id);
+        let chunk = MessageChunk::unpack(&account.data).unwrap();
+        assert_eq!(chunk.message_account, message);
+        assert_eq!(chunk.index, index as u32);
+        assert_eq!(chunk.data, data);
+    }
+
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize(&program_id, &author, &message, &[]).unwrap()],
+        Some(&author),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    assert!(HelloWorldState::unpack(&account.data).unwrap().finalized);
+
+    // a finalized message can neither be updated nor extended
+    let chunk = find_chunk_address(&program_id, &message, 2).0;
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    for instruction in [
+        hello(&program_id, &author, &message, &[], "bye").unwrap(),
+        append_chunk(&program_id, &author, &message, &chunk, "more").unwrap(),
+        finalize(&program_id, &author, &message, &[]).unwrap(),
+    ] {
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&author),
+            &[&context.payer],
+            blockhash,
+        );
+        let error = context.banks_client.process_transaction(tx).await.unwrap_err().unwrap();
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(HelloWorldError::MessageFinalized as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn fail_append_chunk_out_of_order() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let (message, chunks) = setup_long_message(&mut context, &program_id).await;
+    let author = context.payer.pubkey();
+
+    // the next chunk must be at index 2
+    let tx = Transaction::new_signed_with_payer(
+        &[append_chunk(&program_id, &author, &message, &chunks[1], "again").unwrap()],
+        Some(&author),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let error = context.banks_client.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::InvalidChunkAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn success_erase_with_chunks() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let (message, chunks) = setup_long_message(&mut context, &program_id).await;
+    let author = context.payer.pubkey();
+    let mut lamports = context.banks_client.get_balance(message).await.unwrap();
+    for chunk in chunks.iter() {
+        lamports += context.banks_client.get_balance(*chunk).await.unwrap();
+    }
+    let destination = Pubkey::new_unique();
+
+    // every chunk must be passed, in order
+    for chunk_accounts in [vec![&chunks[0]], vec![&chunks[1], &chunks[0]]] {
+        let tx = Transaction::new_signed_with_payer(
+            &[erase(&program_id, &author, &message, &destination, &chunk_accounts, &[]).unwrap()],
+            Some(&author),
+            &[&context.payer],
+            context.last_blockhash,
+        );
+        assert!(context.banks_client.process_transaction(tx).await.is_err());
+    }
+
+    let tx = Transaction::new_signed_with_payer(
+        &[erase(&program_id, &author, &message, &destination, &[&chunks[0], &chunks[1]], &[]).unwrap()],
+        Some(&author),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    assert_eq!(context.banks_client.get_balance(destination).await.unwrap(), lamports);
+    assert!(context.banks_client.get_account(message).await.unwrap().is_none());
+    for chunk in chunks.iter() {
+        assert!(context.banks_client.get_account(*chunk).await.unwrap().is_none());
+    }
+}
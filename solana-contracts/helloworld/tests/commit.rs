@@ -0,0 +1,256 @@
+mod common;
+
+use {
+    common::{advance_clock, custom_error, get_balance, get_clock, process},
+    helloworld::{
+        error::HelloWorldError,
+        find_commitment_address, find_message_address,
+        instruction::{close_commitment, commit, initialize, reveal, set_authority},
+        processor::Processor,
+        state::{commitment_hash, Commitment, HelloWorldState, Timepoint, SALT_LEN},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::Signer,
+        signer::keypair::Keypair,
+        system_instruction,
+        transaction::TransactionError,
+    },
+};
+
+const SEED: &str = "bid";
+const BID: &str = "I bid 42";
+const SALT: [u8; SALT_LEN] = [7; SALT_LEN];
+
+/// Creates a message of the payer and commits `BID` to it for the given reveal window
+async fn setup(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    reveal_after: Timepoint,
+    reveal_deadline: Timepoint,
+) -> Pubkey {
+    let author = context.payer.pubkey();
+    let (message, _) = find_message_address(program_id, &author, SEED);
+    let hash = commitment_hash(BID, &SALT).to_bytes();
+    process(
+        context,
+        &[
+            initialize(program_id, &author, &message, SEED).unwrap(),
+            commit(program_id, &author, &message, &hash, reveal_after, reveal_deadline, None).unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    message
+}
+
+#[tokio::test]
+async fn success_commit_and_reveal_after_slot() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let author = context.payer.pubkey();
+    let slot = get_clock(&mut context).await.slot;
+    let message = setup(&mut context, &program_id, Timepoint::Slot(slot + 10), Timepoint::Slot(slot + 100)).await;
+
+    let (commitment, _) = find_commitment_address(&program_id, &message);
+    let account = context.banks_client.get_account(commitment).await.unwrap().unwrap();
+    let state = Commitment::unpack(&account.data).unwrap();
+    assert_eq!(state.committer, author);
+    assert_eq!(state.hash, commitment_hash(BID, &SALT));
+    // only the hash is public until the reveal
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    assert_eq!(HelloWorldState::unpack(&account.data).unwrap().message, "");
+
+    // a pending commitment is not replaced
+    let hash = commitment_hash("I bid 43", &SALT).to_bytes();
+    assert_eq!(
+        process(
+            &mut context,
+            &[commit(&program_id, &author, &message, &hash, Timepoint::Slot(0), Timepoint::Slot(slot + 100), None).unwrap()],
+            &[],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+
+    assert_eq!(
+        process(&mut context, &[reveal(&program_id, &author, &message, BID, &SALT).unwrap()], &[])
+            .await
+            .unwrap_err(),
+        custom_error(HelloWorldError::RevealTooEarly)
+    );
+    context.warp_to_slot(slot + 10).unwrap();
+    process(&mut context, &[reveal(&program_id, &author, &message, BID, &SALT).unwrap()], &[])
+        .await
+        .unwrap();
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    assert_eq!(HelloWorldState::unpack(&account.data).unwrap().message, BID);
+    assert_eq!(context.banks_client.get_account(commitment).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn fail_reveal_mismatched_or_late() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let author = context.payer.pubkey();
+    let now = get_clock(&mut context).await.unix_timestamp;
+    let message = setup(
+        &mut context,
+        &program_id,
+        Timepoint::UnixTimestamp(now),
+        Timepoint::UnixTimestamp(now + 60),
+    )
+    .await;
+
+    for (text, salt) in [("I bid 43", SALT), (BID, [8; SALT_LEN])] {
+        assert_eq!(
+            process(&mut context, &[reveal(&program_id, &author, &message, text, &salt).unwrap()], &[])
+                .await
+                .unwrap_err(),
+            custom_error(HelloWorldError::CommitmentMismatch)
+        );
+    }
+
+    let mut clock = get_clock(&mut context).await;
+    clock.unix_timestamp = now + 60;
+    context.set_sysvar(&clock);
+    assert_eq!(
+        process(&mut context, &[reveal(&program_id, &author, &message, BID, &SALT).unwrap()], &[])
+            .await
+            .unwrap_err(),
+        custom_error(HelloWorldError::RevealTooLate)
+    );
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    assert_eq!(HelloWorldState::unpack(&account.data).unwrap().message, "");
+
+    // an expired commitment is replaced by a new one
+    let hash = commitment_hash("I bid 43", &SALT).to_bytes();
+    process(
+        &mut context,
+        &[commit(
+            &program_id,
+            &author,
+            &message,
+            &hash,
+            Timepoint::UnixTimestamp(0),
+            Timepoint::UnixTimestamp(now + 120),
+            None,
+        )
+        .unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+    process(&mut context, &[reveal(&program_id, &author, &message, "I bid 43", &SALT).unwrap()], &[])
+        .await
+        .unwrap();
+
+    // a deadline already passed is refused
+    assert_eq!(
+        process(
+            &mut context,
+            &[commit(&program_id, &author, &message, &hash, Timepoint::Slot(0), Timepoint::UnixTimestamp(now), None).unwrap()],
+            &[],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+}
+
+#[tokio::test]
+async fn success_close_and_replace_expired_commitment() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let now = get_clock(&mut context).await.unix_timestamp;
+    // 承诺人都不是payer，手续费不会影响它们的余额
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let author = alice.pubkey();
+    let (message, _) = find_message_address(&program_id, &author, SEED);
+    let hash = commitment_hash(BID, &SALT).to_bytes();
+    process(
+        &mut context,
+        &[
+            system_instruction::transfer(&payer, &author, 1_000_000_000),
+            system_instruction::transfer(&payer, &bob.pubkey(), 1_000_000_000),
+            initialize(&program_id, &author, &message, SEED).unwrap(),
+            commit(
+                &program_id,
+                &author,
+                &message,
+                &hash,
+                Timepoint::UnixTimestamp(now),
+                Timepoint::UnixTimestamp(now + 60),
+                None,
+            )
+            .unwrap(),
+        ],
+        &[&alice],
+    )
+    .await
+    .unwrap();
+    let (commitment, _) = find_commitment_address(&program_id, &message);
+    let rent = Rent::default().minimum_balance(Commitment::LEN);
+
+    // 新的authority替换过期的承诺，原来的承诺人拿回租金
+    process(
+        &mut context,
+        &[set_authority(&program_id, &author, &message, &bob.pubkey(), &[]).unwrap()],
+        &[&alice],
+    )
+    .await
+    .unwrap();
+    let close = close_commitment(&program_id, &author, &message).unwrap();
+    assert_eq!(
+        process(&mut context, std::slice::from_ref(&close), &[&alice]).await.unwrap_err(),
+        custom_error(HelloWorldError::CommitmentNotExpired)
+    );
+    advance_clock(&mut context, 60).await;
+
+    let deadline = Timepoint::UnixTimestamp(now + 120);
+    let commit_of = |previous: Option<&Pubkey>| {
+        commit(&program_id, &bob.pubkey(), &message, &hash, Timepoint::UnixTimestamp(0), deadline, previous).unwrap()
+    };
+    assert_eq!(
+        process(&mut context, &[commit_of(None)], &[&bob]).await.unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+    assert_eq!(
+        process(&mut context, &[commit_of(Some(&bob.pubkey()))], &[&bob]).await.unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+    let author_lamports = get_balance(&mut context, &author).await;
+    let bob_lamports = get_balance(&mut context, &bob.pubkey()).await;
+    process(&mut context, &[commit_of(Some(&author))], &[&bob]).await.unwrap();
+    assert_eq!(get_balance(&mut context, &author).await, author_lamports + rent);
+    assert_eq!(get_balance(&mut context, &bob.pubkey()).await, bob_lamports - rent);
+    assert_eq!(get_balance(&mut context, &commitment).await, rent);
+    let account = context.banks_client.get_account(commitment).await.unwrap().unwrap();
+    assert_eq!(Commitment::unpack(&account.data).unwrap().committer, bob.pubkey());
+
+    // 只有承诺人能在截止时间以后关掉承诺
+    advance_clock(&mut context, 60).await;
+    assert_eq!(
+        process(&mut context, &[close], &[&alice]).await.unwrap_err(),
+        custom_error(HelloWorldError::Unauthorized)
+    );
+    process(
+        &mut context,
+        &[close_commitment(&program_id, &bob.pubkey(), &message).unwrap()],
+        &[&bob],
+    )
+    .await
+    .unwrap();
+    assert_eq!(context.banks_client.get_account(commitment).await.unwrap(), None);
+    assert_eq!(get_balance(&mut context, &bob.pubkey()).await, bob_lamports);
+}
@@ -0,0 +1,137 @@
+//! 集成测试共用的辅助函数
+#![allow(dead_code)]
+
+use {
+    helloworld::{error::HelloWorldError, find_program_data_address, state::HelloWorldState},
+    solana_program_test::ProgramTestContext,
+    solana_sdk::{
+        account::Account,
+        bpf_loader_upgradeable,
+        clock::Clock,
+        instruction::{Instruction, InstructionError},
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token::state::{Account as TokenAccount, AccountState, Mint},
+};
+
+/// Processes the instructions in one transaction paid by the context payer, signed by it
+/// and `signers`
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .map_err(|error| error.unwrap())
+}
+
+/// The error of the first instruction failing with `error`
+pub fn custom_error(error: HelloWorldError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+pub async fn get_clock(context: &mut ProgramTestContext) -> Clock {
+    context.banks_client.get_sysvar::<Clock>().await.unwrap()
+}
+
+/// Moves the cluster time `seconds` forward
+pub async fn advance_clock(context: &mut ProgramTestContext, seconds: i64) {
+    let mut clock = get_clock(context).await;
+    clock.unix_timestamp += seconds;
+    context.set_sysvar(&clock);
+}
+
+pub async fn get_balance(context: &mut ProgramTestContext, pubkey: &Pubkey) -> u64 {
+    context.banks_client.get_balance(*pubkey).await.unwrap()
+}
+
+pub async fn get_state(context: &mut ProgramTestContext, message: &Pubkey) -> HelloWorldState {
+    let account = context.banks_client.get_account(*message).await.unwrap().unwrap();
+    HelloWorldState::unpack(&account.data).unwrap()
+}
+
+/// An initialized, rent-exempt token account of `owner` holding `amount` of `mint`
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; TokenAccount::LEN];
+    TokenAccount::pack(
+        TokenAccount {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: AccountState::Initialized,
+            ..TokenAccount::default()
+        },
+        &mut data,
+    )
+    .unwrap();
+    Account {
+        lamports: Rent::default().minimum_balance(TokenAccount::LEN),
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+/// An initialized, rent-exempt mint without mint authority
+pub fn mint_account() -> Account {
+    let mut data = vec![0; Mint::LEN];
+    Mint::pack(
+        Mint {
+            supply: 1_000,
+            is_initialized: true,
+            ..Mint::default()
+        },
+        &mut data,
+    )
+    .unwrap();
+    Account {
+        lamports: Rent::default().minimum_balance(Mint::LEN),
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+pub async fn token_balance(context: &mut ProgramTestContext, account: Pubkey) -> u64 {
+    let account = context.banks_client.get_account(account).await.unwrap().unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+/// Writes the ProgramData account of the program as if it was deployed with `authority` as
+/// its upgrade authority
+pub fn set_upgrade_authority(context: &mut ProgramTestContext, program_id: &Pubkey, authority: Option<&Pubkey>) {
+    // bincode编码的UpgradeableLoaderState::ProgramData，后面不带程序的代码
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    match authority {
+        Some(authority) => {
+            data.push(1);
+            data.extend_from_slice(authority.as_ref());
+        }
+        None => data.extend_from_slice(&[0; 33]),
+    }
+    let account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: bpf_loader_upgradeable::id(),
+        ..Account::default()
+    };
+    context.set_account(&find_program_data_address(program_id).0, &account.into());
+}
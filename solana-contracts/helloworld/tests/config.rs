@@ -0,0 +1,239 @@
+mod common;
+
+use {
+    common::{process, set_upgrade_authority, token_account},
+    helloworld::{
+        error::HelloWorldError,
+        find_config_address, find_message_address,
+        instruction::{
+            create_poll, erase, hello, init_config, initialize, pause, transfer_admin, unpause, update_config,
+        },
+        processor::Processor,
+        state::{Config, HelloWorldState, Timepoint},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::TransactionError,
+    },
+    spl_token::state::Account as TokenAccount,
+};
+
+const SEED: &str = "hello";
+
+async fn get_config(context: &mut ProgramTestContext, program_id: &Pubkey) -> Config {
+    let config = find_config_address(program_id).0;
+    let account = context.banks_client.get_account(config).await.unwrap().unwrap();
+    Config::unpack(&account.data).unwrap()
+}
+
+/// Starts the program with the given treasury token accounts, a free posting config using
+/// the first one, administered by the payer, and a message of the payer
+async fn setup(program_id: &Pubkey, treasuries: &[Pubkey]) -> ProgramTestContext {
+    let mut program_test = ProgramTest::new("helloworld", *program_id, processor!(Processor::process));
+    for treasury in treasuries {
+        program_test.add_account(*treasury, token_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 0));
+    }
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    set_upgrade_authority(&mut context, program_id, Some(&payer));
+    let (message, _) = find_message_address(program_id, &payer, SEED);
+    process(
+        &mut context,
+        &[
+            init_config(program_id, &payer, &treasuries[0], 0).unwrap(),
+            initialize(program_id, &payer, &message, SEED).unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    context
+}
+
+#[tokio::test]
+async fn success_pause_and_unpause() {
+    let program_id = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+    let mut context = setup(&program_id, &[treasury]).await;
+    let payer = context.payer.pubkey();
+    let (message, _) = find_message_address(&program_id, &payer, SEED);
+    let (other_message, _) = find_message_address(&program_id, &payer, "other");
+
+    process(&mut context, &[pause(&program_id, &payer).unwrap()], &[])
+        .await
+        .unwrap();
+    assert!(get_config(&mut context, &program_id).await.paused);
+
+    // every instruction but the admin ones is rejected while paused, also the ones moving lamports
+    for instruction in [
+        hello(&program_id, &payer, &message, &[], "paused").unwrap(),
+        initialize(&program_id, &payer, &other_message, "other").unwrap(),
+        erase(&program_id, &payer, &message, &payer, &[], &[]).unwrap(),
+    ] {
+        assert_eq!(
+            process(&mut context, &[instruction], &[]).await.unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(HelloWorldError::ProgramPaused as u32)
+            )
+        );
+    }
+    process(
+        &mut context,
+        &[
+            unpause(&program_id, &payer).unwrap(),
+            hello(&program_id, &payer, &message, &[], "back").unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert!(!get_config(&mut context, &program_id).await.paused);
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    assert_eq!(HelloWorldState::unpack(&account.data).unwrap().message, "back");
+}
+
+#[tokio::test]
+async fn success_update_config_and_transfer_admin() {
+    let program_id = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+    let new_treasury = Pubkey::new_unique();
+    let mut context = setup(&program_id, &[treasury, new_treasury]).await;
+    let payer = context.payer.pubkey();
+
+    process(
+        &mut context,
+        &[update_config(&program_id, &payer, &new_treasury, 5).unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+    let config = get_config(&mut context, &program_id).await;
+    assert_eq!(config.treasury, new_treasury);
+    assert_eq!(config.fee_amount, 5);
+    let account = context.banks_client.get_account(new_treasury).await.unwrap().unwrap();
+    assert_eq!(config.fee_mint, TokenAccount::unpack(&account.data).unwrap().mint);
+
+    let admin = Keypair::new();
+    process(
+        &mut context,
+        &[transfer_admin(&program_id, &payer, &admin.pubkey()).unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_config(&mut context, &program_id).await.admin, admin.pubkey());
+
+    // the previous admin has no rights left, the new one has them all
+    for instruction in [
+        pause(&program_id, &payer).unwrap(),
+        update_config(&program_id, &payer, &treasury, 0).unwrap(),
+        transfer_admin(&program_id, &payer, &payer).unwrap(),
+    ] {
+        assert_eq!(
+            process(&mut context, &[instruction], &[]).await.unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(HelloWorldError::Unauthorized as u32)
+            )
+        );
+    }
+    process(&mut context, &[pause(&program_id, &admin.pubkey()).unwrap()], &[&admin])
+        .await
+        .unwrap();
+    assert!(get_config(&mut context, &program_id).await.paused);
+}
+
+#[tokio::test]
+async fn fail_admin_instruction_without_config() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+
+    assert_eq!(
+        process(&mut context, &[pause(&program_id, &payer).unwrap()], &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::AccountNotInitialized as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_without_config_account() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+
+    // the config account is taken the same way by every instruction
+    for (mut instruction, config_index) in [
+        (pause(&program_id, &payer).unwrap(), 1),
+        (initialize(&program_id, &payer, &find_message_address(&program_id, &payer, SEED).0, SEED).unwrap(), 3),
+        (
+            create_poll(
+                &program_id,
+                &payer,
+                SEED,
+                "poll?",
+                &["yes", "no"],
+                Timepoint::UnixTimestamp(0),
+                Timepoint::UnixTimestamp(i64::MAX),
+                None,
+            )
+            .unwrap(),
+            3,
+        ),
+    ] {
+        assert_eq!(instruction.accounts[config_index].pubkey, find_config_address(&program_id).0);
+        instruction.accounts.truncate(config_index);
+        assert_eq!(
+            process(&mut context, &[instruction], &[]).await.unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(HelloWorldError::InvalidConfigAccount as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn fail_init_config_not_upgrade_authority() {
+    let program_id = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    program_test.add_account(treasury, token_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 0));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let init = init_config(&program_id, &payer, &treasury, 0).unwrap();
+
+    // 程序没有ProgramData账号，升级权限属于别人，或者程序已经不可升级
+    assert_eq!(
+        process(&mut context, std::slice::from_ref(&init), &[]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::IncorrectAccountOwner as u32)
+        )
+    );
+    for authority in [Some(Pubkey::new_unique()), None] {
+        set_upgrade_authority(&mut context, &program_id, authority.as_ref());
+        assert_eq!(
+            process(&mut context, std::slice::from_ref(&init), &[]).await.unwrap_err(),
+            TransactionError::InstructionError(0, InstructionError::Custom(HelloWorldError::Unauthorized as u32))
+        );
+    }
+    let config = find_config_address(&program_id).0;
+    assert!(context.banks_client.get_account(config).await.unwrap().is_none());
+
+    set_upgrade_authority(&mut context, &program_id, Some(&payer));
+    process(&mut context, &[init], &[]).await.unwrap();
+    assert_eq!(get_config(&mut context, &program_id).await.admin, payer);
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use {
+    common::process,
+    helloworld::{
+        error::HelloWorldError,
+        find_message_address,
+        instruction::{approve, erase, hello, initialize, revoke},
+        processor::Processor,
+        state::HelloWorldState,
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        instruction::InstructionError,
+        program_option::COption,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::TransactionError,
+    },
+};
+
+const SEED: &str = "hello";
+
+/// Creates the message of the payer and approves `delegate` until `expiry_slot`
+async fn setup_delegated_message(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    delegate: &Pubkey,
+    expiry_slot: u64,
+) -> Pubkey {
+    let author = context.payer.pubkey();
+    let (message, _) = find_message_address(program_id, &author, SEED);
+    process(
+        context,
+        &[
+            initialize(program_id, &author, &message, SEED).unwrap(),
+            approve(program_id, &author, &message, delegate, &[], expiry_slot).unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    message
+}
+
+#[tokio::test]
+async fn success_delegate_posts() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let bot = Keypair::new();
+    let message = setup_delegated_message(&mut context, &program_id, &bot.pubkey(), 1_000).await;
+
+    process(
+        &mut context,
+        &[hello(&program_id, &bot.pubkey(), &message, &[], "posted by a bot").unwrap()],
+        &[&bot],
+    )
+    .await
+    .unwrap();
+
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    let state = HelloWorldState::unpack(&account.data).unwrap();
+    assert_eq!(state.message, "posted by a bot");
+    // the message still belongs to the author
+    assert_eq!(state.account_key, context.payer.pubkey());
+    assert_eq!(state.delegate, COption::Some(bot.pubkey()));
+    assert_eq!(state.delegate_expiry_slot, 1_000);
+
+    // the delegate can only post, not erase
+    let bot_pubkey = bot.pubkey();
+    assert_eq!(
+        process(
+            &mut context,
+            &[erase(&program_id, &bot_pubkey, &message, &bot_pubkey, &[], &[]).unwrap()],
+            &[&bot],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::Unauthorized as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_expired_delegate() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let bot = Keypair::new();
+    let message = setup_delegated_message(&mut context, &program_id, &bot.pubkey(), 100).await;
+
+    context.warp_to_slot(100).unwrap();
+    assert_eq!(
+        process(
+            &mut context,
+            &[hello(&program_id, &bot.pubkey(), &message, &[], "too late").unwrap()],
+            &[&bot],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::DelegateExpired as u32)
+        )
+    );
+
+    // a grant can not expire in the past
+    let author = context.payer.pubkey();
+    assert_eq!(
+        process(
+            &mut context,
+            &[approve(&program_id, &author, &message, &bot.pubkey(), &[], 50).unwrap()],
+            &[],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+}
+
+#[tokio::test]
+async fn fail_revoked_delegate() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let bot = Keypair::new();
+    let message = setup_delegated_message(&mut context, &program_id, &bot.pubkey(), 1_000).await;
+    let author = context.payer.pubkey();
+
+    process(&mut context, &[revoke(&program_id, &author, &message, &[]).unwrap()], &[])
+        .await
+        .unwrap();
+    assert_eq!(
+        process(
+            &mut context,
+            &[hello(&program_id, &bot.pubkey(), &message, &[], "revoked").unwrap()],
+            &[&bot],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::Unauthorized as u32)
+        )
+    );
+
+    // only the author can approve a delegate
+    let other = Keypair::new();
+    assert_eq!(
+        process(
+            &mut context,
+            &[approve(&program_id, &bot.pubkey(), &message, &other.pubkey(), &[], 1_000).unwrap()],
+            &[&bot],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::Unauthorized as u32)
+        )
+    );
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use {
+    common::process,
+    helloworld::{
+        error::HelloWorldError,
+        find_direct_message_address, find_inbox_address,
+        instruction::send_direct_message,
+        processor::Processor,
+        state::{DirectMessage, Inbox, MAX_DIRECT_MESSAGE_LEN},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        account::Account,
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::Signer,
+        transaction::TransactionError,
+    },
+};
+
+async fn get_inbox(context: &mut ProgramTestContext, program_id: &Pubkey, recipient: &Pubkey) -> Inbox {
+    let inbox = find_inbox_address(program_id, recipient).0;
+    let account = context.banks_client.get_account(inbox).await.unwrap().unwrap();
+    Inbox::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn success_send_direct_message() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let sender = context.payer.pubkey();
+    let recipient = Pubkey::new_unique();
+
+    // the first message creates the inbox, the next ones follow it
+    let payloads: [&[u8]; 2] = [&[1; 40], &[2; MAX_DIRECT_MESSAGE_LEN]];
+    for (index, payload) in payloads.iter().enumerate() {
+        process(
+            &mut context,
+            &[send_direct_message(&program_id, &sender, &recipient, index as u64, payload).unwrap()],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    let inbox = get_inbox(&mut context, &program_id, &recipient).await;
+    assert_eq!(inbox.owner, recipient);
+    assert_eq!(inbox.message_count, 2);
+    for (index, payload) in payloads.iter().enumerate() {
+        let (message, _) = find_direct_message_address(&program_id, &recipient, index as u64);
+        let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+        assert_eq!(account.owner, program_id);
+        let message = DirectMessage::unpack(&account.data).unwrap();
+        assert_eq!(message.sender, sender);
+        assert_eq!(message.recipient, recipient);
+        assert_eq!(message.index, index as u64);
+        assert_eq!(message.payload, payload.to_vec());
+    }
+}
+
+#[tokio::test]
+async fn fail_send_direct_message() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let sender = context.payer.pubkey();
+    let recipient = Pubkey::new_unique();
+    process(
+        &mut context,
+        &[send_direct_message(&program_id, &sender, &recipient, 0, &[1]).unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // the message must be the next one of the inbox, an index is never overwritten
+    for index in [0, 2] {
+        assert_eq!(
+            process(
+                &mut context,
+                &[send_direct_message(&program_id, &sender, &recipient, index, &[2]).unwrap()],
+                &[],
+            )
+            .await
+            .unwrap_err(),
+            TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+        );
+    }
+    // the inbox of another recipient
+    let mut instruction = send_direct_message(&program_id, &sender, &recipient, 1, &[2]).unwrap();
+    instruction.accounts[1].pubkey = find_inbox_address(&program_id, &sender).0;
+    assert_eq!(
+        process(&mut context, &[instruction], &[]).await.unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+    // a payload longer than an account
+    assert_eq!(
+        send_direct_message(&program_id, &sender, &recipient, 1, &[0; MAX_DIRECT_MESSAGE_LEN + 1]).unwrap_err(),
+        HelloWorldError::MessageTooLong.into()
+    );
+    assert_eq!(get_inbox(&mut context, &program_id, &recipient).await.message_count, 1);
+
+    // an inbox account owned by another program, with a valid layout
+    let other = Pubkey::new_unique();
+    let inbox = find_inbox_address(&program_id, &other).0;
+    let mut data = vec![0; Inbox::LEN];
+    let state = Inbox {
+        is_initialized: true,
+        owner: other,
+        message_count: 0,
+    };
+    Inbox::pack(state, &mut data).unwrap();
+    let account = Account {
+        lamports: Rent::default().minimum_balance(Inbox::LEN),
+        data,
+        owner: Pubkey::new_unique(),
+        ..Account::default()
+    };
+    context.set_account(&inbox, &account.into());
+    assert_eq!(
+        process(
+            &mut context,
+            &[send_direct_message(&program_id, &sender, &other, 0, &[3]).unwrap()],
+            &[],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::IncorrectAccountOwner as u32)
+        )
+    );
+}
@@ -0,0 +1,168 @@
+use {
+    helloworld::{
+        error::HelloWorldError,
+        find_message_address,
+        instruction::{erase, hello, initialize},
+        processor::Processor,
+        state::{HelloWorldState, CLOSED_ACCOUNT_DISCRIMINATOR},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::Signer,
+        system_instruction,
+        transaction::{Transaction, TransactionError},
+    },
+};
+
+const SEED: &str = "hello";
+
+async fn setup_message(context: &mut ProgramTestContext, program_id: &Pubkey) -> Pubkey {
+    let author = context.payer.pubkey();
+    let (message, _) = find_message_address(program_id, &author, SEED);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            initialize(program_id, &author, &message, SEED).unwrap(),
+            hello(program_id, &author, &message, &[], "hello world!").unwrap(),
+        ],
+        Some(&author),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+    message
+}
+
+#[tokio::test]
+async fn success_erase_to_destination() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let message = setup_message(&mut context, &program_id).await;
+    let message_lamports = context.banks_client.get_balance(message).await.unwrap();
+
+    let destination = Pubkey::new_unique();
+    let tx = Transaction::new_signed_with_payer(
+        &[erase(&program_id, &context.payer.pubkey(), &message, &destination, &[], &[]).unwrap()],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    assert_eq!(context.banks_client.get_balance(destination).await.unwrap(), message_lamports);
+    assert!(context.banks_client.get_account(message).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn fail_erase_to_itself() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let message = setup_message(&mut context, &program_id).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[erase(&program_id, &context.payer.pubkey(), &message, &message, &[], &[]).unwrap()],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let error = context.banks_client.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+}
+
+#[tokio::test]
+async fn fail_erase_without_config() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let message = setup_message(&mut context, &program_id).await;
+    let author = context.payer.pubkey();
+
+    // an Erase without the program config
+    let mut instruction = erase(&program_id, &author, &message, &author, &[], &[]).unwrap();
+    instruction.accounts.remove(3);
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&author),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let error = context.banks_client.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::InvalidConfigAccount as u32)
+        )
+    );
+    assert!(context.banks_client.get_account(message).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn fail_revived_account_after_erase() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let message = setup_message(&mut context, &program_id).await;
+    let message_lamports = context.banks_client.get_balance(message).await.unwrap();
+    let author = context.payer.pubkey();
+
+    // revive the erased account by sending the rent back within the same transaction
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            erase(&program_id, &author, &message, &author, &[], &[]).unwrap(),
+            system_instruction::transfer(&author, &message, message_lamports),
+        ],
+        Some(&author),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    assert_eq!(account.owner, program_id);
+    assert_eq!(account.data.len(), HelloWorldState::LEN);
+    assert_eq!(account.data[..8], CLOSED_ACCOUNT_DISCRIMINATOR);
+    assert!(account.data[8..].iter().all(|byte| *byte == 0));
+    assert_eq!(
+        HelloWorldState::unpack_unchecked(&account.data).unwrap_err(),
+        HelloWorldError::AccountClosed.into()
+    );
+
+    // the stale account can neither be written nor erased again
+    let tx = Transaction::new_signed_with_payer(
+        &[hello(&program_id, &author, &message, &[], "hello again").unwrap()],
+        Some(&author),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let error = context.banks_client.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::AccountClosed as u32)
+        )
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[erase(&program_id, &author, &message, &author, &[], &[]).unwrap()],
+        Some(&author),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let error = context.banks_client.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::AccountClosed as u32)
+        )
+    );
+}
@@ -0,0 +1,89 @@
+use {
+    helloworld::{
+        event::HelloWorldEvent,
+        find_message_address,
+        instruction::{approve, erase, hello, initialize},
+        processor::Processor,
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        instruction::Instruction, pubkey::Pubkey, signature::Signer, transaction::Transaction,
+    },
+};
+
+const SEED: &str = "hello";
+
+/// Processes the instructions and returns the events they logged
+async fn process(context: &mut ProgramTestContext, instructions: &[Instruction]) -> Vec<HelloWorldEvent> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        blockhash,
+    );
+    let result = context
+        .banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    result.result.unwrap();
+    result
+        .metadata
+        .unwrap()
+        .log_messages
+        .iter()
+        .filter_map(|log| HelloWorldEvent::from_log(log))
+        .collect()
+}
+
+#[tokio::test]
+async fn success_events() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let author = context.payer.pubkey();
+    let (message, _) = find_message_address(&program_id, &author, SEED);
+    let delegate = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+
+    let events = process(
+        &mut context,
+        &[
+            initialize(&program_id, &author, &message, SEED).unwrap(),
+            hello(&program_id, &author, &message, &[], "hello world!").unwrap(),
+            approve(&program_id, &author, &message, &delegate, &[], 1_000).unwrap(),
+        ],
+    )
+    .await;
+    assert_eq!(
+        events,
+        vec![
+            HelloWorldEvent::MessageInitialized { message, author },
+            HelloWorldEvent::MessagePosted {
+                message,
+                poster: author,
+                edit_count: 1,
+                text: "hello world!".to_string(),
+            },
+            HelloWorldEvent::DelegateApproved {
+                message,
+                delegate,
+                expiry_slot: 1_000,
+            },
+        ]
+    );
+
+    let events = process(
+        &mut context,
+        &[erase(&program_id, &author, &message, &destination, &[], &[]).unwrap()],
+    )
+    .await;
+    assert_eq!(
+        events,
+        vec![HelloWorldEvent::MessageErased {
+            message,
+            destination
+        }]
+    );
+}
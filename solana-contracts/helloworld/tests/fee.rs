@@ -0,0 +1,219 @@
+mod common;
+
+use {
+    common::{mint_account, process, set_upgrade_authority, token_account, token_balance},
+    helloworld::{
+        error::HelloWorldError,
+        find_config_address, find_message_address,
+        instruction::{hello, hello_with_fee, init_config, initialize, FeeAccounts},
+        processor::Processor,
+        state::{Config, HelloWorldState},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::Signer,
+        transaction::TransactionError,
+    },
+};
+
+const SEED: &str = "hello";
+const FEE: u64 = 10;
+
+/// Accounts of a program charging `FEE` tokens of `mint` per post
+struct Setup {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    message: Pubkey,
+    fee: FeeAccounts,
+    /// token account of the payer in another mint
+    other_source: Pubkey,
+}
+
+/// Starts the program with a treasury, a funded token account of the payer and a token
+/// account of another mint, then creates the config and the message of the payer
+async fn setup(source_amount: u64) -> Setup {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mint = Pubkey::new_unique();
+    let other_mint = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let other_source = Pubkey::new_unique();
+    program_test.add_account(mint, mint_account());
+    program_test.add_account(other_mint, mint_account());
+    program_test.add_account(treasury, token_account(&mint, &Pubkey::new_unique(), 0));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    set_upgrade_authority(&mut context, &program_id, Some(&payer));
+    context.set_account(&source, &token_account(&mint, &payer, source_amount).into());
+    context.set_account(&other_source, &token_account(&other_mint, &payer, 1_000).into());
+
+    let (message, _) = find_message_address(&program_id, &payer, SEED);
+    process(
+        &mut context,
+        &[
+            init_config(&program_id, &payer, &treasury, FEE).unwrap(),
+            initialize(&program_id, &payer, &message, SEED).unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    let fee = FeeAccounts {
+        source,
+        owner: payer,
+        treasury,
+    };
+    Setup {
+        context,
+        program_id,
+        message,
+        fee,
+        other_source,
+    }
+}
+
+#[tokio::test]
+async fn success_hello_pays_fee() {
+    let mut setup = setup(100).await;
+    let program_id = setup.program_id;
+    let payer = setup.context.payer.pubkey();
+
+    let account = setup
+        .context
+        .banks_client
+        .get_account(find_config_address(&program_id).0)
+        .await
+        .unwrap()
+        .unwrap();
+    let config = Config::unpack(&account.data).unwrap();
+    assert_eq!(config.admin, payer);
+    assert_eq!(config.fee_amount, FEE);
+    assert_eq!(config.treasury, setup.fee.treasury);
+
+    process(
+        &mut setup.context,
+        &[hello_with_fee(&program_id, &payer, &setup.message, &[], &setup.fee, "paid", None).unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(token_balance(&mut setup.context, setup.fee.source).await, 100 - FEE);
+    assert_eq!(token_balance(&mut setup.context, setup.fee.treasury).await, FEE);
+    let account = setup.context.banks_client.get_account(setup.message).await.unwrap().unwrap();
+    assert_eq!(HelloWorldState::unpack(&account.data).unwrap().message, "paid");
+
+    // the fee accounts can not be left out
+    assert_eq!(
+        process(&mut setup.context, &[hello(&program_id, &payer, &setup.message, &[], "free").unwrap()], &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    // the config is created once
+    assert_eq!(
+        process(
+            &mut setup.context,
+            &[init_config(&program_id, &payer, &setup.fee.treasury, 0).unwrap()],
+            &[],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn fail_invalid_fee_accounts() {
+    let mut setup = setup(100).await;
+    let other_source = setup.other_source;
+    let program_id = setup.program_id;
+    let payer = setup.context.payer.pubkey();
+
+    // paid to another account than the treasury, or in another mint
+    for fee in [
+        FeeAccounts {
+            treasury: other_source,
+            ..setup.fee
+        },
+        FeeAccounts {
+            source: other_source,
+            ..setup.fee
+        },
+    ] {
+        assert_eq!(
+            process(
+                &mut setup.context,
+                &[hello_with_fee(&program_id, &payer, &setup.message, &[], &fee, "wrong", None).unwrap()],
+                &[],
+            )
+            .await
+            .unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(HelloWorldError::InvalidFeeAccount as u32)
+            )
+        );
+    }
+    assert_eq!(token_balance(&mut setup.context, setup.fee.source).await, 100);
+}
+
+#[tokio::test]
+async fn fail_underfunded_fee_account() {
+    let mut setup = setup(FEE - 1).await;
+    let program_id = setup.program_id;
+    let payer = setup.context.payer.pubkey();
+
+    assert_eq!(
+        process(
+            &mut setup.context,
+            &[hello_with_fee(&program_id, &payer, &setup.message, &[], &setup.fee, "broke", None).unwrap()],
+            &[],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::InsufficientFeeFunds as u32)
+        )
+    );
+    let account = setup.context.banks_client.get_account(setup.message).await.unwrap().unwrap();
+    assert_eq!(HelloWorldState::unpack(&account.data).unwrap().message, "");
+}
+
+#[tokio::test]
+async fn fail_init_config_without_token_treasury() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    set_upgrade_authority(&mut context, &program_id, Some(&payer));
+
+    assert_eq!(
+        process(&mut context, &[init_config(&program_id, &payer, &payer, FEE).unwrap()], &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::InvalidFeeAccount as u32)
+        )
+    );
+    // without config posting stays free
+    let (message, _) = find_message_address(&program_id, &payer, SEED);
+    process(
+        &mut context,
+        &[
+            initialize(&program_id, &payer, &message, SEED).unwrap(),
+            hello(&program_id, &payer, &message, &[], "free").unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    assert_eq!(HelloWorldState::unpack(&account.data).unwrap().message, "free");
+}
@@ -0,0 +1,353 @@
+mod common;
+
+use {
+    common::{get_state, process},
+    helloworld::{
+        error::HelloWorldError,
+        find_message_address, find_message_counter_address,
+        instruction::{hello, hello_checked, initialize, HelloWorldInstruction},
+        processor::Processor,
+        state::{HelloWorldState, MessageCounter, MAX_MESSAGE_LEN, STATE_VERSION},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        account::Account,
+        hash::hash,
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::Signer,
+        signer::keypair::Keypair,
+        system_instruction,
+        transaction::{Transaction, TransactionError},
+    },
+};
+
+const SEED: &str = "hello";
+
+async fn setup_message(context: &mut ProgramTestContext, program_id: &Pubkey) -> Pubkey {
+    let author = context.payer.pubkey();
+    let (message, _) = find_message_address(program_id, &author, SEED);
+    process(context, &[initialize(program_id, &author, &message, SEED).unwrap()], &[])
+        .await
+        .unwrap();
+    message
+}
+
+/// An initialized message account of `author`, owned by `owner`
+fn message_account(owner: &Pubkey, author: &Pubkey) -> Account {
+    let mut data = vec![0; HelloWorldState::LEN];
+    HelloWorldState {
+        version: STATE_VERSION,
+        account_key: *author,
+        ..HelloWorldState::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: Rent::default().minimum_balance(HelloWorldState::LEN),
+        data,
+        owner: *owner,
+        ..Account::default()
+    }
+}
+
+#[tokio::test]
+async fn success_initialize() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let author = context.payer.pubkey();
+    let author_lamports = context.banks_client.get_balance(author).await.unwrap();
+
+    let message = setup_message(&mut context, &program_id).await;
+
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert_eq!(account.owner, program_id);
+    assert_eq!(account.data.len(), HelloWorldState::LEN);
+    assert_eq!(account.lamports, rent.minimum_balance(HelloWorldState::LEN));
+    // the first message also creates the message counter
+    let (counter, _) = find_message_counter_address(&program_id);
+    let counter = context.banks_client.get_account(counter).await.unwrap().unwrap();
+    assert_eq!(counter.owner, program_id);
+    assert_eq!(
+        MessageCounter::unpack(&counter.data).unwrap(),
+        MessageCounter { is_initialized: true, count: 1 }
+    );
+    // the author pays the rent and the transaction fee
+    let fee = author_lamports - account.lamports - counter.lamports
+        - context.banks_client.get_balance(author).await.unwrap();
+    assert!(fee > 0 && fee < rent.minimum_balance(0));
+
+    let state = get_state(&mut context, &message).await;
+    assert_eq!(state.account_key, author);
+    assert_eq!(state.message, "");
+    assert_eq!(state.edit_count, 0);
+    assert_eq!(state.creation_id, 1);
+
+    // the message account can only be created once
+    assert_eq!(
+        process(&mut context, &[initialize(&program_id, &author, &message, SEED).unwrap()], &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn success_initialize_prefunded_address() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let author = context.payer.pubkey();
+    let (message, _) = find_message_address(&program_id, &author, SEED);
+
+    // anyone can send lamports to the address before it is created
+    let rent = context.banks_client.get_rent().await.unwrap();
+    process(&mut context, &[system_instruction::transfer(&author, &message, rent.minimum_balance(0))], &[])
+        .await
+        .unwrap();
+    process(&mut context, &[initialize(&program_id, &author, &message, SEED).unwrap()], &[])
+        .await
+        .unwrap();
+
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    assert_eq!(account.owner, program_id);
+    assert_eq!(account.lamports, rent.minimum_balance(HelloWorldState::LEN));
+}
+
+#[tokio::test]
+async fn fail_initialize_wrong_address() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let author = context.payer.pubkey();
+    let (message, _) = find_message_address(&program_id, &author, "other");
+
+    assert_eq!(
+        process(&mut context, &[initialize(&program_id, &author, &message, SEED).unwrap()], &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+}
+
+#[tokio::test]
+async fn success_hello_and_overwrite() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let message = setup_message(&mut context, &program_id).await;
+    let author = context.payer.pubkey();
+    let message_lamports = context.banks_client.get_balance(message).await.unwrap();
+
+    process(&mut context, &[hello(&program_id, &author, &message, &[], "hello world!").unwrap()], &[])
+        .await
+        .unwrap();
+    let state = get_state(&mut context, &message).await;
+    assert_eq!(state.message, "hello world!");
+    assert_eq!(state.edit_count, 1);
+    assert!(state.updated_at >= state.created_at);
+
+    // a shorter message replaces the whole previous one
+    process(&mut context, &[hello(&program_id, &author, &message, &[], "bye").unwrap()], &[])
+        .await
+        .unwrap();
+    let state = get_state(&mut context, &message).await;
+    assert_eq!(state.message, "bye");
+    assert_eq!(state.edit_count, 2);
+    assert!(state.updated_slot >= state.created_slot);
+    assert_eq!(
+        state.previous_hashes(),
+        vec![hash(b"hello world!"), hash(b"")]
+    );
+
+    let longest = "a".repeat(MAX_MESSAGE_LEN);
+    process(&mut context, &[hello(&program_id, &author, &message, &[], &longest).unwrap()], &[])
+        .await
+        .unwrap();
+    assert_eq!(get_state(&mut context, &message).await.message, longest);
+
+    // writing does not cost the message account anything
+    assert_eq!(context.banks_client.get_balance(message).await.unwrap(), message_lamports);
+}
+
+#[tokio::test]
+async fn fail_hello_stale_version() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let message = setup_message(&mut context, &program_id).await;
+    let author = context.payer.pubkey();
+
+    // two clients read the message at version 0, the first update wins
+    process(&mut context, &[hello_checked(&program_id, &author, &message, &[], "first", 0).unwrap()], &[])
+        .await
+        .unwrap();
+    assert_eq!(
+        process(&mut context, &[hello_checked(&program_id, &author, &message, &[], "second", 0).unwrap()], &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::VersionMismatch as u32)
+        )
+    );
+    assert_eq!(get_state(&mut context, &message).await.message, "first");
+
+    // the second client retries on top of the current version
+    process(&mut context, &[hello_checked(&program_id, &author, &message, &[], "second", 1).unwrap()], &[])
+        .await
+        .unwrap();
+    let state = get_state(&mut context, &message).await;
+    assert_eq!(state.message, "second");
+    assert_eq!(state.edit_count, 2);
+}
+
+#[tokio::test]
+async fn fail_hello_missing_signature() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let author = Pubkey::new_unique();
+    let message = Pubkey::new_unique();
+    program_test.add_account(message, message_account(&program_id, &author));
+    let mut context = program_test.start_with_context().await;
+
+    let mut instruction = hello(&program_id, &author, &message, &[], "hello world!").unwrap();
+    instruction.accounts[0].is_signer = false;
+    assert_eq!(
+        process(&mut context, &[instruction], &[]).await.unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn fail_hello_without_config() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let message = setup_message(&mut context, &program_id).await;
+    let author = context.payer.pubkey();
+
+    // only the author and the message, without the program config
+    let mut instruction = hello(&program_id, &author, &message, &[], "hello world!").unwrap();
+    instruction.accounts.truncate(2);
+    assert_eq!(
+        process(&mut context, &[instruction], &[]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::InvalidConfigAccount as u32)
+        )
+    );
+    assert_eq!(get_state(&mut context, &message).await.message, "");
+}
+
+#[tokio::test]
+async fn fail_hello_unauthorized() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let message = setup_message(&mut context, &program_id).await;
+
+    let other = Keypair::new();
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[hello(&program_id, &other.pubkey(), &message, &[], "hello world!").unwrap()],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &other],
+        blockhash,
+    );
+    assert_eq!(
+        context.banks_client.process_transaction(tx).await.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::Unauthorized as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_hello_oversize_message() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let message = setup_message(&mut context, &program_id).await;
+    let author = context.payer.pubkey();
+
+    let too_long = "a".repeat(MAX_MESSAGE_LEN + 1);
+    assert_eq!(
+        hello(&program_id, &author, &message, &[], &too_long).unwrap_err(),
+        HelloWorldError::MessageTooLong.into()
+    );
+
+    // the program checks it as well, whatever the client sends
+    let mut instruction = hello(&program_id, &author, &message, &[], "").unwrap();
+    instruction.data = HelloWorldInstruction::Hello {
+        message: too_long,
+        expected_version: None,
+        parent: None,
+    }
+    .pack();
+    assert_eq!(
+        process(&mut context, &[instruction], &[]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::MessageTooLong as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_hello_wrong_owner() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let author = Keypair::new();
+
+    // a well formed message account of the author, but owned by another program
+    let forged = Pubkey::new_unique();
+    program_test.add_account(forged, message_account(&Pubkey::new_unique(), &author.pubkey()));
+    let mut context = program_test.start_with_context().await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[hello(&program_id, &author.pubkey(), &forged, &[], "hello world!").unwrap()],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &author],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context.banks_client.process_transaction(tx).await.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::IncorrectAccountOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_hello_uninitialized_account() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let message = Pubkey::new_unique();
+    program_test.add_account(
+        message,
+        Account {
+            lamports: Rent::default().minimum_balance(HelloWorldState::LEN),
+            data: vec![0; HelloWorldState::LEN],
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+    let author = context.payer.pubkey();
+
+    assert_eq!(
+        process(&mut context, &[hello(&program_id, &author, &message, &[], "hello world!").unwrap()], &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::AccountNotInitialized as u32)
+        )
+    );
+}
@@ -0,0 +1,173 @@
+use {
+    helloworld::{
+        error::HelloWorldError,
+        find_message_address, find_message_counter_address,
+        instruction::{hello, migrate},
+        processor::Processor,
+        state::{HelloWorldState, HelloWorldStateV1, CLOSED_ACCOUNT_DISCRIMINATOR, STATE_VERSION},
+    },
+    solana_program_test::{processor, tokio, ProgramTest},
+    solana_sdk::{
+        account::Account,
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::{Transaction, TransactionError},
+    },
+};
+
+/// A message account written by the program before the version header was introduced
+fn legacy_account(program_id: &Pubkey, author: &Pubkey, message: &str) -> Account {
+    // author, message length, message
+    let mut data = vec![0; HelloWorldStateV1::LEN];
+    data[..32].copy_from_slice(author.as_ref());
+    data[32] = message.len() as u8;
+    data[33..33 + message.len()].copy_from_slice(message.as_bytes());
+    Account {
+        lamports: Rent::default().minimum_balance(HelloWorldStateV1::LEN),
+        data,
+        owner: *program_id,
+        ..Account::default()
+    }
+}
+
+#[tokio::test]
+async fn success_migrate() {
+    let program_id = Pubkey::new_unique();
+    let author = Keypair::new();
+    let legacy_message = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    program_test.add_account(
+        legacy_message,
+        legacy_account(&program_id, &author.pubkey(), "hello world!"),
+    );
+    let mut context = program_test.start_with_context().await;
+    let legacy_lamports = context.banks_client.get_balance(legacy_message).await.unwrap();
+
+    let payer = context.payer.pubkey();
+    let (message, _) = find_message_address(&program_id, &payer, "hello");
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate(&program_id, &payer, &legacy_message, &message, "hello").unwrap()],
+        Some(&payer),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    // only the author may migrate
+    let error = context.banks_client.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(0, InstructionError::Custom(HelloWorldError::Unauthorized as u32))
+    );
+
+    let (message, _) = find_message_address(&program_id, &author.pubkey(), "hello");
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            solana_sdk::system_instruction::transfer(&payer, &author.pubkey(), 1_000_000_000),
+            migrate(&program_id, &author.pubkey(), &legacy_message, &message, "hello").unwrap(),
+        ],
+        Some(&payer),
+        &[&context.payer, &author],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    assert_eq!(account.owner, program_id);
+    let state = HelloWorldState::unpack(&account.data).unwrap();
+    assert_eq!(state.version, STATE_VERSION);
+    assert_eq!(state.account_key, author.pubkey());
+    assert_eq!(state.message, "hello world!");
+    assert_eq!(state.edit_count, 0);
+    assert_eq!(state.created_at, state.updated_at);
+
+    assert_eq!(state.creation_id, 1);
+
+    // the legacy rent goes back to the author, who paid for the new account and the first
+    // one of the message counter
+    let counter_lamports = context
+        .banks_client
+        .get_balance(find_message_counter_address(&program_id).0)
+        .await
+        .unwrap();
+    assert!(context.banks_client.get_account(legacy_message).await.unwrap().is_none());
+    assert_eq!(
+        context.banks_client.get_balance(author.pubkey()).await.unwrap(),
+        1_000_000_000 + legacy_lamports - account.lamports - counter_lamports
+    );
+}
+
+#[tokio::test]
+async fn fail_hello_on_legacy_account() {
+    let program_id = Pubkey::new_unique();
+    let author = Keypair::new();
+    let legacy_message = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    program_test.add_account(
+        legacy_message,
+        legacy_account(&program_id, &author.pubkey(), "hello world!"),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[hello(&program_id, &author.pubkey(), &legacy_message, &[], "hello again").unwrap()],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &author],
+        context.last_blockhash,
+    );
+    let error = context.banks_client.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::AccountNotMigrated as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_migrate_twice() {
+    let program_id = Pubkey::new_unique();
+    let author = Keypair::new();
+    let legacy_message = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    program_test.add_account(
+        legacy_message,
+        legacy_account(&program_id, &author.pubkey(), "hello world!"),
+    );
+    program_test.add_account(
+        author.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            ..Account::default()
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let (message, _) = find_message_address(&program_id, &author.pubkey(), "hello");
+    let (other_message, _) = find_message_address(&program_id, &author.pubkey(), "other");
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            migrate(&program_id, &author.pubkey(), &legacy_message, &message, "hello").unwrap(),
+            migrate(&program_id, &author.pubkey(), &legacy_message, &other_message, "other").unwrap(),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &author],
+        context.last_blockhash,
+    );
+    let error = context.banks_client.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(1, InstructionError::Custom(HelloWorldError::AccountClosed as u32))
+    );
+
+    // the failed transaction is rolled back, the legacy account is still there
+    let account = context.banks_client.get_account(legacy_message).await.unwrap().unwrap();
+    assert_ne!(account.data[..8], CLOSED_ACCOUNT_DISCRIMINATOR);
+    assert_eq!(
+        HelloWorldStateV1::unpack(&account.data).unwrap().message,
+        "hello world!"
+    );
+}
@@ -0,0 +1,244 @@
+mod common;
+
+use {
+    common::process,
+    helloworld::{
+        error::HelloWorldError,
+        find_message_address,
+        instruction::{hello, initialize, initialize_multisig, set_authority, HelloWorldInstruction},
+        processor::Processor,
+        state::{HelloWorldState, Multisig},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        account::Account,
+        instruction::InstructionError,
+        program_option::COption,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::Signer,
+        signer::keypair::Keypair,
+        system_instruction,
+        transaction::TransactionError,
+    },
+};
+
+const SEED: &str = "hello";
+
+/// Creates a M-of-N multisignature account of the given signers
+async fn create_multisig(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    signers: &[&Pubkey],
+    m: u8,
+) -> Result<Pubkey, TransactionError> {
+    let multisig = Keypair::new();
+    let payer = context.payer.pubkey();
+    process(
+        context,
+        &[
+            system_instruction::create_account(
+                &payer,
+                &multisig.pubkey(),
+                Rent::default().minimum_balance(Multisig::LEN),
+                Multisig::LEN as u64,
+                program_id,
+            ),
+            initialize_multisig(program_id, &multisig.pubkey(), signers, m).unwrap(),
+        ],
+        &[&multisig],
+    )
+    .await?;
+    Ok(multisig.pubkey())
+}
+
+#[tokio::test]
+async fn success_multisig_authority() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let signers = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let signer_pubkeys: Vec<Pubkey> = signers.iter().map(|signer| signer.pubkey()).collect();
+    let signer_refs: Vec<&Pubkey> = signer_pubkeys.iter().collect();
+    let multisig = create_multisig(&mut context, &program_id, &signer_refs, 2).await.unwrap();
+
+    let account = context.banks_client.get_account(multisig).await.unwrap().unwrap();
+    let state = Multisig::unpack(&account.data).unwrap();
+    assert_eq!((state.m, state.n), (2, 3));
+    assert_eq!(state.signers[..3], signer_pubkeys[..]);
+
+    // the author hands the message over to the multisig
+    let author = context.payer.pubkey();
+    let (message, _) = find_message_address(&program_id, &author, SEED);
+    process(
+        &mut context,
+        &[
+            initialize(&program_id, &author, &message, SEED).unwrap(),
+            set_authority(&program_id, &author, &message, &multisig, &[]).unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    assert_eq!(HelloWorldState::unpack(&account.data).unwrap().authority, COption::Some(multisig));
+
+    // the author alone can not post anymore
+    assert_eq!(
+        process(&mut context, &[hello(&program_id, &author, &message, &[], "alone").unwrap()], &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::Unauthorized as u32)
+        )
+    );
+
+    // one signature is not enough, nor is the same signer twice
+    for signing in [vec![&signers[0]], vec![&signers[0], &signers[0]]] {
+        let keys: Vec<Pubkey> = signing.iter().map(|signer| signer.pubkey()).collect();
+        let keys: Vec<&Pubkey> = keys.iter().collect();
+        assert_eq!(
+            process(
+                &mut context,
+                &[hello(&program_id, &multisig, &message, &keys, "one of three").unwrap()],
+                &signing,
+            )
+            .await
+            .unwrap_err(),
+            TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+        );
+    }
+
+    process(
+        &mut context,
+        &[hello(&program_id, &multisig, &message, &[&signer_pubkeys[0], &signer_pubkeys[2]], "two of three").unwrap()],
+        &[&signers[0], &signers[2]],
+    )
+    .await
+    .unwrap();
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    let state = HelloWorldState::unpack(&account.data).unwrap();
+    assert_eq!(state.message, "two of three");
+    assert_eq!(state.account_key, author);
+
+    // the multisig gives the message back to its author
+    process(
+        &mut context,
+        &[set_authority(&program_id, &multisig, &message, &author, &[&signer_pubkeys[1], &signer_pubkeys[2]]).unwrap()],
+        &[&signers[1], &signers[2]],
+    )
+    .await
+    .unwrap();
+    process(&mut context, &[hello(&program_id, &author, &message, &[], "back").unwrap()], &[])
+        .await
+        .unwrap();
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    let state = HelloWorldState::unpack(&account.data).unwrap();
+    assert_eq!(state.authority, COption::None);
+    assert_eq!(state.message, "back");
+}
+
+#[tokio::test]
+async fn fail_initialize_multisig() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let signer = Pubkey::new_unique();
+    let multisig = create_multisig(&mut context, &program_id, &[&signer], 1).await.unwrap();
+
+    // the builder refuses M > N, the program checks it too
+    assert!(initialize_multisig(&program_id, &multisig, &[&signer], 2).is_err());
+    let mut instruction = initialize_multisig(&program_id, &multisig, &[&signer], 1).unwrap();
+    instruction.data = HelloWorldInstruction::InitializeMultisig { m: 2 }.pack();
+    let fresh = Keypair::new();
+    instruction.accounts[0].pubkey = fresh.pubkey();
+    let payer = context.payer.pubkey();
+    assert_eq!(
+        process(
+            &mut context,
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &fresh.pubkey(),
+                    Rent::default().minimum_balance(Multisig::LEN),
+                    Multisig::LEN as u64,
+                    &program_id,
+                ),
+                instruction,
+            ],
+            &[&fresh],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(HelloWorldError::InvalidNumberOfRequiredSigners as u32)
+        )
+    );
+
+    // a multisig can not be initialized twice
+    assert_eq!(
+        process(
+            &mut context,
+            &[initialize_multisig(&program_id, &multisig, &[&signer], 1).unwrap()],
+            &[],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn fail_multisig_without_discriminator() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let signer = Keypair::new();
+    // an account of the program with the size and layout of a multisig, but not marked as one
+    let lookalike = Pubkey::new_unique();
+    let mut state = Multisig {
+        m: 1,
+        n: 1,
+        is_initialized: true,
+        ..Multisig::default()
+    };
+    state.signers[0] = signer.pubkey();
+    let mut data = vec![0; Multisig::LEN];
+    Multisig::pack(state, &mut data).unwrap();
+    data[0] = 0;
+    program_test.add_account(
+        lookalike,
+        Account {
+            lamports: Rent::default().minimum_balance(Multisig::LEN),
+            data,
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+    let author = context.payer.pubkey();
+    let (message, _) = find_message_address(&program_id, &author, SEED);
+    process(
+        &mut context,
+        &[
+            initialize(&program_id, &author, &message, SEED).unwrap(),
+            set_authority(&program_id, &author, &message, &lookalike, &[]).unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        process(
+            &mut context,
+            &[hello(&program_id, &lookalike, &message, &[&signer.pubkey()], "lookalike").unwrap()],
+            &[&signer],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
@@ -0,0 +1,338 @@
+mod common;
+
+use {
+    common::{advance_clock, custom_error, get_clock, mint_account, process, token_account, token_balance},
+    helloworld::{
+        error::HelloWorldError,
+        find_poll_address, find_poll_vault_address, find_vote_address,
+        instruction::{create_poll, vote, withdraw_vote},
+        processor::Processor,
+        state::{Poll, Timepoint, VoteReceipt},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        instruction::{Instruction, InstructionError},
+        program_option::COption,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        system_instruction,
+        transaction::TransactionError,
+    },
+};
+
+const SEED: &str = "poll";
+const QUESTION: &str = "Which network should we launch on?";
+const OPTIONS: [&str; 3] = ["devnet", "testnet", "mainnet"];
+
+/// Funds new wallets so they can pay for their vote receipts
+async fn fund(context: &mut ProgramTestContext, count: usize) -> Vec<Keypair> {
+    let payer = context.payer.pubkey();
+    let wallets: Vec<Keypair> = (0..count).map(|_| Keypair::new()).collect();
+    let instructions: Vec<Instruction> = wallets
+        .iter()
+        .map(|wallet| system_instruction::transfer(&payer, &wallet.pubkey(), 1_000_000_000))
+        .collect();
+    process(context, &instructions, &[]).await.unwrap();
+    wallets
+}
+
+async fn get_poll(context: &mut ProgramTestContext, poll: &Pubkey) -> Poll {
+    let account = context.banks_client.get_account(*poll).await.unwrap().unwrap();
+    Poll::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn success_poll() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let voters = fund(&mut context, 3).await;
+    let creator = context.payer.pubkey();
+    let (poll, _) = find_poll_address(&program_id, &creator, SEED);
+
+    process(
+        &mut context,
+        &[create_poll(
+            &program_id,
+            &creator,
+            SEED,
+            QUESTION,
+            &OPTIONS,
+            Timepoint::UnixTimestamp(0),
+            Timepoint::UnixTimestamp(i64::MAX),
+            None,
+        )
+        .unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+    let state = get_poll(&mut context, &poll).await;
+    assert_eq!(state.creator, creator);
+    assert_eq!(state.question, QUESTION);
+    assert_eq!(
+        state.options.iter().map(|option| option.label.as_str()).collect::<Vec<_>>(),
+        OPTIONS
+    );
+    assert_eq!(state.vote_mint, COption::None);
+
+    for (voter, option) in voters.iter().zip([2, 0, 2]) {
+        process(
+            &mut context,
+            &[vote(&program_id, &voter.pubkey(), &poll, option, None).unwrap()],
+            &[voter],
+        )
+        .await
+        .unwrap();
+    }
+    let state = get_poll(&mut context, &poll).await;
+    assert_eq!(
+        state.options.iter().map(|option| option.votes).collect::<Vec<_>>(),
+        [1, 0, 2]
+    );
+    assert_eq!(state.voter_count, 3);
+    let receipt_address = find_vote_address(&program_id, &poll, &voters[0].pubkey()).0;
+    let account = context.banks_client.get_account(receipt_address).await.unwrap().unwrap();
+    let receipt = VoteReceipt::unpack(&account.data).unwrap();
+    assert_eq!((receipt.poll, receipt.voter), (poll, voters[0].pubkey()));
+    assert_eq!((receipt.option, receipt.weight), (2, 1));
+
+    // 每个钱包只能投一次，换一个选项也不行
+    assert_eq!(
+        process(
+            &mut context,
+            &[vote(&program_id, &voters[0].pubkey(), &poll, 1, None).unwrap()],
+            &[&voters[0]],
+        )
+        .await
+        .unwrap_err(),
+        custom_error(HelloWorldError::AlreadyVoted)
+    );
+    let others = fund(&mut context, 1).await;
+    assert_eq!(
+        process(
+            &mut context,
+            &[vote(&program_id, &others[0].pubkey(), &poll, OPTIONS.len() as u8, None).unwrap()],
+            &[&others[0]],
+        )
+        .await
+        .unwrap_err(),
+        custom_error(HelloWorldError::InvalidPollOption)
+    );
+    // 同一个种子不能创建第二个投票
+    assert_eq!(
+        process(
+            &mut context,
+            &[create_poll(
+                &program_id,
+                &creator,
+                SEED,
+                QUESTION,
+                &OPTIONS[..2],
+                Timepoint::UnixTimestamp(0),
+                Timepoint::UnixTimestamp(i64::MAX),
+                None,
+            )
+            .unwrap()],
+            &[],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+    assert_eq!(get_poll(&mut context, &poll).await.voter_count, 3);
+}
+
+#[tokio::test]
+async fn success_poll_window() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let voters = fund(&mut context, 3).await;
+    let creator = context.payer.pubkey();
+    let (poll, _) = find_poll_address(&program_id, &creator, SEED);
+    let slot = get_clock(&mut context).await.slot;
+
+    // 已经结束的投票不能创建
+    assert_eq!(
+        process(
+            &mut context,
+            &[create_poll(
+                &program_id,
+                &creator,
+                SEED,
+                QUESTION,
+                &OPTIONS,
+                Timepoint::UnixTimestamp(0),
+                Timepoint::UnixTimestamp(1),
+                None,
+            )
+            .unwrap()],
+            &[],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+    process(
+        &mut context,
+        &[create_poll(
+            &program_id,
+            &creator,
+            SEED,
+            QUESTION,
+            &OPTIONS,
+            Timepoint::Slot(slot + 10),
+            Timepoint::Slot(slot + 20),
+            None,
+        )
+        .unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let vote_of = |voter: &Keypair| vote(&program_id, &voter.pubkey(), &poll, 1, None).unwrap();
+    assert_eq!(
+        process(&mut context, &[vote_of(&voters[0])], &[&voters[0]]).await.unwrap_err(),
+        custom_error(HelloWorldError::PollNotOpen)
+    );
+    context.warp_to_slot(slot + 10).unwrap();
+    process(&mut context, &[vote_of(&voters[1])], &[&voters[1]]).await.unwrap();
+    context.warp_to_slot(slot + 20).unwrap();
+    assert_eq!(
+        process(&mut context, &[vote_of(&voters[2])], &[&voters[2]]).await.unwrap_err(),
+        custom_error(HelloWorldError::PollClosed)
+    );
+    let state = get_poll(&mut context, &poll).await;
+    assert_eq!(state.options[1].votes, 1);
+    assert_eq!(state.voter_count, 1);
+
+    // 投票结束后回执可以关闭，计票结果保留
+    let receipt = find_vote_address(&program_id, &poll, &voters[1].pubkey()).0;
+    process(
+        &mut context,
+        &[withdraw_vote(&program_id, &voters[1].pubkey(), &poll, None).unwrap()],
+        &[&voters[1]],
+    )
+    .await
+    .unwrap();
+    assert!(context.banks_client.get_account(receipt).await.unwrap().is_none());
+    assert_eq!(get_poll(&mut context, &poll).await.options[1].votes, 1);
+}
+
+#[tokio::test]
+async fn success_weighted_poll() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let (mint, other_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (alice_tokens, bob_tokens, bob_other_tokens, bob_empty) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    program_test.add_account(alice_tokens, token_account(&mint, &alice.pubkey(), 300));
+    program_test.add_account(bob_tokens, token_account(&mint, &bob.pubkey(), 200));
+    program_test.add_account(bob_other_tokens, token_account(&other_mint, &bob.pubkey(), 500));
+    program_test.add_account(bob_empty, token_account(&mint, &bob.pubkey(), 0));
+    program_test.add_account(mint, mint_account());
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let closes_at = get_clock(&mut context).await.unix_timestamp + 100;
+    process(
+        &mut context,
+        &[
+            system_instruction::transfer(&payer, &alice.pubkey(), 1_000_000_000),
+            system_instruction::transfer(&payer, &bob.pubkey(), 1_000_000_000),
+            create_poll(
+                &program_id,
+                &payer,
+                SEED,
+                QUESTION,
+                &OPTIONS,
+                Timepoint::UnixTimestamp(0),
+                Timepoint::UnixTimestamp(closes_at),
+                Some(&mint),
+            )
+            .unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    let (poll, _) = find_poll_address(&program_id, &payer, SEED);
+    let (vault, _) = find_poll_vault_address(&program_id, &poll);
+    assert_eq!(get_poll(&mut context, &poll).await.vote_mint, COption::Some(mint));
+    assert_eq!(token_balance(&mut context, vault).await, 0);
+
+    // 投票权重账号必须是投票人自己在投票代币上有余额的账号
+    for weight_account in [None, Some(&bob_other_tokens), Some(&alice_tokens), Some(&bob_empty)] {
+        let error = match weight_account {
+            None => TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys),
+            Some(_) => custom_error(HelloWorldError::InvalidWeightAccount),
+        };
+        assert_eq!(
+            process(
+                &mut context,
+                &[vote(&program_id, &bob.pubkey(), &poll, 0, weight_account).unwrap()],
+                &[&bob],
+            )
+            .await
+            .unwrap_err(),
+            error
+        );
+    }
+
+    process(
+        &mut context,
+        &[
+            vote(&program_id, &alice.pubkey(), &poll, 1, Some(&alice_tokens)).unwrap(),
+            vote(&program_id, &bob.pubkey(), &poll, 1, Some(&bob_tokens)).unwrap(),
+        ],
+        &[&alice, &bob],
+    )
+    .await
+    .unwrap();
+    let state = get_poll(&mut context, &poll).await;
+    assert_eq!(state.options[1].votes, 500);
+    assert_eq!(state.voter_count, 2);
+    assert_eq!(state.total_votes(), 500);
+    let receipt_address = find_vote_address(&program_id, &poll, &bob.pubkey()).0;
+    let account = context.banks_client.get_account(receipt_address).await.unwrap().unwrap();
+    assert_eq!(VoteReceipt::unpack(&account.data).unwrap().weight, 200);
+    // 计票的代币托管到投票结束，不能转给别的钱包再投一次
+    assert_eq!(token_balance(&mut context, vault).await, 500);
+    assert_eq!(token_balance(&mut context, alice_tokens).await, 0);
+    assert_eq!(token_balance(&mut context, bob_tokens).await, 0);
+
+    let withdraw_of = |voter: &Keypair, destination: &Pubkey| {
+        withdraw_vote(&program_id, &voter.pubkey(), &poll, Some(destination)).unwrap()
+    };
+    assert_eq!(
+        process(&mut context, &[withdraw_of(&bob, &bob_tokens)], &[&bob]).await.unwrap_err(),
+        custom_error(HelloWorldError::PollNotClosed)
+    );
+    advance_clock(&mut context, 100).await;
+    process(
+        &mut context,
+        &[withdraw_of(&alice, &alice_tokens), withdraw_of(&bob, &bob_empty)],
+        &[&alice, &bob],
+    )
+    .await
+    .unwrap();
+    assert_eq!(token_balance(&mut context, alice_tokens).await, 300);
+    assert_eq!(token_balance(&mut context, bob_empty).await, 200);
+    assert_eq!(token_balance(&mut context, vault).await, 0);
+    assert!(context.banks_client.get_account(receipt_address).await.unwrap().is_none());
+    assert_eq!(get_poll(&mut context, &poll).await.total_votes(), 500);
+
+    // 回执关闭后不能再领一次
+    assert_eq!(
+        process(&mut context, &[withdraw_of(&bob, &bob_tokens)], &[&bob]).await.unwrap_err(),
+        custom_error(HelloWorldError::IncorrectAccountOwner)
+    );
+}
@@ -0,0 +1,220 @@
+mod common;
+
+use {
+    common::{get_state, process},
+    helloworld::{
+        error::HelloWorldError,
+        find_message_address, find_reaction_address,
+        instruction::{erase, hello, initialize, react, unreact},
+        processor::Processor,
+        state::{Reaction, ReactionReceipt},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        instruction::InstructionError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::Signer,
+        signer::keypair::Keypair,
+        system_instruction,
+        transaction::TransactionError,
+    },
+};
+
+const SEED: &str = "hello";
+
+/// Creates a message of the payer and funds two reactors, the payer pays the fees so the
+/// balances of the reactors only move by the rent of their receipts
+async fn setup(context: &mut ProgramTestContext, program_id: &Pubkey) -> (Pubkey, Keypair, Keypair) {
+    let payer = context.payer.pubkey();
+    let (message, _) = find_message_address(program_id, &payer, SEED);
+    let reactors = (Keypair::new(), Keypair::new());
+    process(
+        context,
+        &[
+            initialize(program_id, &payer, &message, SEED).unwrap(),
+            system_instruction::transfer(&payer, &reactors.0.pubkey(), 1_000_000_000),
+            system_instruction::transfer(&payer, &reactors.1.pubkey(), 1_000_000_000),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    (message, reactors.0, reactors.1)
+}
+
+async fn react_with(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    reactor: &Keypair,
+    message: &Pubkey,
+    reaction: Reaction,
+) -> Result<(), TransactionError> {
+    let (receipt, _) = find_reaction_address(program_id, message, &reactor.pubkey());
+    process(
+        context,
+        &[react(program_id, &reactor.pubkey(), message, &receipt, reaction).unwrap()],
+        &[reactor],
+    )
+    .await
+}
+
+async fn unreact_with(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    reactor: &Keypair,
+    message: &Pubkey,
+) -> Result<(), TransactionError> {
+    let (receipt, _) = find_reaction_address(program_id, message, &reactor.pubkey());
+    process(
+        context,
+        &[unreact(program_id, &reactor.pubkey(), message, &receipt).unwrap()],
+        &[reactor],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn success_react_and_unreact() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let (message, alice, bob) = setup(&mut context, &program_id).await;
+    let rent = Rent::default().minimum_balance(ReactionReceipt::LEN);
+    let alice_lamports = context.banks_client.get_balance(alice.pubkey()).await.unwrap();
+
+    react_with(&mut context, &program_id, &alice, &message, Reaction::Like).await.unwrap();
+    react_with(&mut context, &program_id, &bob, &message, Reaction::Like).await.unwrap();
+    assert_eq!(get_state(&mut context, &message).await.reactions, [2, 0, 0, 0, 0, 0]);
+    assert_eq!(
+        context.banks_client.get_balance(alice.pubkey()).await.unwrap(),
+        alice_lamports - rent
+    );
+    let (receipt, _) = find_reaction_address(&program_id, &message, &alice.pubkey());
+    let account = context.banks_client.get_account(receipt).await.unwrap().unwrap();
+    let receipt_state = ReactionReceipt::unpack(&account.data).unwrap();
+    assert_eq!(receipt_state.message, message);
+    assert_eq!(receipt_state.reactor, alice.pubkey());
+    assert_eq!(receipt_state.reaction, Reaction::Like);
+
+    // a wallet reacts once, whatever the reaction
+    for reaction in [Reaction::Like, Reaction::Heart] {
+        assert_eq!(
+            react_with(&mut context, &program_id, &alice, &message, reaction)
+                .await
+                .unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(HelloWorldError::AlreadyReacted as u32)
+            )
+        );
+    }
+
+    // taking the reaction back refunds the rent of the receipt and allows another one
+    unreact_with(&mut context, &program_id, &alice, &message).await.unwrap();
+    assert_eq!(get_state(&mut context, &message).await.reactions, [1, 0, 0, 0, 0, 0]);
+    assert_eq!(context.banks_client.get_account(receipt).await.unwrap(), None);
+    assert_eq!(
+        context.banks_client.get_balance(alice.pubkey()).await.unwrap(),
+        alice_lamports
+    );
+    react_with(&mut context, &program_id, &alice, &message, Reaction::Heart).await.unwrap();
+    assert_eq!(get_state(&mut context, &message).await.reactions, [1, 0, 0, 1, 0, 0]);
+}
+
+#[tokio::test]
+async fn success_unreact_erased_message() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let (message, alice, bob) = setup(&mut context, &program_id).await;
+    let alice_lamports = context.banks_client.get_balance(alice.pubkey()).await.unwrap();
+    let bob_lamports = context.banks_client.get_balance(bob.pubkey()).await.unwrap();
+
+    process(&mut context, &[hello(&program_id, &payer, &message, &[], "hello world!").unwrap()], &[])
+        .await
+        .unwrap();
+    react_with(&mut context, &program_id, &alice, &message, Reaction::Eyes).await.unwrap();
+    react_with(&mut context, &program_id, &bob, &message, Reaction::Eyes).await.unwrap();
+    process(&mut context, &[erase(&program_id, &payer, &message, &payer, &[], &[]).unwrap()], &[])
+        .await
+        .unwrap();
+
+    // the rent comes back once the message is gone
+    unreact_with(&mut context, &program_id, &bob, &message).await.unwrap();
+    assert_eq!(context.banks_client.get_balance(bob.pubkey()).await.unwrap(), bob_lamports);
+
+    // and once a new message is created at the same address, even within the same slot,
+    // without touching the reactions to the new message
+    process(&mut context, &[initialize(&program_id, &payer, &message, SEED).unwrap()], &[])
+        .await
+        .unwrap();
+    react_with(&mut context, &program_id, &bob, &message, Reaction::Eyes).await.unwrap();
+    unreact_with(&mut context, &program_id, &alice, &message).await.unwrap();
+    assert_eq!(
+        context.banks_client.get_balance(alice.pubkey()).await.unwrap(),
+        alice_lamports
+    );
+    assert_eq!(get_state(&mut context, &message).await.reactions, [0, 0, 0, 0, 0, 1]);
+}
+
+#[tokio::test]
+async fn success_unreact_message_created_again_in_same_slot() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+    let (message, alice, bob) = setup(&mut context, &program_id).await;
+    let erased = get_state(&mut context, &message).await;
+
+    // the new message has the same slot and edit count as the erased one, only its
+    // creation id tells them apart
+    react_with(&mut context, &program_id, &alice, &message, Reaction::Like).await.unwrap();
+    process(&mut context, &[erase(&program_id, &payer, &message, &payer, &[], &[]).unwrap()], &[])
+        .await
+        .unwrap();
+    process(&mut context, &[initialize(&program_id, &payer, &message, SEED).unwrap()], &[])
+        .await
+        .unwrap();
+    let state = get_state(&mut context, &message).await;
+    assert_eq!((state.created_slot, state.edit_count), (erased.created_slot, erased.edit_count));
+    assert_eq!(state.creation_id, erased.creation_id + 1);
+
+    react_with(&mut context, &program_id, &bob, &message, Reaction::Like).await.unwrap();
+    unreact_with(&mut context, &program_id, &alice, &message).await.unwrap();
+    assert_eq!(get_state(&mut context, &message).await.reactions, [1, 0, 0, 0, 0, 0]);
+}
+
+#[tokio::test]
+async fn fail_invalid_receipt() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let (message, alice, bob) = setup(&mut context, &program_id).await;
+
+    // the receipt of another wallet can not be used
+    let (bob_receipt, _) = find_reaction_address(&program_id, &message, &bob.pubkey());
+    assert_eq!(
+        process(
+            &mut context,
+            &[react(&program_id, &alice.pubkey(), &message, &bob_receipt, Reaction::Like).unwrap()],
+            &[&alice],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+    // nothing to take back without a reaction
+    assert_eq!(
+        unreact_with(&mut context, &program_id, &alice, &message)
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::IncorrectAccountOwner as u32)
+        )
+    );
+    assert_eq!(get_state(&mut context, &message).await.reactions, [0; 6]);
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use {
+    common::{advance_clock, get_balance, get_state, process},
+    helloworld::{
+        error::HelloWorldError,
+        find_chunk_address, find_config_address, find_message_address,
+        instruction::{append_chunk, initialize, reap, set_reap_bounty, set_ttl},
+        processor::Processor,
+        state::{Config, DEFAULT_REAP_BOUNTY_BPS, MAX_BPS},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        account::Account,
+        instruction::InstructionError,
+        program_error::ProgramError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::Signer,
+        signer::keypair::Keypair,
+        system_instruction,
+        transaction::TransactionError,
+    },
+};
+
+const SEED: &str = "hello";
+const TTL: u64 = 60;
+
+/// Funds an author and a reaper, the author writes a message with one chunk. The payer pays
+/// the fees so the balances of the author and the reaper only move by the reaped lamports
+async fn setup(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+) -> (Keypair, Keypair, Pubkey, Pubkey) {
+    let payer = context.payer.pubkey();
+    let (author, reaper) = (Keypair::new(), Keypair::new());
+    let (message, _) = find_message_address(program_id, &author.pubkey(), SEED);
+    let (chunk, _) = find_chunk_address(program_id, &message, 0);
+    process(
+        context,
+        &[
+            system_instruction::transfer(&payer, &author.pubkey(), 1_000_000_000),
+            system_instruction::transfer(&payer, &reaper.pubkey(), 1_000_000_000),
+            initialize(program_id, &author.pubkey(), &message, SEED).unwrap(),
+            append_chunk(program_id, &author.pubkey(), &message, &chunk, "world!").unwrap(),
+        ],
+        &[&author],
+    )
+    .await
+    .unwrap();
+    (author, reaper, message, chunk)
+}
+
+#[tokio::test]
+async fn success_reap() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let (author, reaper, message, chunk) = setup(&mut context, &program_id).await;
+    process(
+        &mut context,
+        &[set_ttl(&program_id, &author.pubkey(), &message, &[], TTL).unwrap()],
+        &[&author],
+    )
+    .await
+    .unwrap();
+    let state = get_state(&mut context, &message).await;
+    assert_eq!(state.ttl, TTL);
+    assert_eq!(state.expires_at(), Some(state.updated_at + TTL as i64));
+
+    let reap_instruction = reap(&program_id, &reaper.pubkey(), &message, &author.pubkey(), &[&chunk]).unwrap();
+    assert_eq!(
+        process(&mut context, std::slice::from_ref(&reap_instruction), &[&reaper]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::MessageNotExpired as u32)
+        )
+    );
+
+    // without a config the default bounty applies, rounded down for each account
+    let message_lamports = get_balance(&mut context, &message).await;
+    let chunk_lamports = get_balance(&mut context, &chunk).await;
+    let author_lamports = get_balance(&mut context, &author.pubkey()).await;
+    let reaper_lamports = get_balance(&mut context, &reaper.pubkey()).await;
+    let bounty_of = |lamports: u64| lamports * DEFAULT_REAP_BOUNTY_BPS as u64 / MAX_BPS as u64;
+    let bounty = bounty_of(message_lamports) + bounty_of(chunk_lamports);
+
+    advance_clock(&mut context, TTL as i64).await;
+    process(&mut context, &[reap_instruction], &[&reaper]).await.unwrap();
+    assert_eq!(context.banks_client.get_account(message).await.unwrap(), None);
+    assert_eq!(context.banks_client.get_account(chunk).await.unwrap(), None);
+    assert_eq!(get_balance(&mut context, &reaper.pubkey()).await, reaper_lamports + bounty);
+    assert_eq!(
+        get_balance(&mut context, &author.pubkey()).await,
+        author_lamports + message_lamports + chunk_lamports - bounty
+    );
+}
+
+#[tokio::test]
+async fn success_reap_configured_bounty() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let admin = Keypair::new();
+    let mut data = vec![0; Config::LEN];
+    Config {
+        is_initialized: true,
+        admin: admin.pubkey(),
+        ..Config::default()
+    }
+    .pack_into_slice(&mut data);
+    program_test.add_account(
+        find_config_address(&program_id).0,
+        Account {
+            lamports: Rent::default().minimum_balance(Config::LEN),
+            data,
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+    let (author, reaper, message, chunk) = setup(&mut context, &program_id).await;
+    process(
+        &mut context,
+        &[
+            set_reap_bounty(&program_id, &admin.pubkey(), MAX_BPS).unwrap(),
+            set_ttl(&program_id, &author.pubkey(), &message, &[], TTL).unwrap(),
+        ],
+        &[&admin, &author],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        set_reap_bounty(&program_id, &admin.pubkey(), MAX_BPS + 1).unwrap_err(),
+        ProgramError::InvalidArgument
+    );
+
+    // the whole rent goes to the reaper
+    let lamports = get_balance(&mut context, &message).await + get_balance(&mut context, &chunk).await;
+    let author_lamports = get_balance(&mut context, &author.pubkey()).await;
+    let reaper_lamports = get_balance(&mut context, &reaper.pubkey()).await;
+    advance_clock(&mut context, TTL as i64).await;
+    process(
+        &mut context,
+        &[reap(&program_id, &reaper.pubkey(), &message, &author.pubkey(), &[&chunk]).unwrap()],
+        &[&reaper],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_balance(&mut context, &reaper.pubkey()).await, reaper_lamports + lamports);
+    assert_eq!(get_balance(&mut context, &author.pubkey()).await, author_lamports);
+}
+
+#[tokio::test]
+async fn fail_reap() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let (author, reaper, message, chunk) = setup(&mut context, &program_id).await;
+
+    // a message without time to live never expires
+    advance_clock(&mut context, i64::from(u32::MAX)).await;
+    let reap_instruction = reap(&program_id, &reaper.pubkey(), &message, &author.pubkey(), &[&chunk]).unwrap();
+    assert_eq!(
+        process(&mut context, &[reap_instruction], &[&reaper]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::MessageNotExpired as u32)
+        )
+    );
+
+    // only the author gets the rest back, and every chunk is closed along
+    process(
+        &mut context,
+        &[set_ttl(&program_id, &author.pubkey(), &message, &[], TTL).unwrap()],
+        &[&author],
+    )
+    .await
+    .unwrap();
+    advance_clock(&mut context, TTL as i64).await;
+    for (instruction, error) in [
+        (
+            reap(&program_id, &reaper.pubkey(), &message, &reaper.pubkey(), &[&chunk]).unwrap(),
+            InstructionError::Custom(HelloWorldError::Unauthorized as u32),
+        ),
+        (
+            reap(&program_id, &reaper.pubkey(), &message, &author.pubkey(), &[]).unwrap(),
+            InstructionError::NotEnoughAccountKeys,
+        ),
+    ] {
+        assert_eq!(
+            process(&mut context, &[instruction], &[&reaper]).await.unwrap_err(),
+            TransactionError::InstructionError(0, error)
+        );
+    }
+    assert_eq!(get_state(&mut context, &message).await.chunk_count, 1);
+}
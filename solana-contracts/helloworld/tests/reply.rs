@@ -0,0 +1,157 @@
+mod common;
+
+use {
+    common::{get_state, process},
+    helloworld::{
+        error::HelloWorldError,
+        find_message_address,
+        instruction::{hello, initialize, reply},
+        processor::Processor,
+        state::{HelloWorldState, STATE_VERSION},
+    },
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        account::Account,
+        instruction::{Instruction, InstructionError},
+        program_option::COption,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::Signer,
+        transaction::TransactionError,
+    },
+};
+
+const SEEDS: [&str; 3] = ["root", "reply", "nested"];
+
+/// Creates a message of the payer for each of `SEEDS`
+async fn setup_messages(context: &mut ProgramTestContext, program_id: &Pubkey) -> Vec<Pubkey> {
+    let author = context.payer.pubkey();
+    let messages: Vec<Pubkey> = SEEDS
+        .iter()
+        .map(|seed| find_message_address(program_id, &author, seed).0)
+        .collect();
+    let instructions: Vec<Instruction> = messages
+        .iter()
+        .zip(SEEDS.iter())
+        .map(|(message, seed)| initialize(program_id, &author, message, seed).unwrap())
+        .collect();
+    process(context, &instructions, &[]).await.unwrap();
+    messages
+}
+
+#[tokio::test]
+async fn success_reply_thread() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let author = context.payer.pubkey();
+    let messages = setup_messages(&mut context, &program_id).await;
+    let (root, first, nested) = (messages[0], messages[1], messages[2]);
+
+    process(
+        &mut context,
+        &[
+            hello(&program_id, &author, &root, &[], "what do you think?").unwrap(),
+            reply(&program_id, &author, &first, &root, &[], None, "looks good").unwrap(),
+            reply(&program_id, &author, &nested, &first, &[], None, "agreed").unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let root_state = get_state(&mut context, &root).await;
+    assert_eq!(root_state.parent, COption::None);
+    assert_eq!(root_state.reply_count, 1);
+    let first_state = get_state(&mut context, &first).await;
+    assert_eq!(first_state.parent, COption::Some(root));
+    assert_eq!(first_state.reply_count, 1);
+    assert_eq!(first_state.message, "looks good");
+    let nested_state = get_state(&mut context, &nested).await;
+    assert_eq!(nested_state.parent, COption::Some(first));
+    assert_eq!(nested_state.reply_count, 0);
+
+    // editing a reply, with or without naming its parent again, is not another reply
+    process(
+        &mut context,
+        &[
+            reply(&program_id, &author, &first, &root, &[], None, "looks great").unwrap(),
+            hello(&program_id, &author, &first, &[], "looks really great").unwrap(),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_state(&mut context, &root).await.reply_count, 1);
+    let first_state = get_state(&mut context, &first).await;
+    assert_eq!(first_state.parent, COption::Some(root));
+    assert_eq!(first_state.message, "looks really great");
+}
+
+#[tokio::test]
+async fn fail_reply_invalid_parent() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let foreign = Pubkey::new_unique();
+    let mut data = vec![0; HelloWorldState::LEN];
+    HelloWorldState {
+        version: STATE_VERSION,
+        account_key: Pubkey::new_unique(),
+        ..HelloWorldState::default()
+    }
+    .pack_into_slice(&mut data);
+    program_test.add_account(
+        foreign,
+        Account {
+            lamports: Rent::default().minimum_balance(HelloWorldState::LEN),
+            data,
+            owner: Pubkey::new_unique(),
+            ..Account::default()
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+    let author = context.payer.pubkey();
+    let messages = setup_messages(&mut context, &program_id).await;
+    let (root, first, nested) = (messages[0], messages[1], messages[2]);
+    process(
+        &mut context,
+        &[reply(&program_id, &author, &first, &root, &[], None, "first").unwrap()],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // the parent account must be the one named in the instruction
+    let mut mismatched = reply(&program_id, &author, &nested, &root, &[], None, "nested").unwrap();
+    mismatched.accounts[3].pubkey = first;
+
+    for (instruction, error) in [
+        (
+            reply(&program_id, &author, &nested, &foreign, &[], None, "nested").unwrap(),
+            HelloWorldError::IncorrectAccountOwner,
+        ),
+        (
+            reply(&program_id, &author, &nested, &nested, &[], None, "nested").unwrap(),
+            HelloWorldError::InvalidParent,
+        ),
+        (mismatched, HelloWorldError::InvalidParent),
+        // a reply does not move to another thread
+        (
+            reply(&program_id, &author, &first, &nested, &[], None, "moved").unwrap(),
+            HelloWorldError::InvalidParent,
+        ),
+        // a message with replies can not reply itself, it would close a loop
+        (
+            reply(&program_id, &author, &root, &first, &[], None, "loop").unwrap(),
+            HelloWorldError::InvalidParent,
+        ),
+    ] {
+        assert_eq!(
+            process(&mut context, &[instruction], &[]).await.unwrap_err(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    }
+    assert_eq!(get_state(&mut context, &root).await.reply_count, 1);
+    assert_eq!(get_state(&mut context, &nested).await.parent, COption::None);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use {
+    common::process,
+    helloworld::{
+        error::HelloWorldError,
+        find_message_address, find_nonce_address,
+        instruction::{post_signed, signed_post_message, verify_signature},
+        processor::Processor,
+        state::{HelloWorldState, SignedNonce},
+    },
+    solana_program_test::{processor, tokio, ProgramTest},
+    solana_sdk::{
+        instruction::{Instruction, InstructionError},
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::TransactionError,
+    },
+};
+
+const SEED: &str = "hello";
+
+/// Signs `text` off-chain with `signer` for the message of `author`, returns the verification
+/// and `PostSigned` instructions of the relayer
+fn signed_post(
+    program_id: &Pubkey,
+    relayer: &Pubkey,
+    author: &Pubkey,
+    signer: &Keypair,
+    nonce: u64,
+    text: &str,
+) -> Vec<Instruction> {
+    let (message, _) = find_message_address(program_id, author, SEED);
+    let signed = signed_post_message(&message, nonce, text);
+    let signature: [u8; 64] = signer.sign_message(&signed).into();
+    vec![
+        verify_signature(author, &signature, &signed),
+        post_signed(program_id, relayer, author, SEED, nonce, None, text).unwrap(),
+    ]
+}
+
+#[tokio::test]
+async fn success_post_signed() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let relayer = context.payer.pubkey();
+    // the author has no lamports, the relayer pays for everything
+    let author = Keypair::new();
+    let (message, _) = find_message_address(&program_id, &author.pubkey(), SEED);
+
+    process(&mut context, &signed_post(&program_id, &relayer, &author.pubkey(), &author, 1, "hello"), &[])
+        .await
+        .unwrap();
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    let state = HelloWorldState::unpack(&account.data).unwrap();
+    assert_eq!(state.account_key, author.pubkey());
+    assert_eq!(state.message, "hello");
+    assert_eq!(state.edit_count, 1);
+    let (nonce, _) = find_nonce_address(&program_id, &author.pubkey(), 1);
+    let account = context.banks_client.get_account(nonce).await.unwrap().unwrap();
+    assert_eq!(
+        SignedNonce::unpack(&account.data).unwrap(),
+        SignedNonce {
+            is_initialized: true,
+            author: author.pubkey(),
+            nonce: 1,
+            message,
+        }
+    );
+
+    // the next nonce updates the existing message
+    process(&mut context, &signed_post(&program_id, &relayer, &author.pubkey(), &author, 2, "world"), &[])
+        .await
+        .unwrap();
+    let account = context.banks_client.get_account(message).await.unwrap().unwrap();
+    let state = HelloWorldState::unpack(&account.data).unwrap();
+    assert_eq!(state.message, "world");
+    assert_eq!(state.edit_count, 2);
+
+    // a signature is relayed once
+    assert_eq!(
+        process(&mut context, &signed_post(&program_id, &relayer, &author.pubkey(), &author, 1, "hello"), &[])
+            .await
+            .unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(HelloWorldError::NonceAlreadyUsed as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_post_signed_invalid_signature() {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(Processor::process));
+    let mut context = program_test.start_with_context().await;
+    let relayer = context.payer.pubkey();
+    let author = Keypair::new();
+    let (message, _) = find_message_address(&program_id, &author.pubkey(), SEED);
+
+    // without the verification of the signature
+    let instructions = signed_post(&program_id, &relayer, &author.pubkey(), &author, 1, "hello");
+    assert_eq!(
+        process(&mut context, &instructions[1..], &[]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(HelloWorldError::InvalidSignature as u32)
+        )
+    );
+
+    // with the verification of another message
+    let mut instructions = signed_post(&program_id, &relayer, &author.pubkey(), &author, 1, "hello");
+    instructions[1] = post_signed(&program_id, &relayer, &author.pubkey(), SEED, 1, None, "bye").unwrap();
+    assert_eq!(
+        process(&mut context, &instructions, &[]).await.unwrap_err(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(HelloWorldError::InvalidSignature as u32)
+        )
+    );
+
+    // the ed25519 program rejects a signature of another key before the transaction runs
+    let forger = Keypair::new();
+    let instructions = signed_post(&program_id, &relayer, &author.pubkey(), &forger, 1, "hello");
+    assert_eq!(
+        process(&mut context, &instructions, &[]).await.unwrap_err(),
+        TransactionError::InvalidAccountIndex
+    );
+    assert_eq!(context.banks_client.get_account(message).await.unwrap(), None);
+}
@@ -0,0 +1,172 @@
+//! End-to-end encryption of direct messages between Solana wallets.
+//!
+//! Both wallets convert their ed25519 keys to x25519 and agree on a shared secret, the
+//! payload is sealed with AES-256-GCM-SIV under a key derived from it. A sealed payload is
+//! the random nonce followed by the ciphertext and its tag.
+
+use aes_gcm_siv::{
+    aead::{Aead, NewAead, Payload},
+    Aes256GcmSiv, Nonce,
+};
+use curve25519_dalek::{edwards::CompressedEdwardsY, montgomery::MontgomeryPoint, scalar::Scalar};
+use rand::{thread_rng, RngCore};
+use sha2::{Digest, Sha256, Sha512};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use thiserror::Error;
+
+/// Length of the nonce at the start of a sealed payload
+pub const NONCE_LEN: usize = 12;
+
+/// Length of the authentication tag at the end of a sealed payload
+pub const TAG_LEN: usize = 16;
+
+/// Bytes added by `seal` to the plaintext
+pub const SEAL_OVERHEAD: usize = NONCE_LEN + TAG_LEN;
+
+/// Domain of the key derived from the shared secret, so it is not reused by another protocol
+const KEY_DOMAIN: &[u8] = b"hello-world direct message v1";
+
+/// Errors of the direct message helpers
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum DmError {
+    /// The public key is not a point of the curve
+    #[error("invalid public key")]
+    InvalidPublicKey,
+    /// The shared secret is zero, the public key has a small order
+    #[error("weak public key")]
+    WeakPublicKey,
+    /// The payload is shorter than its nonce and tag
+    #[error("payload too short")]
+    PayloadTooShort,
+    /// The payload was not sealed for these keys, or was tampered with
+    #[error("decryption failed")]
+    Decryption,
+    /// The plaintext could not be encrypted, it is too long for the cipher
+    #[error("encryption failed")]
+    Encryption,
+}
+
+/// Converts the secret of an ed25519 keypair to an x25519 secret scalar, clamped like the
+/// ed25519 signing scalar it is derived from.
+pub fn x25519_secret(keypair: &Keypair) -> [u8; 32] {
+    let hash = Sha512::digest(&keypair.to_bytes()[..32]);
+    let mut secret = [0; 32];
+    secret.copy_from_slice(&hash[..32]);
+    secret[0] &= 248;
+    secret[31] &= 127;
+    secret[31] |= 64;
+    secret
+}
+
+/// Converts an ed25519 public key to the x25519 public key of the same secret.
+pub fn x25519_public(pubkey: &Pubkey) -> Result<[u8; 32], DmError> {
+    let point = CompressedEdwardsY(pubkey.to_bytes())
+        .decompress()
+        .ok_or(DmError::InvalidPublicKey)?;
+    Ok(point.to_montgomery().to_bytes())
+}
+
+/// Derives the secret shared by `keypair` and the owner of `pubkey`, both sides get the same one.
+pub fn shared_secret(keypair: &Keypair, pubkey: &Pubkey) -> Result<[u8; 32], DmError> {
+    let scalar = Scalar::from_bits(x25519_secret(keypair));
+    let shared = (MontgomeryPoint(x25519_public(pubkey)?) * scalar).to_bytes();
+    // 低阶点得到的共享密钥是全零，任何人都能算出来
+    if shared == [0; 32] {
+        return Err(DmError::WeakPublicKey);
+    }
+    Ok(shared)
+}
+
+/// Key of the AEAD between `keypair` and `pubkey`, hashed from their shared secret.
+fn cipher(keypair: &Keypair, pubkey: &Pubkey) -> Result<Aes256GcmSiv, DmError> {
+    let shared = shared_secret(keypair, pubkey)?;
+    let key = Sha256::new().chain(KEY_DOMAIN).chain(shared).finalize();
+    Ok(Aes256GcmSiv::new(&key))
+}
+
+/// Additional data of a payload, binding it to its sender and recipient.
+fn associated_data(sender: &Pubkey, recipient: &Pubkey) -> Vec<u8> {
+    [sender.as_ref(), recipient.as_ref()].concat()
+}
+
+/// Encrypts `plaintext` from `sender` to `recipient`, the result is `SEAL_OVERHEAD` bytes longer.
+pub fn seal(sender: &Keypair, recipient: &Pubkey, plaintext: &[u8]) -> Result<Vec<u8>, DmError> {
+    let mut nonce = [0; NONCE_LEN];
+    thread_rng().fill_bytes(&mut nonce);
+    let aad = associated_data(&sender.pubkey(), recipient);
+    let ciphertext = cipher(sender, recipient)?
+        .encrypt(&Nonce::from(nonce), Payload { msg: plaintext, aad: &aad })
+        .map_err(|_| DmError::Encryption)?;
+
+    let mut payload = Vec::with_capacity(SEAL_OVERHEAD + plaintext.len());
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+    Ok(payload)
+}
+
+/// Decrypts a `payload` sealed by `sender` for `recipient`.
+pub fn open(recipient: &Keypair, sender: &Pubkey, payload: &[u8]) -> Result<Vec<u8>, DmError> {
+    if payload.len() < SEAL_OVERHEAD {
+        return Err(DmError::PayloadTooShort);
+    }
+    let (nonce_buf, ciphertext) = payload.split_at(NONCE_LEN);
+    let mut nonce = [0; NONCE_LEN];
+    nonce.copy_from_slice(nonce_buf);
+    let aad = associated_data(sender, &recipient.pubkey());
+    cipher(recipient, sender)?
+        .decrypt(&Nonce::from(nonce), Payload { msg: ciphertext, aad: &aad })
+        .map_err(|_| DmError::Decryption)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_secret() {
+        let (alice, bob) = (Keypair::new(), Keypair::new());
+        let secret = shared_secret(&alice, &bob.pubkey()).unwrap();
+        assert_eq!(secret, shared_secret(&bob, &alice.pubkey()).unwrap());
+        assert_ne!(secret, shared_secret(&alice, &Keypair::new().pubkey()).unwrap());
+
+        // 公钥转换和私钥转换对应的是同一个x25519密钥对
+        let public = (MontgomeryPoint(curve25519_dalek::constants::X25519_BASEPOINT.0)
+            * Scalar::from_bits(x25519_secret(&alice)))
+        .to_bytes();
+        assert_eq!(public, x25519_public(&alice.pubkey()).unwrap());
+
+        // 不在曲线上的点不能当作公钥
+        let invalid = (0..=u8::MAX)
+            .map(|byte| Pubkey::new_from_array([byte; 32]))
+            .find(|pubkey| CompressedEdwardsY(pubkey.to_bytes()).decompress().is_none())
+            .unwrap();
+        assert_eq!(x25519_public(&invalid).unwrap_err(), DmError::InvalidPublicKey);
+        // 单位元是低阶点
+        let mut identity = [0; 32];
+        identity[0] = 1;
+        assert_eq!(
+            shared_secret(&alice, &Pubkey::new_from_array(identity)).unwrap_err(),
+            DmError::WeakPublicKey
+        );
+    }
+
+    #[test]
+    fn test_seal_open() {
+        let (alice, bob, eve) = (Keypair::new(), Keypair::new(), Keypair::new());
+        let payload = seal(&alice, &bob.pubkey(), b"hello bob").unwrap();
+        assert_eq!(payload.len(), b"hello bob".len() + SEAL_OVERHEAD);
+        assert_eq!(open(&bob, &alice.pubkey(), &payload).unwrap(), b"hello bob");
+        // 同样的明文每次加密的结果不同
+        assert_ne!(payload, seal(&alice, &bob.pubkey(), b"hello bob").unwrap());
+
+        assert_eq!(open(&eve, &alice.pubkey(), &payload).unwrap_err(), DmError::Decryption);
+        assert_eq!(open(&bob, &eve.pubkey(), &payload).unwrap_err(), DmError::Decryption);
+        let mut tampered = payload.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(open(&bob, &alice.pubkey(), &tampered).unwrap_err(), DmError::Decryption);
+        assert_eq!(
+            open(&bob, &alice.pubkey(), &payload[..SEAL_OVERHEAD - 1]).unwrap_err(),
+            DmError::PayloadTooShort
+        );
+    }
+}
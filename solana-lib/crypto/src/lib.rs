@@ -3,6 +3,8 @@ Minted Ok(Keypair(Keypair { secret: SecretKey: [68, 211, 247, 214, 74, 106, 11,
         X: FieldElement51([270578207582189, 2131973742968173, 1906940198320206, 413430813510370, 2054329916012432]),
 */
 
+pub mod dm;
+
 #[cfg(test)]
 mod tests {
     use ed25519_dalek::SecretKey;
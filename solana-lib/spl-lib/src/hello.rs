@@ -0,0 +1,550 @@
+use anyhow::format_err;
+use helloworld::{
+    error::HelloWorldError,
+    event::HelloWorldEvent,
+    find_channel_page_address, find_chunk_address, find_direct_message_address, find_inbox_address,
+    state::{
+        Channel, ChannelPage, DirectMessage, HelloWorldState, Inbox, MessageChunk, Poll, PARENT_OFFSET,
+    },
+};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_request::MAX_MULTIPLE_ACCOUNTS,
+};
+use solana_program::{decode_error::DecodeError, program_error::ProgramError, program_pack::Pack};
+use solana_sdk::{instruction::InstructionError, pubkey::Pubkey, transaction::TransactionError};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+};
+
+/// Turns a `ProgramError::Custom` returned by the hello-world program back into the named error.
+pub fn decode_program_error(error: &ProgramError) -> Option<HelloWorldError> {
+    match error {
+        ProgramError::Custom(code) => HelloWorldError::decode_custom_error_to_enum(*code),
+        _ => None,
+    }
+}
+
+/// Same as `decode_program_error`, for the error of a failed hello-world transaction.
+pub fn decode_transaction_error(error: &TransactionError) -> Option<HelloWorldError> {
+    match error {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            HelloWorldError::decode_custom_error_to_enum(*code)
+        }
+        _ => None,
+    }
+}
+
+/// Puts a long message back together from its head account and its chunk accounts, in order.
+pub fn assemble_message(
+    message: &Pubkey,
+    head: &HelloWorldState,
+    chunks: &[MessageChunk],
+) -> Result<String, HelloWorldError> {
+    if chunks.len() != head.chunk_count as usize {
+        return Err(HelloWorldError::InvalidChunkAccount);
+    }
+    let mut text = head.message.clone();
+    for (index, chunk) in chunks.iter().enumerate() {
+        if chunk.message_account != *message || chunk.index as usize != index {
+            return Err(HelloWorldError::InvalidChunkAccount);
+        }
+        text.push_str(&chunk.data);
+    }
+    Ok(text)
+}
+
+/// Reads the full text of a message account, fetching its chunk accounts if it has any.
+/// The message may still grow while `finalized` is not set on the head account.
+pub fn read_message(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    message: &Pubkey,
+) -> anyhow::Result<String> {
+    let head = HelloWorldState::unpack(&client.get_account_data(message)?)?;
+    let chunk_addresses: Vec<Pubkey> = (0..head.chunk_count)
+        .map(|index| find_chunk_address(program_id, message, index).0)
+        .collect();
+
+    let mut chunks = Vec::with_capacity(chunk_addresses.len());
+    for addresses in chunk_addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        for (address, account) in addresses.iter().zip(client.get_multiple_accounts(addresses)?) {
+            let account = account.ok_or_else(|| format_err!("Missing chunk account {}", address))?;
+            chunks.push(MessageChunk::unpack(&account.data)?);
+        }
+    }
+    Ok(assemble_message(message, &head, &chunks)?)
+}
+
+/// A message of a discussion thread and the replies to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreadNode {
+    /// message account
+    pub message: Pubkey,
+    /// state of the message account
+    pub state: HelloWorldState,
+    /// replies to the message, oldest first
+    pub replies: Vec<ThreadNode>,
+}
+
+/// Arranges `messages` into the thread rooted at `root`. Messages which are not part of
+/// the thread are left out.
+pub fn build_thread(
+    root: Pubkey,
+    root_state: HelloWorldState,
+    messages: &[(Pubkey, HelloWorldState)],
+) -> ThreadNode {
+    let mut replies: HashMap<Pubkey, Vec<&(Pubkey, HelloWorldState)>> = HashMap::new();
+    for entry in messages {
+        if let Some(parent) = Option::<Pubkey>::from(entry.1.parent) {
+            replies.entry(parent).or_default().push(entry);
+        }
+    }
+    for siblings in replies.values_mut() {
+        siblings.sort_by_key(|(message, state)| (state.created_slot, *message));
+    }
+    let mut visited = HashSet::new();
+    build_node(root, root_state, &replies, &mut visited)
+}
+
+fn build_node(
+    message: Pubkey,
+    state: HelloWorldState,
+    replies: &HashMap<Pubkey, Vec<&(Pubkey, HelloWorldState)>>,
+    visited: &mut HashSet<Pubkey>,
+) -> ThreadNode {
+    visited.insert(message);
+    // the program never lets a thread loop back, the check only protects against bad input
+    let replies = replies
+        .get(&message)
+        .map(|siblings| {
+            siblings
+                .iter()
+                .filter_map(|(reply, state)| {
+                    if visited.contains(reply) {
+                        return None;
+                    }
+                    Some(build_node(*reply, state.clone(), replies, visited))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    ThreadNode {
+        message,
+        state,
+        replies,
+    }
+}
+
+/// Fetches the whole thread rooted at `root`, one `getProgramAccounts` request per message
+/// with replies. Replies whose account was erased are missing from the tree.
+pub fn fetch_thread(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    root: &Pubkey,
+) -> anyhow::Result<ThreadNode> {
+    let root_state = HelloWorldState::unpack(&client.get_account_data(root)?)?;
+    let mut messages = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = Vec::new();
+    if root_state.reply_count > 0 {
+        pending.push(*root);
+    }
+    while let Some(parent) = pending.pop() {
+        if !visited.insert(parent) {
+            continue;
+        }
+        // 父消息的COption：4字节的tag 1加上公钥
+        let mut parent_bytes = vec![1, 0, 0, 0];
+        parent_bytes.extend_from_slice(parent.as_ref());
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(HelloWorldState::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(PARENT_OFFSET, parent_bytes)),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        for (message, account) in client.get_program_accounts_with_config(program_id, config)? {
+            let state = HelloWorldState::unpack(&account.data)?;
+            if state.reply_count > 0 {
+                pending.push(message);
+            }
+            messages.push((message, state));
+        }
+    }
+    Ok(build_thread(*root, root_state, &messages))
+}
+
+/// Checks that `page` is the page account at `index` of the channel account.
+pub fn check_channel_page(
+    channel: &Pubkey,
+    index: u32,
+    page: &ChannelPage,
+) -> Result<(), HelloWorldError> {
+    if page.channel != *channel || page.index != index {
+        return Err(HelloWorldError::InvalidChannelAccount);
+    }
+    Ok(())
+}
+
+/// Reads the message accounts indexed by the page at `index` of a channel, oldest first.
+pub fn read_channel_page(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    channel: &Pubkey,
+    index: u32,
+) -> anyhow::Result<Vec<Pubkey>> {
+    let address = find_channel_page_address(program_id, channel, index).0;
+    let page = ChannelPage::unpack(&client.get_account_data(&address)?)?;
+    check_channel_page(channel, index, &page)?;
+    Ok(page.messages)
+}
+
+/// Iterates over the pages of a channel, oldest first, yielding the message accounts of
+/// each page. Messages posted after the pager was created are not visited once their page
+/// is past the last one known then.
+pub struct ChannelPager<'a> {
+    client: &'a RpcClient,
+    program_id: Pubkey,
+    channel: Pubkey,
+    next_page: u32,
+    page_count: u32,
+}
+
+impl<'a> ChannelPager<'a> {
+    /// Reads the channel account to learn how many pages it has.
+    pub fn new(client: &'a RpcClient, program_id: &Pubkey, channel: &Pubkey) -> anyhow::Result<Self> {
+        let state = Channel::unpack(&client.get_account_data(channel)?)?;
+        Ok(ChannelPager {
+            client,
+            program_id: *program_id,
+            channel: *channel,
+            next_page: 0,
+            page_count: u32::try_from(state.page_count())?,
+        })
+    }
+
+    /// Number of pages of the channel when the pager was created.
+    pub fn page_count(&self) -> u32 {
+        self.page_count
+    }
+
+    /// Skips to the page at `index`, e.g. to resume reading a channel.
+    pub fn seek(&mut self, index: u32) {
+        self.next_page = index;
+    }
+}
+
+impl Iterator for ChannelPager<'_> {
+    type Item = anyhow::Result<Vec<Pubkey>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_page >= self.page_count {
+            return None;
+        }
+        let index = self.next_page;
+        self.next_page += 1;
+        Some(read_channel_page(self.client, &self.program_id, &self.channel, index))
+    }
+}
+
+/// Reads the direct messages received by `recipient` from the position `start` of its inbox,
+/// oldest first. The payloads are still sealed, see `crypto::dm::open`.
+pub fn read_inbox(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    recipient: &Pubkey,
+    start: u64,
+) -> anyhow::Result<Vec<(Pubkey, DirectMessage)>> {
+    let inbox_address = find_inbox_address(program_id, recipient).0;
+    // 还没收到过私信的钱包没有收件箱账号
+    let message_count = match client.get_multiple_accounts(&[inbox_address])?.pop().flatten() {
+        Some(account) => Inbox::unpack(&account.data)?.message_count,
+        None => return Ok(vec![]),
+    };
+    let addresses: Vec<Pubkey> = (start..message_count)
+        .map(|index| find_direct_message_address(program_id, recipient, index).0)
+        .collect();
+
+    let mut messages = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        for (address, account) in chunk.iter().zip(client.get_multiple_accounts(chunk)?) {
+            let account = account.ok_or_else(|| format_err!("Missing direct message account {}", address))?;
+            messages.push((*address, DirectMessage::unpack(&account.data)?));
+        }
+    }
+    Ok(messages)
+}
+
+/// Tally of an option in [PollResults](struct.PollResults.html)
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionResult {
+    /// label of the option
+    pub label: String,
+    /// sum of the weights of the votes for the option
+    pub votes: u64,
+    /// share of all the votes, in basis points rounded down
+    pub share_bps: u16,
+}
+
+/// Results of a poll, in the order of its options.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PollResults {
+    /// question of the poll
+    pub question: String,
+    /// tally of every option
+    pub options: Vec<OptionResult>,
+    /// number of wallets which voted
+    pub voter_count: u64,
+    /// indexes of the options with the most votes, several in case of a tie, none without votes
+    pub leaders: Vec<usize>,
+}
+
+impl From<&Poll> for PollResults {
+    fn from(poll: &Poll) -> Self {
+        let total = poll.total_votes();
+        let options = poll
+            .options
+            .iter()
+            .map(|option| OptionResult {
+                label: option.label.clone(),
+                votes: option.votes,
+                // 份额不超过10000，转换不会失败
+                share_bps: (option.votes as u128 * 10_000)
+                    .checked_div(total)
+                    .map_or(0, |share| share as u16),
+            })
+            .collect();
+        let most = poll.options.iter().map(|option| option.votes).max().unwrap_or(0);
+        let leaders = if most == 0 {
+            vec![]
+        } else {
+            (0..poll.options.len()).filter(|index| poll.options[*index].votes == most).collect()
+        };
+        PollResults {
+            question: poll.question.clone(),
+            options,
+            voter_count: poll.voter_count,
+            leaders,
+        }
+    }
+}
+
+/// Reads the current results of a poll account.
+pub fn read_poll_results(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    poll: &Pubkey,
+) -> anyhow::Result<PollResults> {
+    let account = client.get_account(poll)?;
+    if account.owner != *program_id {
+        return Err(HelloWorldError::IncorrectAccountOwner.into());
+    }
+    Ok(PollResults::from(&Poll::unpack(&account.data)?))
+}
+
+/// Pulls the events logged by the hello-world program out of the log messages of a
+/// transaction, in order. Lines logged by other programs, including programs invoked by
+/// the hello-world program, are skipped so they can not forge events.
+pub fn decode_events(program_id: &Pubkey, log_messages: &[String]) -> Vec<HelloWorldEvent> {
+    let program_id = program_id.to_string();
+    let mut invoked = Vec::new();
+    let mut events = Vec::new();
+    for log in log_messages {
+        let words: Vec<&str> = log.split_whitespace().collect();
+        match words.as_slice() {
+            ["Program", program, "invoke", _depth] => invoked.push(program.to_string()),
+            ["Program", _program, "success"] => {
+                invoked.pop();
+            }
+            ["Program", _program, "failed:", ..] => {
+                invoked.pop();
+            }
+            _ if invoked.last() == Some(&program_id) => {
+                events.extend(HelloWorldEvent::from_log(log));
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helloworld::state::{PollOption, Timepoint};
+    use solana_program::program_option::COption;
+
+    #[test]
+    fn test_decode_program_error() {
+        let error: ProgramError = HelloWorldError::MessageTooLong.into();
+        assert_eq!(decode_program_error(&error), Some(HelloWorldError::MessageTooLong));
+        assert_eq!(decode_program_error(&ProgramError::Custom(u32::MAX)), None);
+        assert_eq!(decode_program_error(&ProgramError::InvalidArgument), None);
+    }
+
+    #[test]
+    fn test_decode_transaction_error() {
+        let error = TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(HelloWorldError::Unauthorized as u32),
+        );
+        assert_eq!(decode_transaction_error(&error), Some(HelloWorldError::Unauthorized));
+        assert_eq!(decode_transaction_error(&TransactionError::AccountNotFound), None);
+    }
+
+    #[test]
+    fn test_decode_events() {
+        let program_id = Pubkey::new_unique();
+        let other_program_id = Pubkey::new_unique();
+        let posted = HelloWorldEvent::MessagePosted {
+            message: Pubkey::new_unique(),
+            poster: Pubkey::new_unique(),
+            edit_count: 1,
+            text: "hello".to_string(),
+        };
+        let forged = HelloWorldEvent::PauseChanged { paused: true };
+        let logs: Vec<String> = vec![
+            format!("Program {} invoke [1]", program_id),
+            "Program log: hello-world: HelloWorld".to_string(),
+            format!("Program {} invoke [2]", other_program_id),
+            format!("Program log: {}", forged.to_log()),
+            format!("Program {} success", other_program_id),
+            format!("Program log: {}", posted.to_log()),
+            format!("Program {} consumed 4242 of 200000 compute units", program_id),
+            format!("Program {} success", program_id),
+            format!("Program {} invoke [1]", other_program_id),
+            format!("Program log: {}", forged.to_log()),
+            format!("Program {} success", other_program_id),
+        ];
+        assert_eq!(decode_events(&program_id, &logs), vec![posted]);
+        assert_eq!(decode_events(&other_program_id, &logs), vec![forged.clone(), forged]);
+    }
+
+    #[test]
+    fn test_build_thread() {
+        let message = |parent: Option<Pubkey>, created_slot| HelloWorldState {
+            parent: parent.into(),
+            created_slot,
+            ..HelloWorldState::default()
+        };
+        let root = Pubkey::new_unique();
+        let (first, second, nested, unrelated) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let messages = vec![
+            (nested, message(Some(first), 3)),
+            (second, message(Some(root), 2)),
+            (unrelated, message(Some(Pubkey::new_unique()), 1)),
+            (first, message(Some(root), 1)),
+            // a reply to the root can not show up again deeper in the tree
+            (root, message(Some(nested), 0)),
+        ];
+        let thread = build_thread(root, message(None, 0), &messages);
+        assert_eq!(thread.message, root);
+        let replies: Vec<Pubkey> = thread.replies.iter().map(|node| node.message).collect();
+        assert_eq!(replies, vec![first, second]);
+        assert_eq!(thread.replies[0].replies.len(), 1);
+        assert_eq!(thread.replies[0].replies[0].message, nested);
+        assert!(thread.replies[0].replies[0].replies.is_empty());
+        assert!(thread.replies[1].replies.is_empty());
+    }
+
+    #[test]
+    fn test_check_channel_page() {
+        let channel = Pubkey::new_unique();
+        let page = ChannelPage {
+            is_initialized: true,
+            channel,
+            index: 1,
+            messages: vec![Pubkey::new_unique()],
+        };
+        assert_eq!(check_channel_page(&channel, 1, &page), Ok(()));
+        assert_eq!(
+            check_channel_page(&channel, 0, &page),
+            Err(HelloWorldError::InvalidChannelAccount)
+        );
+        assert_eq!(
+            check_channel_page(&Pubkey::new_unique(), 1, &page),
+            Err(HelloWorldError::InvalidChannelAccount)
+        );
+    }
+
+    #[test]
+    fn test_poll_results() {
+        let option = |label: &str, votes| PollOption {
+            label: label.to_string(),
+            votes,
+        };
+        let mut poll = Poll {
+            is_initialized: true,
+            creator: Pubkey::new_unique(),
+            question: "tabs or spaces?".to_string(),
+            options: vec![option("tabs", 0), option("spaces", 0), option("both", 0)],
+            opens_at: Timepoint::Slot(0),
+            closes_at: Timepoint::Slot(10),
+            vote_mint: COption::None,
+            voter_count: 0,
+        };
+        let results = PollResults::from(&poll);
+        assert_eq!(results.question, "tabs or spaces?");
+        assert!(results.options.iter().all(|option| option.share_bps == 0));
+        assert!(results.leaders.is_empty());
+
+        poll.options = vec![option("tabs", 2), option("spaces", 2), option("both", 2)];
+        poll.voter_count = 3;
+        let results = PollResults::from(&poll);
+        assert_eq!(results.voter_count, 3);
+        assert_eq!(results.leaders, [0, 1, 2]);
+        assert!(results.options.iter().all(|option| option.share_bps == 3333));
+
+        poll.options[1].votes = u64::MAX;
+        let results = PollResults::from(&poll);
+        assert_eq!(results.leaders, [1]);
+        assert_eq!(results.options[1].votes, u64::MAX);
+        assert_eq!(results.options[1].share_bps, 9999);
+    }
+
+    #[test]
+    fn test_assemble_message() {
+        let message = Pubkey::new_unique();
+        let head = HelloWorldState {
+            chunk_count: 2,
+            message: "hello ".to_string(),
+            ..HelloWorldState::default()
+        };
+        let chunk = |index: u32, data: &str| MessageChunk {
+            is_initialized: true,
+            message_account: message,
+            index,
+            data: data.to_string(),
+        };
+        let chunks = vec![chunk(0, "long "), chunk(1, "world!")];
+        assert_eq!(assemble_message(&message, &head, &chunks).unwrap(), "hello long world!");
+
+        assert_eq!(
+            assemble_message(&message, &head, &chunks[..1]).unwrap_err(),
+            HelloWorldError::InvalidChunkAccount
+        );
+        let swapped = vec![chunks[1].clone(), chunks[0].clone()];
+        assert_eq!(
+            assemble_message(&message, &head, &swapped).unwrap_err(),
+            HelloWorldError::InvalidChunkAccount
+        );
+        assert_eq!(
+            assemble_message(&Pubkey::new_unique(), &head, &chunks).unwrap_err(),
+            HelloWorldError::InvalidChunkAccount
+        );
+    }
+}
@@ -1,5 +1,6 @@
 pub mod accounts;
 pub mod errors;
+pub mod hello;
 pub mod state;
 pub mod tokens;
 